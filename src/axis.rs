@@ -0,0 +1,171 @@
+use crate::*;
+
+/// How an axis of a plot is drawn, as set for an experiment in the config
+/// (or on the command line)
+///
+/// The bounds and the tick spacing are in the units of the experiment (e.g., `v_units`).
+/// On logarithmic axes, the tick spacing is the factor between consecutive ticks (10 by default)
+#[derive(Clone, Copy, Debug, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct AxisStyle {
+    /// Whether the axis is in a logarithmic scale
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) log: bool,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) min: Option<f64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) max: Option<f64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) tick_spacing: Option<f64>,
+
+    /// Whether the axis goes from the largest value to the smallest one
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) reverse: bool,
+}
+
+// the experiments are hashed (and compared) when merging configs: the bounds are compared bitwise
+impl Eq for AxisStyle {}
+
+impl std::hash::Hash for AxisStyle {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.log.hash(state);
+        self.min.map(f64::to_bits).hash(state);
+        self.max.map(f64::to_bits).hash(state);
+        self.tick_spacing.map(f64::to_bits).hash(state);
+        self.reverse.hash(state);
+    }
+}
+
+impl AxisStyle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn log(mut self, log: bool) -> Self {
+        self.log = log;
+        self
+    }
+
+    pub fn range(mut self, min: Option<f64>, max: Option<f64>) -> Self {
+        self.min = min;
+        self.max = max;
+        self
+    }
+
+    pub fn tick_spacing(mut self, tick_spacing: Option<f64>) -> Self {
+        self.tick_spacing = tick_spacing;
+        self
+    }
+
+    pub fn reverse(mut self, reverse: bool) -> Self {
+        self.reverse = reverse;
+        self
+    }
+
+    /// Override this style with another one (e.g., from the command line)
+    ///
+    /// Only the options set by the other style are overridden
+    pub fn overridden_by(self, other: AxisStyle) -> Self {
+        AxisStyle {
+            log: self.log || other.log,
+            min: other.min.or(self.min),
+            max: other.max.or(self.max),
+            tick_spacing: other.tick_spacing.or(self.tick_spacing),
+            reverse: self.reverse || other.reverse,
+        }
+    }
+
+    /// What is wrong with the style, if anything
+    pub(crate) fn problem(&self) -> Option<String> {
+        match (self.min, self.max, self.tick_spacing) {
+            (Some(min), Some(max), _) if min >= max => Some(format!(
+                "the minimum ({}) is not below the maximum ({})",
+                min, max
+            )),
+            (Some(min), _, _) if self.log && min <= 0.0 => Some(format!(
+                "the minimum ({}) of a logarithmic axis is not positive",
+                min
+            )),
+            (_, Some(max), _) if self.log && max <= 0.0 => Some(format!(
+                "the maximum ({}) of a logarithmic axis is not positive",
+                max
+            )),
+            (_, _, Some(spacing)) if self.log && spacing <= 1.0 => Some(format!(
+                "the tick spacing ({}) of a logarithmic axis is not above 1",
+                spacing
+            )),
+            (_, _, Some(spacing)) if spacing <= 0.0 => {
+                Some(format!("the tick spacing ({}) is not positive", spacing))
+            }
+            _ => None,
+        }
+    }
+
+    pub fn validate(&self) -> BencherResult<()> {
+        match self.problem() {
+            Some(problem) => Err(BencherError::InvalidAxis(problem)),
+            None => Ok(()),
+        }
+    }
+
+    /// gnuplot commands setting up an axis (`x` or `y`) whose values are displayed in a scale
+    ///
+    /// Bounds which are not set default to `default_range` (on linear axes) or are autoscaled
+    pub(crate) fn gnuplot(
+        &self,
+        axis: &str,
+        scale: &Scale,
+        default_range: (Option<f64>, Option<f64>),
+    ) -> String {
+        let mut commands = String::new();
+        if self.log {
+            commands.push_str(&format!("set logscale {}\n", axis));
+        }
+
+        let bound = |v: Option<f64>, default: Option<f64>| match v
+            .map(|v| scale.scale(v))
+            .or(default.filter(|_| !self.log))
+        {
+            Some(v) => v.to_string(),
+            None => "*".to_string(),
+        };
+        let (min, max) = (
+            bound(self.min, default_range.0),
+            bound(self.max, default_range.1),
+        );
+        match (self.reverse, min == "*" || max == "*") {
+            (true, false) => commands.push_str(&format!("set {}range [{}:{}]\n", axis, max, min)),
+            (true, true) => {
+                commands.push_str(&format!("set {}range [{}:{}] reverse\n", axis, min, max))
+            }
+            (false, _) => commands.push_str(&format!("set {}range [{}:{}]\n", axis, min, max)),
+        }
+
+        if let Some(spacing) = self.tick_spacing {
+            let spacing = if self.log {
+                spacing
+            } else {
+                scale.scale(spacing)
+            };
+            commands.push_str(&format!("set {}tics {}\n", axis, spacing));
+        }
+        commands
+    }
+
+    /// Axis drawn by the renderer, in the displayed units of a scale
+    pub(crate) fn plot_axis(&self, scale: &Scale) -> PlotAxis {
+        PlotAxis {
+            log: self.log,
+            reverse: self.reverse,
+            min: self.min.map(|v| scale.scale(v)),
+            max: self.max.map(|v| scale.scale(v)),
+            tick_spacing: if self.log {
+                self.tick_spacing
+            } else {
+                self.tick_spacing.map(|v| scale.scale(v))
+            },
+        }
+    }
+}
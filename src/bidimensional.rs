@@ -1,3 +1,4 @@
+use std::collections::BTreeSet;
 use std::fs::File;
 use std::io::Write;
 
@@ -18,73 +19,24 @@ pub struct XYExperimentView {
     x_units: String,
     y_label: String,
     y_units: String,
+    x_format: AxisFormat,
+    y_format: AxisFormat,
+    x_axis: AxisStyle,
+    y_axis: AxisStyle,
+    style: PlotStyle,
 }
 
-/// Choose a magnitude based on a given iterator of LinearExperimentSets
-fn choose_magnitude<'a>(
-    lines: impl Iterator<Item = &'a XYExperimentLine>,
-) -> (Magnitude, Magnitude) {
-    let mut x_magnitude_counts = [0; 7];
-    let mut y_magnitude_counts = [0; 7];
-
-    lines.for_each(|lines| {
-        lines.values.iter().for_each(|d| {
-            let (x_mag, y_mag) = d.magnitudes();
-            match x_mag {
-                Magnitude::Nano => x_magnitude_counts[0] += 1,
-                Magnitude::Micro => x_magnitude_counts[1] += 1,
-                Magnitude::Mili => x_magnitude_counts[2] += 1,
-                Magnitude::Normal => x_magnitude_counts[3] += 1,
-                Magnitude::Kilo => x_magnitude_counts[4] += 1,
-                Magnitude::Mega => x_magnitude_counts[5] += 1,
-                Magnitude::Giga => x_magnitude_counts[6] += 1,
-            };
-            match y_mag {
-                Magnitude::Nano => y_magnitude_counts[0] += 1,
-                Magnitude::Micro => y_magnitude_counts[1] += 1,
-                Magnitude::Mili => y_magnitude_counts[2] += 1,
-                Magnitude::Normal => y_magnitude_counts[3] += 1,
-                Magnitude::Kilo => y_magnitude_counts[4] += 1,
-                Magnitude::Mega => y_magnitude_counts[5] += 1,
-                Magnitude::Giga => y_magnitude_counts[6] += 1,
-            };
-        })
-    });
-
-    let x_idx = x_magnitude_counts
-        .iter()
-        .enumerate()
-        .max_by_key(|v| v.1)
-        .map(|(idx, c)| if *c > 0 { idx } else { 3 })
-        .unwrap();
-
-    let y_idx = y_magnitude_counts
-        .iter()
-        .enumerate()
-        .max_by_key(|v| v.1)
-        .map(|(idx, c)| if *c > 0 { idx } else { 3 })
-        .unwrap();
-
-    let x_mag = match x_idx {
-        0 => Magnitude::Nano,
-        1 => Magnitude::Micro,
-        2 => Magnitude::Mili,
-        3 => Magnitude::Normal,
-        4 => Magnitude::Kilo,
-        5 => Magnitude::Mega,
-        _ => Magnitude::Giga,
-    };
-    let y_mag = match y_idx {
-        0 => Magnitude::Nano,
-        1 => Magnitude::Micro,
-        2 => Magnitude::Mili,
-        3 => Magnitude::Normal,
-        4 => Magnitude::Kilo,
-        5 => Magnitude::Mega,
-        _ => Magnitude::Giga,
-    };
-
-    (x_mag, y_mag)
+/// Choose the scales of the x and y values of a given iterator of XYExperimentLines
+fn choose_scales<'a>(
+    lines: impl Iterator<Item = &'a XYExperimentLine> + Clone,
+    (x_units, y_units): (&str, &str),
+    (x_format, y_format): (&AxisFormat, &AxisFormat),
+) -> (Scale, Scale) {
+    let datapoints = lines.flat_map(|line| line.values.iter());
+    (
+        Scale::choose(x_units, datapoints.clone().map(|d| d.x), x_format),
+        Scale::choose(y_units, datapoints.map(|d| d.y), y_format),
+    )
 }
 
 impl XYExperimentView {
@@ -101,6 +53,11 @@ impl XYExperimentView {
                 x_units: experiment.x_units.clone(),
                 y_label: experiment.y_label.clone(),
                 y_units: experiment.y_units.clone(),
+                x_format: experiment.x_format.unwrap_or_default(),
+                y_format: experiment.y_format.unwrap_or_default(),
+                x_axis: experiment.x_axis.unwrap_or_default(),
+                y_axis: experiment.y_axis.unwrap_or_default(),
+                style: PlotStyle::default(),
             })
         }
     }
@@ -118,9 +75,64 @@ impl XYExperimentView {
                 x_units: experiment.x_units.clone(),
                 y_label: experiment.y_label.clone(),
                 y_units: experiment.y_units.clone(),
+                x_format: experiment.x_format.unwrap_or_default(),
+                y_format: experiment.y_format.unwrap_or_default(),
+                x_axis: experiment.x_axis.unwrap_or_default(),
+                y_axis: experiment.y_axis.unwrap_or_default(),
+                style: PlotStyle::default(),
             })
         }
     }
+
+    /// Override the format of the x values (e.g., from the command line)
+    pub(crate) fn with_x_format(mut self, x_format: AxisFormat) -> Self {
+        self.x_format = self.x_format.overridden_by(x_format);
+        self
+    }
+
+    /// Override the format of the y values (e.g., from the command line)
+    pub(crate) fn with_y_format(mut self, y_format: AxisFormat) -> Self {
+        self.y_format = self.y_format.overridden_by(y_format);
+        self
+    }
+
+    /// Override the style of the x axis (e.g., from the command line)
+    pub(crate) fn with_x_axis(mut self, x_axis: AxisStyle) -> Self {
+        self.x_axis = self.x_axis.overridden_by(x_axis);
+        self
+    }
+
+    /// Override the style of the y axis (e.g., from the command line)
+    pub(crate) fn with_y_axis(mut self, y_axis: AxisStyle) -> Self {
+        self.y_axis = self.y_axis.overridden_by(y_axis);
+        self
+    }
+
+    /// Set the style of the plots (from the config and the command line)
+    pub(crate) fn with_style(mut self, style: PlotStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Styles of the lines, by label
+    fn series_styles(&self) -> Vec<SeriesStyle> {
+        let labels = self
+            .lines
+            .iter()
+            .map(|line| line.line_label.as_str())
+            .collect::<Vec<_>>();
+        self.style
+            .series_styles(&labels, |slot| 2 * slot as u32 + 4, Some(2))
+    }
+
+    /// Scales of the x and y values
+    fn scales(&self) -> (Scale, Scale) {
+        choose_scales(
+            self.lines.iter(),
+            (&self.x_units, &self.y_units),
+            (&self.x_format, &self.y_format),
+        )
+    }
 }
 
 impl ExperimentView for XYExperimentView {
@@ -129,27 +141,13 @@ impl ExperimentView for XYExperimentView {
         if !gnu_path.set_extension("gnu") {
             return Err(BencherError::PathCreateError(gnu_path, "gnu".to_string()));
         }
-        let mut eps_path: std::path::PathBuf = prefix.into();
-        if !eps_path.set_extension("eps") {
-            return Err(BencherError::PathCreateError(eps_path, "eps".to_string()));
-        }
+        let output_path = self.style.output_path(prefix)?;
 
         let mut file = File::create(&gnu_path).map_err(|e| {
             BencherError::io_err(e, format!("creating {}", gnu_path.to_string_lossy()))
         })?;
-        write!(
-            &mut file,
-            "reset
-
-set terminal postscript eps colour size 12cm,8cm enhanced font 'Helvetica,20'
-set output '{}'
-
-set border linewidth 0.75
-set key outside above
-",
-            eps_path.to_string_lossy()
-        )
-        .map_err(|e| BencherError::io_err(e, "writing gnu to file"))?;
+        write!(&mut file, "{}", self.style.gnuplot_preamble(&output_path))
+            .map_err(|e| BencherError::io_err(e, "writing gnu to file"))?;
 
         let dat_paths = self
             .lines
@@ -171,38 +169,40 @@ set key outside above
             })
             .collect::<BencherResult<Vec<_>>>()?;
 
-        for (idx, _) in self.lines.iter().enumerate() {
+        for (idx, series) in self.series_styles().iter().enumerate() {
             writeln!(
                 &mut file,
-"# Set color of linestyle {0} to #{3}
-set style line {0} linecolor rgb '#{3}' linetype 2 linewidth 2.5 pointtype {2} pointsize 2 dashtype 2
-# Set yerror color of linestyle {1} to #{2}
-set style line {1} linecolor rgb '#{3}' linetype 2 linewidth 2.5 pointtype {2} pointsize 2",
+                "# Set color of linestyle {0} to #{3}
+set style line {0} linecolor rgb '#{3}' linetype 2 linewidth {4} pointtype {2} pointsize {5}{6}
+# Set yerror color of linestyle {1} to #{3}
+set style line {1} linecolor rgb '#{3}' linetype 2 linewidth {4} pointtype {2} pointsize {5}",
                 2 * idx + 1,
                 2 * idx + 2,
-                2 * idx + 4,
-                COLORS[idx % COLORS.len()]
-                ).map_err(|e| BencherError::io_err(e, "writing gnu to file"))?;
+                series.point_type,
+                series.color,
+                self.style.line_width_or(2.5),
+                self.style.point_size_or(2.0),
+                series.gnuplot_dash_type(),
+            )
+            .map_err(|e| BencherError::io_err(e, "writing gnu to file"))?;
         }
 
-        let (x_mag, y_mag) = choose_magnitude(self.lines.iter());
+        let (x_scale, y_scale) = self.scales();
         write!(
             file,
             "
 
 # set axis
 set tics scale 0.75
-set xlabel '{} ({}{})'
-set ylabel '{} ({}{})'
-set xrange [*:*]
-set yrange [*:*]
-",
+set xlabel '{} ({})'
+set ylabel '{} ({})'
+{}{}",
             self.x_label,
-            x_mag.prefix(),
-            self.x_units,
+            x_scale.units(),
             self.y_label,
-            y_mag.prefix(),
-            self.y_units,
+            y_scale.units(),
+            self.x_axis.gnuplot("x", &x_scale, (None, None)),
+            self.y_axis.gnuplot("y", &y_scale, (None, None)),
         )
         .map_err(|e| BencherError::io_err(e, "writing gnu to file"))?;
 
@@ -250,7 +250,7 @@ set yrange [*:*]
     }
 
     fn dat(&self, prefix: &std::path::Path, bar: Bars) -> BencherResult<()> {
-        let (x_mag, y_mag) = choose_magnitude(self.lines.iter());
+        let (x_scale, y_scale) = self.scales();
         for line in &self.lines {
             let mut dat_path: std::path::PathBuf = prefix.into();
             dat_path.set_file_name(format!(
@@ -271,14 +271,12 @@ set yrange [*:*]
 
             writeln!(
                 &mut file,
-                "# {}\n# x axis: {} ({}{})\n# y axis: {} ({}{})\n",
+                "# {}\n# x axis: {} ({})\n# y axis: {} ({})\n",
                 line.line_label,
                 self.x_label,
-                x_mag.prefix(),
-                self.x_units,
+                x_scale.units(),
                 self.y_label,
-                y_mag.prefix(),
-                self.y_units
+                y_scale.units(),
             )
             .map_err(|e| BencherError::io_err(e, "writing dat file"))?;
 
@@ -286,21 +284,20 @@ set yrange [*:*]
                 write!(
                     &mut file,
                     "{:>8} {:>8}",
-                    d.x.display_with_magnitude(x_mag),
-                    d.y.display_with_magnitude(y_mag)
+                    d.x.display_with_scale(&x_scale),
+                    d.y.display_with_scale(&y_scale)
                 )
                 .map_err(|e| BencherError::io_err(e, "writing dat file"))?;
 
                 match bar {
                     Bars::X(c) | Bars::XY(c, _) => {
-                        let (xmin, xmax) = d
-                            .get_x_confidence(c.try_into()?)
-                            .unwrap_or((d.x.clone(), d.x.clone()));
+                        let (xmin, xmax) =
+                            d.get_x_confidence(c).unwrap_or((d.x.clone(), d.x.clone()));
                         write!(
                             &mut file,
                             " {:>8} {:>8}",
-                            xmin.display_with_magnitude(x_mag),
-                            xmax.display_with_magnitude(x_mag)
+                            xmin.display_with_scale(&x_scale),
+                            xmax.display_with_scale(&x_scale)
                         )
                         .map_err(|e| BencherError::io_err(e, "writing dat file"))?;
                     }
@@ -309,14 +306,13 @@ set yrange [*:*]
 
                 match bar {
                     Bars::Y(c) | Bars::XY(_, c) => {
-                        let (ymin, ymax) = d
-                            .get_y_confidence(c.try_into()?)
-                            .unwrap_or((d.y.clone(), d.y.clone()));
+                        let (ymin, ymax) =
+                            d.get_y_confidence(c).unwrap_or((d.y.clone(), d.y.clone()));
                         write!(
                             &mut file,
                             " {:>8} {:>8}",
-                            ymin.display_with_magnitude(y_mag),
-                            ymax.display_with_magnitude(y_mag)
+                            ymin.display_with_scale(&y_scale),
+                            ymax.display_with_scale(&y_scale)
                         )
                         .map_err(|e| BencherError::io_err(e, "writing dat file"))?;
                     }
@@ -332,8 +328,149 @@ set yrange [*:*]
         Ok(())
     }
 
+    fn image(&self, prefix: &std::path::Path, format: ImageFormat, bar: Bars) -> BencherResult<()> {
+        let (x_scale, y_scale) = self.scales();
+        let scaled = |(min, max): (Value, Value), scale: &Scale| {
+            (scale.scale(min.as_float()), scale.scale(max.as_float()))
+        };
+
+        let lines = self
+            .lines
+            .iter()
+            .zip(self.series_styles())
+            .map(|(line, style)| Line {
+                label: line.line_label.clone(),
+                style,
+                points: line
+                    .values
+                    .iter()
+                    .map(|d| LinePoint {
+                        x: x_scale.scale(d.x.as_float()),
+                        y: y_scale.scale(d.y.as_float()),
+                        x_bar: match bar {
+                            Bars::X(c) | Bars::XY(c, _) => {
+                                d.get_x_confidence(c).map(|b| scaled(b, &x_scale))
+                            }
+                            _ => None,
+                        },
+                        y_bar: match bar {
+                            Bars::Y(c) | Bars::XY(_, c) => {
+                                d.get_y_confidence(c).map(|b| scaled(b, &y_scale))
+                            }
+                            _ => None,
+                        },
+                    })
+                    .collect(),
+                joined: true,
+            })
+            .collect::<Vec<_>>();
+
+        render_lines(
+            prefix,
+            format,
+            (
+                &format!("{} ({})", self.x_label, x_scale.units()),
+                &format!("{} ({})", self.y_label, y_scale.units()),
+            ),
+            &lines,
+            None,
+            (
+                &self.x_axis.plot_axis(&x_scale),
+                &self.y_axis.plot_axis(&y_scale),
+            ),
+            &self.style,
+        )
+    }
+
+    fn json<W: Write>(&self, writer: &mut W) -> BencherResult<()> {
+        let (x_scale, y_scale) = self.scales();
+        let lines = self
+            .lines
+            .iter()
+            .map(|line| {
+                serde_json::json!({
+                    "label": line.line_label,
+                    "values": line.values.iter().map(|d| serde_json::json!({
+                        "tag": d.tag,
+                        "x": json_value(&d.x),
+                        "x_confidences": json_confidences(&d.x_confidence),
+                        "y": json_value(&d.y),
+                        "y_confidences": json_confidences(&d.y_confidence),
+                    })).collect::<Vec<_>>(),
+                })
+            })
+            .collect::<Vec<_>>();
+
+        write_json(
+            writer,
+            &serde_json::json!({
+                "x_label": self.x_label,
+                "x_units": self.x_units,
+                "x_display_magnitude": x_scale.prefix(),
+                "x_display_units": x_scale.units(),
+                "y_label": self.y_label,
+                "y_units": self.y_units,
+                "y_display_magnitude": y_scale.prefix(),
+                "y_display_units": y_scale.units(),
+                "lines": lines,
+            }),
+        )
+    }
+
+    fn csv<W: Write>(&self, writer: &mut W) -> BencherResult<()> {
+        let (x_scale, y_scale) = self.scales();
+        let datapoints = || self.lines.iter().flat_map(|line| line.values.iter());
+        let x_confidences = datapoints()
+            .flat_map(|d| d.x_confidence.keys().copied())
+            .collect::<BTreeSet<_>>();
+        let y_confidences = datapoints()
+            .flat_map(|d| d.y_confidence.keys().copied())
+            .collect::<BTreeSet<_>>();
+
+        let mut csv_writer = csv::Writer::from_writer(writer);
+        let mut header = vec![
+            "line".to_string(),
+            "tag".to_string(),
+            "x".to_string(),
+            "x_units".to_string(),
+            "x_display_magnitude".to_string(),
+            "x_display_units".to_string(),
+            "y".to_string(),
+            "y_units".to_string(),
+            "y_display_magnitude".to_string(),
+            "y_display_units".to_string(),
+        ];
+        header.extend(csv_confidence_header(&x_confidences, "x "));
+        header.extend(csv_confidence_header(&y_confidences, "y "));
+        csv_writer.write_record(&header)?;
+
+        for line in &self.lines {
+            for d in &line.values {
+                let mut record = vec![
+                    line.line_label.clone(),
+                    d.tag.map_or(String::new(), |t| t.to_string()),
+                    d.x.to_string(),
+                    self.x_units.clone(),
+                    x_scale.prefix().to_string(),
+                    x_scale.units(),
+                    d.y.to_string(),
+                    self.y_units.clone(),
+                    y_scale.prefix().to_string(),
+                    y_scale.units(),
+                ];
+                record.extend(csv_confidence_cells(&d.x_confidence, &x_confidences));
+                record.extend(csv_confidence_cells(&d.y_confidence, &y_confidences));
+                csv_writer.write_record(&record)?;
+            }
+        }
+
+        csv_writer
+            .flush()
+            .map_err(|e| BencherError::io_err(e, "writing csv"))
+    }
+
     fn table<W: Write>(&self, writer: &mut W) -> BencherResult<()> {
-        let (x_mag, y_mag) = choose_magnitude(self.lines.iter());
+        let (x_scale, y_scale) = self.scales();
 
         for line in &self.lines {
             let table = line
@@ -342,10 +479,10 @@ set yrange [*:*]
                 .map(|d| {
                     vec![
                         d.tag.unwrap().cell().justify(Justify::Right),
-                        d.x.display_with_magnitude(x_mag)
+                        d.x.display_with_scale(&x_scale)
                             .cell()
                             .justify(Justify::Right),
-                        d.y.display_with_magnitude(y_mag)
+                        d.y.display_with_scale(&y_scale)
                             .cell()
                             .justify(Justify::Right),
                     ]
@@ -354,11 +491,11 @@ set yrange [*:*]
                 .table()
                 .title(vec![
                     "Tag".cell().justify(Justify::Center).bold(true),
-                    format!("{} ({}{})", self.x_label, x_mag.prefix(), self.x_units)
+                    format!("{} ({})", self.x_label, x_scale.units())
                         .cell()
                         .justify(Justify::Center)
                         .bold(true),
-                    format!("{} ({}{})", self.y_label, y_mag.prefix(), self.y_units)
+                    format!("{} ({})", self.y_label, y_scale.units())
                         .cell()
                         .justify(Justify::Center)
                         .bold(true),
@@ -377,26 +514,24 @@ set yrange [*:*]
     }
 
     fn latex_table<W: Write>(&self, writer: &mut W) -> BencherResult<()> {
-        let (x_mag, y_mag) = choose_magnitude(self.lines.iter());
+        let (x_scale, y_scale) = self.scales();
         for line in &self.lines {
             writeln!(writer, "\\begin{{table}}[t]\n    \\centering\n    \\begin{{tabular}}{{|r|r|}}\n        \\hline").map_err(|e| BencherError::io_err(e, "writing latex table"))?;
             writeln!(
                 writer,
-                "        \\textbf{{ {} ({}{}) }} & \\textbf{{ {} ({}{}) }} \\\\ \\hline",
+                "        \\textbf{{ {} ({}) }} & \\textbf{{ {} ({}) }} \\\\ \\hline",
                 self.x_label,
-                x_mag.prefix(),
-                self.x_units,
+                x_scale.units(),
                 self.y_label,
-                y_mag.prefix(),
-                self.y_units
+                y_scale.units(),
             )
             .map_err(|e| BencherError::io_err(e, "writing latex table"))?;
             for d in &line.values {
                 writeln!(
                     writer,
                     "        ${:>8}$ & ${:>8}$ \\\\ \\hline",
-                    d.x.display_with_magnitude(x_mag),
-                    d.y.display_with_magnitude(y_mag)
+                    d.x.display_with_scale(&x_scale),
+                    d.y.display_with_scale(&y_scale)
                 )
                 .map_err(|e| BencherError::io_err(e, "writing latex table"))?
             }
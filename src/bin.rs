@@ -1,9 +1,16 @@
 use bencher::{
-    Bars, BencherError, ExperimentView, ReadConfig, Selector, SelectorBuilder, Sorter, WriteConfig,
+    default_db_path, init_config, read_linear_samples, read_xy_samples, Aggregation, AxisFormat,
+    AxisStyle, Bars, BencherError, CompareOptions, Confidence, ConfigEditor, ConfigFormat,
+    ExperimentView, ExportFormat, HistogramStyle, ImageFormat, LegendPosition, LinearDatapoint,
+    LinearExperiment, LinearSampleColumns, Magnitude, Monotonicity, PlotStyle, ReadConfig,
+    RunMetadata, SampleFormat, Selector, SelectorBuilder, Sorter, Terminal, Value,
+    VirtualLinearExperiment, VirtualXYExperiment, WriteConfig, XYDatapoint, XYExperiment,
+    XYSampleColumns,
 };
 
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand};
 use cli_table::{format::Justify, Cell, Style, Table};
+use either::Either;
 use eyre::Result;
 use std::fs::File;
 
@@ -14,6 +21,10 @@ struct Cli {
     #[arg(short, long)]
     default: bool,
 
+    /// Re-aggregate datapoints with stored samples (median or avg)
+    #[arg(short, long)]
+    aggregation: Option<Aggregation>,
+
     #[command(subcommand)]
     command: Command,
 }
@@ -33,6 +44,18 @@ enum Command {
         #[arg(long)]
         include_type_regex: Vec<String>,
 
+        /// Only select experiments with an active datapoint from a matching git revision
+        #[arg(long)]
+        git_revision_regex: Vec<String>,
+
+        /// Only select experiments with an active datapoint from a matching host
+        #[arg(long)]
+        hostname_regex: Vec<String>,
+
+        /// Only select experiments with an active datapoint with matching metadata, as <key>=<regex>
+        #[arg(long, value_parser = parse_key_value)]
+        metadata_regex: Vec<(String, String)>,
+
         #[arg(short, long)]
         sort_regex: Vec<String>,
 
@@ -52,6 +75,18 @@ enum Command {
         #[arg(long)]
         include_type_regex: Vec<String>,
 
+        /// Only select experiments with an active datapoint from a matching git revision
+        #[arg(long)]
+        git_revision_regex: Vec<String>,
+
+        /// Only select experiments with an active datapoint from a matching host
+        #[arg(long)]
+        hostname_regex: Vec<String>,
+
+        /// Only select experiments with an active datapoint with matching metadata, as <key>=<regex>
+        #[arg(long, value_parser = parse_key_value)]
+        metadata_regex: Vec<(String, String)>,
+
         #[arg(short, long)]
         sort_regex: Vec<String>,
 
@@ -73,9 +108,45 @@ enum Command {
         #[arg(long)]
         include_type_regex: Vec<String>,
 
+        /// Only select experiments with an active datapoint from a matching git revision
+        #[arg(long)]
+        git_revision_regex: Vec<String>,
+
+        /// Only select experiments with an active datapoint from a matching host
+        #[arg(long)]
+        hostname_regex: Vec<String>,
+
+        /// Only select experiments with an active datapoint with matching metadata, as <key>=<regex>
+        #[arg(long, value_parser = parse_key_value)]
+        metadata_regex: Vec<(String, String)>,
+
         #[arg(short, long)]
         sort_regex: Vec<String>,
 
+        /// Magnitude of the values (v or y), e.g., m or K (chosen from the values by default)
+        #[arg(long)]
+        magnitude: Option<Magnitude>,
+
+        /// Number of decimal places of the values (v or y)
+        #[arg(long, conflicts_with = "sig_figs")]
+        precision: Option<usize>,
+
+        /// Number of significant figures of the values (v or y)
+        #[arg(long)]
+        sig_figs: Option<usize>,
+
+        /// Magnitude of the x values of bidimensional experiments
+        #[arg(long)]
+        x_magnitude: Option<Magnitude>,
+
+        /// Number of decimal places of the x values of bidimensional experiments
+        #[arg(long, conflicts_with = "x_sig_figs")]
+        x_precision: Option<usize>,
+
+        /// Number of significant figures of the x values of bidimensional experiments
+        #[arg(long)]
+        x_sig_figs: Option<usize>,
+
         /// Paths to DBs
         dbs: Vec<std::path::PathBuf>,
     },
@@ -97,6 +168,83 @@ enum Command {
         #[arg(long)]
         include_type_regex: Vec<String>,
 
+        /// Only select experiments with an active datapoint from a matching git revision
+        #[arg(long)]
+        git_revision_regex: Vec<String>,
+
+        /// Only select experiments with an active datapoint from a matching host
+        #[arg(long)]
+        hostname_regex: Vec<String>,
+
+        /// Only select experiments with an active datapoint with matching metadata, as <key>=<regex>
+        #[arg(long, value_parser = parse_key_value)]
+        metadata_regex: Vec<(String, String)>,
+
+        #[arg(short, long)]
+        sort_regex: Vec<String>,
+
+        /// Magnitude of the values (v or y), e.g., m or K (chosen from the values by default)
+        #[arg(long)]
+        magnitude: Option<Magnitude>,
+
+        /// Number of decimal places of the values (v or y)
+        #[arg(long, conflicts_with = "sig_figs")]
+        precision: Option<usize>,
+
+        /// Number of significant figures of the values (v or y)
+        #[arg(long)]
+        sig_figs: Option<usize>,
+
+        /// Magnitude of the x values of bidimensional experiments
+        #[arg(long)]
+        x_magnitude: Option<Magnitude>,
+
+        /// Number of decimal places of the x values of bidimensional experiments
+        #[arg(long, conflicts_with = "x_sig_figs")]
+        x_precision: Option<usize>,
+
+        /// Number of significant figures of the x values of bidimensional experiments
+        #[arg(long)]
+        x_sig_figs: Option<usize>,
+
+        /// Paths to DBs
+        dbs: Vec<std::path::PathBuf>,
+    },
+    /// Export an experiment as machine-readable data (every datapoint, with all its confidences)
+    Export {
+        exp_type: String,
+
+        /// Output format (json or csv)
+        #[arg(long, default_value = "json")]
+        format: ExportFormat,
+
+        #[arg(short, long)]
+        file: Option<std::path::PathBuf>,
+
+        #[arg(short, long)]
+        exclude_code_regex: Vec<String>,
+
+        #[arg(short, long)]
+        include_code_regex: Vec<String>,
+
+        #[arg(long)]
+        exclude_type_regex: Vec<String>,
+
+        #[arg(long)]
+        include_type_regex: Vec<String>,
+
+        /// Only select experiments with an active datapoint from a matching git revision
+        #[arg(long)]
+        git_revision_regex: Vec<String>,
+
+        /// Only select experiments with an active datapoint from a matching host
+        #[arg(long)]
+        hostname_regex: Vec<String>,
+
+        /// Only select experiments with an active datapoint with matching metadata, as <key>=<regex>
+        #[arg(long, value_parser = parse_key_value)]
+        metadata_regex: Vec<(String, String)>,
+
         #[arg(short, long)]
         sort_regex: Vec<String>,
 
@@ -109,13 +257,13 @@ enum Command {
         prefix: std::path::PathBuf,
 
         #[arg(short, long)]
-        bar: Option<usize>,
+        bar: Option<Confidence>,
 
         #[arg(short, long)]
-        xbar: Option<usize>,
+        xbar: Option<Confidence>,
 
         #[arg(short, long)]
-        ybar: Option<usize>,
+        ybar: Option<Confidence>,
 
         #[arg(short, long)]
         exclude_code_regex: Vec<String>,
@@ -129,9 +277,49 @@ enum Command {
         #[arg(long)]
         include_type_regex: Vec<String>,
 
+        /// Only select experiments with an active datapoint from a matching git revision
+        #[arg(long)]
+        git_revision_regex: Vec<String>,
+
+        /// Only select experiments with an active datapoint from a matching host
+        #[arg(long)]
+        hostname_regex: Vec<String>,
+
+        /// Only select experiments with an active datapoint with matching metadata, as <key>=<regex>
+        #[arg(long, value_parser = parse_key_value)]
+        metadata_regex: Vec<(String, String)>,
+
         #[arg(short, long)]
         sort_regex: Vec<String>,
 
+        /// Layout of the sets of linear experiments (clustered, row_stacked, column_stacked or percent_stacked)
+        #[arg(long)]
+        histogram_style: Option<HistogramStyle>,
+
+        /// Magnitude of the values (v or y), e.g., m or K (chosen from the values by default)
+        #[arg(long)]
+        magnitude: Option<Magnitude>,
+
+        /// Number of decimal places of the values (v or y)
+        #[arg(long, conflicts_with = "sig_figs")]
+        precision: Option<usize>,
+
+        /// Number of significant figures of the values (v or y)
+        #[arg(long)]
+        sig_figs: Option<usize>,
+
+        /// Magnitude of the x values of bidimensional experiments
+        #[arg(long)]
+        x_magnitude: Option<Magnitude>,
+
+        /// Number of decimal places of the x values of bidimensional experiments
+        #[arg(long, conflicts_with = "x_sig_figs")]
+        x_precision: Option<usize>,
+
+        /// Number of significant figures of the x values of bidimensional experiments
+        #[arg(long)]
+        x_sig_figs: Option<usize>,
+
         /// Paths to DBs
         dbs: Vec<std::path::PathBuf>,
     },
@@ -161,9 +349,84 @@ enum Command {
         #[arg(short, long)]
         include_type_regex: Vec<String>,
 
+        /// Only select experiments with an active datapoint from a matching git revision
+        #[arg(long)]
+        git_revision_regex: Vec<String>,
+
+        /// Only select experiments with an active datapoint from a matching host
+        #[arg(long)]
+        hostname_regex: Vec<String>,
+
+        /// Only select experiments with an active datapoint with matching metadata, as <key>=<regex>
+        #[arg(long, value_parser = parse_key_value)]
+        metadata_regex: Vec<(String, String)>,
+
         #[arg(short, long)]
         sort_regex: Vec<String>,
 
+        /// Layout of the sets of linear experiments (clustered, row_stacked, column_stacked or percent_stacked)
+        #[arg(long)]
+        histogram_style: Option<HistogramStyle>,
+
+        /// Magnitude of the values (v or y), e.g., m or K (chosen from the values by default)
+        #[arg(long)]
+        magnitude: Option<Magnitude>,
+
+        /// Number of decimal places of the values (v or y)
+        #[arg(long, conflicts_with = "sig_figs")]
+        precision: Option<usize>,
+
+        /// Number of significant figures of the values (v or y)
+        #[arg(long)]
+        sig_figs: Option<usize>,
+
+        /// Magnitude of the x values of bidimensional experiments
+        #[arg(long)]
+        x_magnitude: Option<Magnitude>,
+
+        /// Number of decimal places of the x values of bidimensional experiments
+        #[arg(long, conflicts_with = "x_sig_figs")]
+        x_precision: Option<usize>,
+
+        /// Number of significant figures of the x values of bidimensional experiments
+        #[arg(long)]
+        x_sig_figs: Option<usize>,
+
+        /// Draw the value (v or y) axis in a logarithmic scale
+        #[arg(long)]
+        log_y: bool,
+
+        /// Draw the x axis of bidimensional experiments in a logarithmic scale
+        #[arg(long)]
+        log_x: bool,
+
+        /// Range of the value (v or y) axis, as <min>:<max> (either may be left empty or `*`)
+        #[arg(long, value_parser = parse_range, allow_hyphen_values = true)]
+        y_range: Option<(Option<f64>, Option<f64>)>,
+
+        /// Range of the x axis of bidimensional experiments, as <min>:<max>
+        #[arg(long, value_parser = parse_range, allow_hyphen_values = true)]
+        x_range: Option<(Option<f64>, Option<f64>)>,
+
+        /// Spacing between the ticks of the value (v or y) axis (factor between ticks on log axes)
+        #[arg(long)]
+        y_tics: Option<f64>,
+
+        /// Spacing between the ticks of the x axis of bidimensional experiments
+        #[arg(long)]
+        x_tics: Option<f64>,
+
+        /// Reverse the value (v or y) axis
+        #[arg(long)]
+        reverse_y: bool,
+
+        /// Reverse the x axis of bidimensional experiments
+        #[arg(long)]
+        reverse_x: bool,
+
+        #[command(flatten)]
+        style: StyleArgs,
+
         /// Paths to DBs
         dbs: Vec<std::path::PathBuf>,
     },
@@ -173,13 +436,13 @@ enum Command {
         prefix: std::path::PathBuf,
 
         #[arg(short, long)]
-        bar: Option<usize>,
+        bar: Option<Confidence>,
 
         #[arg(short, long)]
-        xbar: Option<usize>,
+        xbar: Option<Confidence>,
 
         #[arg(short, long)]
-        ybar: Option<usize>,
+        ybar: Option<Confidence>,
 
         #[arg(short, long)]
         exclude_code_regex: Vec<String>,
@@ -193,12 +456,233 @@ enum Command {
         #[arg(long)]
         include_type_regex: Vec<String>,
 
+        /// Only select experiments with an active datapoint from a matching git revision
+        #[arg(long)]
+        git_revision_regex: Vec<String>,
+
+        /// Only select experiments with an active datapoint from a matching host
+        #[arg(long)]
+        hostname_regex: Vec<String>,
+
+        /// Only select experiments with an active datapoint with matching metadata, as <key>=<regex>
+        #[arg(long, value_parser = parse_key_value)]
+        metadata_regex: Vec<(String, String)>,
+
         #[arg(short, long)]
         sort_regex: Vec<String>,
 
+        /// Render the plot directly as an image (svg or png), instead of writing a gnuplot script
+        #[arg(short, long)]
+        format: Option<ImageFormat>,
+
+        /// Layout of the sets of linear experiments (clustered, row_stacked, column_stacked or percent_stacked)
+        #[arg(long)]
+        histogram_style: Option<HistogramStyle>,
+
+        /// Magnitude of the values (v or y), e.g., m or K (chosen from the values by default)
+        #[arg(long)]
+        magnitude: Option<Magnitude>,
+
+        /// Number of decimal places of the values (v or y)
+        #[arg(long, conflicts_with = "sig_figs")]
+        precision: Option<usize>,
+
+        /// Number of significant figures of the values (v or y)
+        #[arg(long)]
+        sig_figs: Option<usize>,
+
+        /// Magnitude of the x values of bidimensional experiments
+        #[arg(long)]
+        x_magnitude: Option<Magnitude>,
+
+        /// Number of decimal places of the x values of bidimensional experiments
+        #[arg(long, conflicts_with = "x_sig_figs")]
+        x_precision: Option<usize>,
+
+        /// Number of significant figures of the x values of bidimensional experiments
+        #[arg(long)]
+        x_sig_figs: Option<usize>,
+
+        /// Draw the value (v or y) axis in a logarithmic scale
+        #[arg(long)]
+        log_y: bool,
+
+        /// Draw the x axis of bidimensional experiments in a logarithmic scale
+        #[arg(long)]
+        log_x: bool,
+
+        /// Range of the value (v or y) axis, as <min>:<max> (either may be left empty or `*`)
+        #[arg(long, value_parser = parse_range, allow_hyphen_values = true)]
+        y_range: Option<(Option<f64>, Option<f64>)>,
+
+        /// Range of the x axis of bidimensional experiments, as <min>:<max>
+        #[arg(long, value_parser = parse_range, allow_hyphen_values = true)]
+        x_range: Option<(Option<f64>, Option<f64>)>,
+
+        /// Spacing between the ticks of the value (v or y) axis (factor between ticks on log axes)
+        #[arg(long)]
+        y_tics: Option<f64>,
+
+        /// Spacing between the ticks of the x axis of bidimensional experiments
+        #[arg(long)]
+        x_tics: Option<f64>,
+
+        /// Reverse the value (v or y) axis
+        #[arg(long)]
+        reverse_y: bool,
+
+        /// Reverse the x axis of bidimensional experiments
+        #[arg(long)]
+        reverse_x: bool,
+
+        #[command(flatten)]
+        style: StyleArgs,
+
         /// Paths to DBs
         dbs: Vec<std::path::PathBuf>,
     },
+    /// Compare the datapoints in the DBs with the ones in baseline DBs, failing on regressions
+    ///
+    /// Without baseline DBs, the active version of each datapoint is compared with the one added before it
+    Compare {
+        /// Paths to the baseline DBs
+        #[arg(long)]
+        baseline: Vec<std::path::PathBuf>,
+
+        /// Relative change (in percent) above which a worse result is a regression
+        #[arg(short, long, default_value_t = 5.0)]
+        threshold: f64,
+
+        /// Confidence interval used to tell changes apart from noise
+        #[arg(short, long, default_value = "5")]
+        confidence: Confidence,
+
+        /// Significance level of the statistical tests, for datapoints with raw samples
+        #[arg(long, default_value_t = 0.05)]
+        alpha: f64,
+
+        #[arg(short, long)]
+        exclude_code_regex: Vec<String>,
+
+        #[arg(short, long)]
+        include_code_regex: Vec<String>,
+
+        #[arg(long)]
+        exclude_type_regex: Vec<String>,
+
+        #[arg(long)]
+        include_type_regex: Vec<String>,
+
+        /// Only select experiments with an active datapoint from a matching git revision
+        #[arg(long)]
+        git_revision_regex: Vec<String>,
+
+        /// Only select experiments with an active datapoint from a matching host
+        #[arg(long)]
+        hostname_regex: Vec<String>,
+
+        /// Only select experiments with an active datapoint with matching metadata, as <key>=<regex>
+        #[arg(long, value_parser = parse_key_value)]
+        metadata_regex: Vec<(String, String)>,
+
+        /// Paths to the candidate DBs
+        dbs: Vec<std::path::PathBuf>,
+    },
+    /// Show every version of a datapoint, in the order they were added
+    History {
+        code: String,
+
+        /// Group of the datapoint (for linear experiments)
+        #[arg(short, long)]
+        group: Option<String>,
+
+        /// Tag of the datapoint (for bidimensional experiments)
+        #[arg(short, long)]
+        tag: Option<isize>,
+
+        /// Also write a gnuplot time series (and its dat file) with this prefix
+        #[arg(short, long)]
+        prefix: Option<std::path::PathBuf>,
+
+        #[arg(short, long)]
+        bar: Option<Confidence>,
+
+        /// Render the time series directly as an image (svg or png), instead of writing a gnuplot script
+        #[arg(short, long)]
+        format: Option<ImageFormat>,
+
+        #[command(flatten)]
+        style: StyleArgs,
+
+        /// Paths to DBs
+        dbs: Vec<std::path::PathBuf>,
+    },
+    Add {
+        /// Path to DB
+        #[arg(short, long)]
+        db: Option<std::path::PathBuf>,
+
+        /// Git revision the datapoints were produced at (by default, that of the current directory)
+        #[arg(long)]
+        git_revision: Option<String>,
+
+        /// Host the datapoints were produced on (by default, this host)
+        #[arg(long)]
+        hostname: Option<String>,
+
+        /// Metadata to record with the datapoints, as <key>=<value>
+        #[arg(short, long, value_parser = parse_key_value)]
+        metadata: Vec<(String, String)>,
+
+        #[command(subcommand)]
+        datapoint: AddCommand,
+    },
+    Import {
+        /// Path to DB
+        #[arg(short, long)]
+        db: Option<std::path::PathBuf>,
+
+        /// Git revision the datapoints were produced at (by default, that of the current directory)
+        #[arg(long)]
+        git_revision: Option<String>,
+
+        /// Host the datapoints were produced on (by default, this host)
+        #[arg(long)]
+        hostname: Option<String>,
+
+        /// Metadata to record with the datapoints, as <key>=<value>
+        #[arg(short, long, value_parser = parse_key_value)]
+        metadata: Vec<(String, String)>,
+
+        #[command(subcommand)]
+        samples: ImportCommand,
+    },
+    /// Create a config in the current directory, along with its default database
+    Init {
+        /// Path to the default database, relative to the config
+        #[arg(long, default_value = "bencher.db")]
+        database: String,
+
+        /// Format of the config (json, toml or yaml)
+        #[arg(long, default_value = "json")]
+        format: ConfigFormat,
+    },
+    /// Edit the experiment definitions in the config
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommand,
+    },
+    /// Check the experiment definitions in the config, reporting every problem
+    CheckConfig,
+    /// Upgrade databases to the current schema, in place
+    Migrate {
+        /// Paths to DBs (the default DB if none is given)
+        dbs: Vec<std::path::PathBuf>,
+
+        /// Do not keep a copy of each database before upgrading it
+        #[arg(long)]
+        no_backup: bool,
+    },
     Revert {
         code: String,
 
@@ -208,20 +692,445 @@ enum Command {
         #[arg(short, long)]
         db: Option<std::path::PathBuf>,
 
-        #[arg(short, long)]
-        tag: Option<isize>,
+        #[arg(short, long)]
+        tag: Option<isize>,
+
+        #[arg(short, long)]
+        group: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum AddCommand {
+    /// Add a datapoint to a linear experiment
+    Linear {
+        exp_type: String,
+
+        exp_label: String,
+
+        exp_code: String,
+
+        group: String,
+
+        #[arg(allow_negative_numbers = true)]
+        value: Value,
+
+        #[arg(short, long)]
+        tag: Option<isize>,
+
+        /// Confidence interval, as <percentile>:<lower>:<upper>
+        #[arg(short, long, value_parser = parse_confidence)]
+        confidence: Vec<(Confidence, Value, Value)>,
+    },
+    /// Add a datapoint to a bidimensional experiment
+    Xy {
+        exp_type: String,
+
+        exp_label: String,
+
+        exp_code: String,
+
+        #[arg(allow_negative_numbers = true)]
+        x: Value,
+
+        #[arg(allow_negative_numbers = true)]
+        y: Value,
+
+        #[arg(short, long)]
+        tag: Option<isize>,
+
+        /// Confidence interval for x, as <percentile>:<lower>:<upper>
+        #[arg(short, long, value_parser = parse_confidence)]
+        xconfidence: Vec<(Confidence, Value, Value)>,
+
+        /// Confidence interval for y, as <percentile>:<lower>:<upper>
+        #[arg(short, long, value_parser = parse_confidence)]
+        yconfidence: Vec<(Confidence, Value, Value)>,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigCommand {
+    /// Add a linear experiment
+    AddLinear {
+        exp_type: String,
+
+        #[arg(long)]
+        horizontal_label: String,
+
+        #[arg(long)]
+        v_label: String,
+
+        #[arg(long, default_value = "")]
+        v_units: String,
+
+        /// Whether larger values are better (e.g., throughput), for comparisons
+        #[arg(long)]
+        higher_is_better: bool,
+
+        /// Layout of the sets in plots (clustered, row_stacked, column_stacked or percent_stacked)
+        #[arg(long)]
+        histogram_style: Option<HistogramStyle>,
+    },
+    /// Add a bidimensional experiment
+    AddXy {
+        exp_type: String,
+
+        #[arg(long)]
+        x_label: String,
+
+        #[arg(long, default_value = "")]
+        x_units: String,
+
+        #[arg(long)]
+        y_label: String,
+
+        #[arg(long, default_value = "")]
+        y_units: String,
+
+        /// Whether larger y values are better (e.g., throughput), for comparisons
+        #[arg(long)]
+        higher_is_better: bool,
+    },
+    /// Add a virtual experiment
+    AddVirtual {
+        #[command(subcommand)]
+        experiment: AddVirtualCommand,
+    },
+    /// Remove an experiment (of any kind)
+    Remove { exp_type: String },
+}
+
+#[derive(Subcommand)]
+enum AddVirtualCommand {
+    /// Add a virtual linear experiment
+    Linear {
+        exp_type: String,
+
+        /// Source experiments (the first one is the primary source)
+        #[arg(short, long, required = true)]
+        source: Vec<String>,
+
+        #[arg(long)]
+        horizontal_label: String,
+
+        #[arg(long)]
+        v_label: String,
+
+        #[arg(long, default_value = "")]
+        v_units: String,
+
+        #[arg(long)]
+        v_operation: Option<String>,
+
+        #[arg(long)]
+        tag_operation: Option<String>,
+
+        /// Monotonicity of the v operation (increasing, decreasing, monotonic or non_monotonic)
+        #[arg(long)]
+        v_monotonicity: Option<Monotonicity>,
+
+        /// Set of the primary source available in the operations as `baseline`
+        #[arg(long)]
+        baseline_set: Option<String>,
+
+        /// Layout of the sets in plots (clustered, row_stacked, column_stacked or percent_stacked)
+        #[arg(long)]
+        histogram_style: Option<HistogramStyle>,
+    },
+    /// Add a virtual bidimensional experiment
+    Xy {
+        exp_type: String,
+
+        /// Source experiments (the first one is the primary source)
+        #[arg(short, long, required = true)]
+        source: Vec<String>,
+
+        #[arg(long)]
+        x_label: String,
+
+        #[arg(long, default_value = "")]
+        x_units: String,
+
+        #[arg(long)]
+        y_label: String,
+
+        #[arg(long, default_value = "")]
+        y_units: String,
+
+        #[arg(long)]
+        x_operation: Option<String>,
+
+        #[arg(long)]
+        y_operation: Option<String>,
+
+        #[arg(long)]
+        tag_operation: Option<String>,
+
+        /// Monotonicity of the x operation (increasing, decreasing, monotonic or non_monotonic)
+        #[arg(long)]
+        x_monotonicity: Option<Monotonicity>,
+
+        /// Monotonicity of the y operation (increasing, decreasing, monotonic or non_monotonic)
+        #[arg(long)]
+        y_monotonicity: Option<Monotonicity>,
+
+        /// Set (i.e., line) of the primary source available in the operations as `baseline_x`
+        /// and `baseline_y` (or `baseline`, if the primary source is linear)
+        #[arg(long)]
+        baseline_set: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum ImportCommand {
+    /// Import raw samples into a linear experiment, one datapoint per group
+    Linear {
+        exp_type: String,
+
+        exp_label: String,
+
+        exp_code: String,
+
+        /// File with the samples (csv or jsonl)
+        file: std::path::PathBuf,
+
+        /// Column with the group of each sample
+        #[arg(short, long, default_value = "group")]
+        group_column: String,
+
+        /// Column with the value of each sample
+        #[arg(short, long, default_value = "v")]
+        value_column: String,
+
+        /// File format (csv or jsonl), guessed from the extension by default
+        #[arg(short, long)]
+        format: Option<SampleFormat>,
+
+        /// How to aggregate the samples (median or avg)
+        #[arg(short, long, default_value = "median")]
+        aggregation: Aggregation,
+
+        /// Percentile to compute in addition to the default ones (e.g., 99.9)
+        #[arg(short, long)]
+        percentile: Vec<Confidence>,
+
+        /// Only store the aggregated datapoints, not the raw samples
+        #[arg(long)]
+        discard_samples: bool,
+    },
+    /// Import raw samples into a bidimensional experiment, one datapoint per tag (or x value)
+    Xy {
+        exp_type: String,
+
+        exp_label: String,
+
+        exp_code: String,
+
+        /// File with the samples (csv or jsonl)
+        file: std::path::PathBuf,
+
+        /// Column with the x value of each sample
+        #[arg(short, long, default_value = "x")]
+        x_column: String,
+
+        /// Column with the y value of each sample
+        #[arg(short, long, default_value = "y")]
+        y_column: String,
+
+        /// Column with the tag of each sample; samples are grouped by x value if omitted
+        #[arg(short, long)]
+        tag_column: Option<String>,
+
+        /// File format (csv or jsonl), guessed from the extension by default
+        #[arg(short, long)]
+        format: Option<SampleFormat>,
+
+        /// How to aggregate the samples (median or avg)
+        #[arg(short, long, default_value = "median")]
+        aggregation: Aggregation,
+
+        /// Percentile to compute in addition to the default ones (e.g., 99.9)
+        #[arg(short, long)]
+        percentile: Vec<Confidence>,
+
+        /// Only store the aggregated datapoints, not the raw samples
+        #[arg(long)]
+        discard_samples: bool,
+    },
+}
+
+/// Parse a confidence interval, in the format <percentile>:<lower>:<upper>
+fn parse_confidence(s: &str) -> std::result::Result<(Confidence, Value, Value), String> {
+    let parts = s.split(':').collect::<Vec<_>>();
+    if parts.len() != 3 {
+        return Err(format!(
+            "expected <percentile>:<lower>:<upper>, got `{}`",
+            s
+        ));
+    }
+
+    let percentile = parts[0].parse::<Confidence>().map_err(|e| e.to_string())?;
+    let lower = parts[1].parse::<Value>().map_err(|e| e.to_string())?;
+    let upper = parts[2].parse::<Value>().map_err(|e| e.to_string())?;
+    Ok((percentile, lower, upper))
+}
+
+// Overrides of the style of the plots in the config
+#[derive(Args)]
+struct StyleArgs {
+    /// gnuplot terminal (eps, epslatex, pdfcairo, pngcairo or svg)
+    #[arg(long)]
+    terminal: Option<Terminal>,
+
+    /// Size of the plots, as given to gnuplot (e.g., 12cm,8cm or 800,600)
+    #[arg(long)]
+    size: Option<String>,
+
+    /// Font of the plots, as given to gnuplot (e.g., Helvetica,20)
+    #[arg(long)]
+    font: Option<String>,
+
+    /// Colors of the lines (and bars), as comma-separated hex RGB codes
+    #[arg(long, value_delimiter = ',')]
+    palette: Option<Vec<String>>,
+
+    #[arg(long)]
+    line_width: Option<f64>,
+
+    #[arg(long)]
+    point_size: Option<f64>,
+
+    /// gnuplot point types of the lines, comma-separated
+    #[arg(long, value_delimiter = ',')]
+    point_types: Option<Vec<u32>>,
+
+    /// gnuplot dash type of the lines
+    #[arg(long)]
+    dash_type: Option<u32>,
+
+    /// Position of the legend (e.g., outside_above, inside_top_left or hidden)
+    #[arg(long)]
+    legend: Option<LegendPosition>,
+}
+
+impl StyleArgs {
+    /// Style of the plots, overriding the one in the config
+    fn build(self) -> Result<PlotStyle> {
+        let style = PlotStyle::new()
+            .terminal(self.terminal)
+            .size(self.size)
+            .font(self.font)
+            .palette(self.palette)
+            .line_width(self.line_width)
+            .point_size(self.point_size)
+            .point_types(self.point_types)
+            .dash_type(self.dash_type)
+            .legend(self.legend);
+        style.validate()?;
+        Ok(style)
+    }
+}
+
+/// Parse a range of an axis, as <min>:<max>, where an empty bound (or `*`) is not set
+fn parse_range(s: &str) -> std::result::Result<(Option<f64>, Option<f64>), String> {
+    let bound = |b: &str| match b.trim() {
+        "" | "*" => Ok(None),
+        b => b
+            .parse::<f64>()
+            .map(Some)
+            .map_err(|e| format!("invalid bound `{}`: {}", b, e)),
+    };
+    let (min, max) = s
+        .split_once(':')
+        .ok_or_else(|| format!("expected <min>:<max>, got `{}`", s))?;
+    Ok((bound(min)?, bound(max)?))
+}
+
+/// Parse a metadata entry, in the format <key>=<value>
+fn parse_key_value(s: &str) -> std::result::Result<(String, String), String> {
+    s.split_once('=')
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .ok_or_else(|| format!("expected <key>=<value>, got `{}`", s))
+}
+
+/// Metadata of the current run, with the given overrides
+fn run_metadata(
+    git_revision: Option<String>,
+    hostname: Option<String>,
+    values: Vec<(String, String)>,
+) -> RunMetadata {
+    let mut metadata = RunMetadata::current();
+    if git_revision.is_some() {
+        metadata.git_revision = git_revision;
+    }
+    if hostname.is_some() {
+        metadata.hostname = hostname;
+    }
+    for (key, value) in values {
+        metadata = metadata.value(key, value);
+    }
+    metadata
+}
+
+/// Convert a pair of values into the confidence argument for a datapoint with value `v`
+///
+/// Float datapoints accept integer bounds, but not the other way around
+fn confidence_pair(v: Value, lower: Value, upper: Value) -> Either<(i64, i64), (f64, f64)> {
+    fn as_float(v: Value) -> f64 {
+        v.to_float().or(v.to_int().map(|x| x as f64)).unwrap()
+    }
 
-        #[arg(short, long)]
-        group: Option<String>,
-    },
+    match (v, lower, upper) {
+        (Value::Int(_), Value::Int(lower), Value::Int(upper)) => Either::Left((lower, upper)),
+        _ => Either::Right((as_float(lower), as_float(upper))),
+    }
 }
 
-fn get_read_config(default: bool, dbs: Vec<std::path::PathBuf>) -> Result<ReadConfig> {
-    if default {
-        ReadConfig::with_dbs_and_default(dbs.iter().map(|p| p.as_path())).map_err(|e| e.into())
+fn get_read_config(
+    default: bool,
+    aggregation: Option<Aggregation>,
+    dbs: Vec<std::path::PathBuf>,
+) -> Result<ReadConfig> {
+    let config = if default {
+        ReadConfig::with_dbs_and_default(dbs.iter().map(|p| p.as_path()))?
     } else {
-        ReadConfig::with_dbs(dbs.iter().map(|p| p.as_path())).map_err(|e| e.into())
-    }
+        ReadConfig::with_dbs(dbs.iter().map(|p| p.as_path()))?
+    };
+
+    Ok(match aggregation {
+        Some(aggregation) => config.with_aggregation(aggregation),
+        None => config,
+    })
+}
+
+/// Format of an axis, overriding the one in the config
+fn build_format(
+    magnitude: Option<Magnitude>,
+    precision: Option<usize>,
+    significant_figures: Option<usize>,
+) -> AxisFormat {
+    AxisFormat::new()
+        .magnitude(magnitude)
+        .precision(precision)
+        .significant_figures(significant_figures)
+}
+
+/// Style of an axis, overriding the one in the config
+fn build_axis(
+    log: bool,
+    range: Option<(Option<f64>, Option<f64>)>,
+    tick_spacing: Option<f64>,
+    reverse: bool,
+) -> Result<AxisStyle> {
+    let (min, max) = range.unwrap_or((None, None));
+    let axis = AxisStyle::new()
+        .log(log)
+        .range(min, max)
+        .tick_spacing(tick_spacing)
+        .reverse(reverse);
+    axis.validate()?;
+    Ok(axis)
 }
 
 fn get_write_config(db: Option<std::path::PathBuf>) -> Result<WriteConfig> {
@@ -237,6 +1146,9 @@ fn build_selector(
     include_code_regex: &Vec<String>,
     exclude_type_regex: &Vec<String>,
     include_type_regex: &Vec<String>,
+    git_revision_regex: &Vec<String>,
+    hostname_regex: &Vec<String>,
+    metadata_regex: &Vec<(String, String)>,
 ) -> Result<Selector> {
     let exclude_code_regex = exclude_code_regex.iter().map(|re| regex::Regex::new(re));
     let include_code_regex = include_code_regex.iter().map(|re| regex::Regex::new(re));
@@ -260,6 +1172,18 @@ fn build_selector(
         let re = re.map_err(|e| eyre::eyre!("regex error: {:?}", e))?;
         builder = builder.type_exclude(re);
     }
+    for re in git_revision_regex {
+        let re = regex::Regex::new(re).map_err(|e| eyre::eyre!("regex error: {:?}", e))?;
+        builder = builder.git_revision_include(re);
+    }
+    for re in hostname_regex {
+        let re = regex::Regex::new(re).map_err(|e| eyre::eyre!("regex error: {:?}", e))?;
+        builder = builder.hostname_include(re);
+    }
+    for (key, re) in metadata_regex {
+        let re = regex::Regex::new(re).map_err(|e| eyre::eyre!("regex error: {:?}", e))?;
+        builder = builder.metadata_include(key, re);
+    }
     Ok(builder.build())
 }
 
@@ -283,6 +1207,9 @@ fn main() -> Result<()> {
             include_code_regex,
             exclude_type_regex,
             include_type_regex,
+            git_revision_regex,
+            hostname_regex,
+            metadata_regex,
             sort_regex,
         } => {
             let selector = build_selector(
@@ -290,9 +1217,12 @@ fn main() -> Result<()> {
                 &include_code_regex,
                 &exclude_type_regex,
                 &include_type_regex,
+                &git_revision_regex,
+                &hostname_regex,
+                &metadata_regex,
             )?;
             let sorter = build_sorter(&sort_regex)?;
-            let config = get_read_config(cli.default, dbs)?;
+            let config = get_read_config(cli.default, cli.aggregation, dbs)?;
             list(&config, &selector, &sorter)?;
         }
         Command::Status {
@@ -301,6 +1231,9 @@ fn main() -> Result<()> {
             include_code_regex,
             exclude_type_regex,
             include_type_regex,
+            git_revision_regex,
+            hostname_regex,
+            metadata_regex,
             sort_regex,
         } => {
             let selector = build_selector(
@@ -308,9 +1241,12 @@ fn main() -> Result<()> {
                 &include_code_regex,
                 &exclude_type_regex,
                 &include_type_regex,
+                &git_revision_regex,
+                &hostname_regex,
+                &metadata_regex,
             )?;
             let sorter = build_sorter(&sort_regex)?;
-            let config = get_read_config(cli.default, dbs)?;
+            let config = get_read_config(cli.default, cli.aggregation, dbs)?;
             status(&config, &selector, &sorter)?;
         }
         Command::Table {
@@ -319,17 +1255,31 @@ fn main() -> Result<()> {
             include_code_regex,
             exclude_type_regex,
             include_type_regex,
+            git_revision_regex,
+            hostname_regex,
+            metadata_regex,
             sort_regex,
             exp_type,
+            magnitude,
+            precision,
+            sig_figs,
+            x_magnitude,
+            x_precision,
+            x_sig_figs,
         } => {
             let selector = build_selector(
                 &exclude_code_regex,
                 &include_code_regex,
                 &exclude_type_regex,
                 &include_type_regex,
+                &git_revision_regex,
+                &hostname_regex,
+                &metadata_regex,
             )?;
             let sorter = build_sorter(&sort_regex)?;
-            let config = get_read_config(cli.default, dbs)?;
+            let config = get_read_config(cli.default, cli.aggregation, dbs)?
+                .with_value_format(build_format(magnitude, precision, sig_figs))
+                .with_x_format(build_format(x_magnitude, x_precision, x_sig_figs));
             table(&config, &exp_type, &selector, &sorter)?;
         }
         Command::Latex {
@@ -338,18 +1288,32 @@ fn main() -> Result<()> {
             include_code_regex,
             exclude_type_regex,
             include_type_regex,
+            git_revision_regex,
+            hostname_regex,
+            metadata_regex,
             sort_regex,
             exp_type,
             file,
+            magnitude,
+            precision,
+            sig_figs,
+            x_magnitude,
+            x_precision,
+            x_sig_figs,
         } => {
             let selector = build_selector(
                 &exclude_code_regex,
                 &include_code_regex,
                 &exclude_type_regex,
                 &include_type_regex,
+                &git_revision_regex,
+                &hostname_regex,
+                &metadata_regex,
             )?;
             let sorter = build_sorter(&sort_regex)?;
-            let config = get_read_config(cli.default, dbs)?;
+            let config = get_read_config(cli.default, cli.aggregation, dbs)?
+                .with_value_format(build_format(magnitude, precision, sig_figs))
+                .with_x_format(build_format(x_magnitude, x_precision, x_sig_figs));
             latex(
                 &config,
                 &exp_type,
@@ -358,27 +1322,80 @@ fn main() -> Result<()> {
                 &sorter,
             )?;
         }
+        Command::Export {
+            dbs,
+            exclude_code_regex,
+            include_code_regex,
+            exclude_type_regex,
+            include_type_regex,
+            git_revision_regex,
+            hostname_regex,
+            metadata_regex,
+            sort_regex,
+            exp_type,
+            format,
+            file,
+        } => {
+            let selector = build_selector(
+                &exclude_code_regex,
+                &include_code_regex,
+                &exclude_type_regex,
+                &include_type_regex,
+                &git_revision_regex,
+                &hostname_regex,
+                &metadata_regex,
+            )?;
+            let sorter = build_sorter(&sort_regex)?;
+            let config = get_read_config(cli.default, cli.aggregation, dbs)?;
+            export(
+                &config,
+                &exp_type,
+                format,
+                file.as_deref(),
+                &selector,
+                &sorter,
+            )?;
+        }
         Command::Dat {
             dbs,
             exclude_code_regex,
             include_code_regex,
             exclude_type_regex,
             include_type_regex,
+            git_revision_regex,
+            hostname_regex,
+            metadata_regex,
             sort_regex,
             exp_type,
             prefix,
             bar,
             xbar,
             ybar,
+            magnitude,
+            precision,
+            sig_figs,
+            x_magnitude,
+            x_precision,
+            x_sig_figs,
+            histogram_style,
         } => {
             let selector = build_selector(
                 &exclude_code_regex,
                 &include_code_regex,
                 &exclude_type_regex,
                 &include_type_regex,
+                &git_revision_regex,
+                &hostname_regex,
+                &metadata_regex,
             )?;
             let sorter = build_sorter(&sort_regex)?;
-            let config = get_read_config(cli.default, dbs)?;
+            let config = get_read_config(cli.default, cli.aggregation, dbs)?
+                .with_value_format(build_format(magnitude, precision, sig_figs))
+                .with_x_format(build_format(x_magnitude, x_precision, x_sig_figs));
+            let config = match histogram_style {
+                Some(histogram_style) => config.with_histogram_style(histogram_style),
+                None => config,
+            };
             dat(
                 &config,
                 &exp_type,
@@ -396,21 +1413,52 @@ fn main() -> Result<()> {
             include_code_regex,
             exclude_type_regex,
             include_type_regex,
+            git_revision_regex,
+            hostname_regex,
+            metadata_regex,
             sort_regex,
             exp_type,
             prefix,
             bar,
             xbar,
             ybar,
+            magnitude,
+            precision,
+            sig_figs,
+            x_magnitude,
+            x_precision,
+            x_sig_figs,
+            histogram_style,
+            log_y,
+            log_x,
+            y_range,
+            x_range,
+            y_tics,
+            x_tics,
+            reverse_y,
+            reverse_x,
+            style,
         } => {
             let selector = build_selector(
                 &exclude_code_regex,
                 &include_code_regex,
                 &exclude_type_regex,
                 &include_type_regex,
+                &git_revision_regex,
+                &hostname_regex,
+                &metadata_regex,
             )?;
             let sorter = build_sorter(&sort_regex)?;
-            let config = get_read_config(cli.default, dbs)?;
+            let config = get_read_config(cli.default, cli.aggregation, dbs)?
+                .with_value_format(build_format(magnitude, precision, sig_figs))
+                .with_x_format(build_format(x_magnitude, x_precision, x_sig_figs))
+                .with_value_axis(build_axis(log_y, y_range, y_tics, reverse_y)?)
+                .with_x_axis(build_axis(log_x, x_range, x_tics, reverse_x)?)
+                .with_style(style.build()?);
+            let config = match histogram_style {
+                Some(histogram_style) => config.with_histogram_style(histogram_style),
+                None => config,
+            };
             gnuplot(
                 &config, &exp_type, &prefix, bar, xbar, ybar, &selector, &sorter,
             )?;
@@ -421,25 +1469,155 @@ fn main() -> Result<()> {
             include_code_regex,
             exclude_type_regex,
             include_type_regex,
+            git_revision_regex,
+            hostname_regex,
+            metadata_regex,
             sort_regex,
             exp_type,
             prefix,
             bar,
             xbar,
             ybar,
+            format,
+            magnitude,
+            precision,
+            sig_figs,
+            x_magnitude,
+            x_precision,
+            x_sig_figs,
+            histogram_style,
+            log_y,
+            log_x,
+            y_range,
+            x_range,
+            y_tics,
+            x_tics,
+            reverse_y,
+            reverse_x,
+            style,
         } => {
             let selector = build_selector(
                 &exclude_code_regex,
                 &include_code_regex,
                 &exclude_type_regex,
                 &include_type_regex,
+                &git_revision_regex,
+                &hostname_regex,
+                &metadata_regex,
             )?;
             let sorter = build_sorter(&sort_regex)?;
-            let config = get_read_config(cli.default, dbs)?;
+            let config = get_read_config(cli.default, cli.aggregation, dbs)?
+                .with_value_format(build_format(magnitude, precision, sig_figs))
+                .with_x_format(build_format(x_magnitude, x_precision, x_sig_figs))
+                .with_value_axis(build_axis(log_y, y_range, y_tics, reverse_y)?)
+                .with_x_axis(build_axis(log_x, x_range, x_tics, reverse_x)?)
+                .with_style(style.build()?);
+            let config = match histogram_style {
+                Some(histogram_style) => config.with_histogram_style(histogram_style),
+                None => config,
+            };
+            let bars = Bars::from_optionals(bar, xbar, ybar)?;
             plot(
-                &config, &exp_type, &prefix, bar, xbar, ybar, &selector, &sorter,
+                &config, &exp_type, &prefix, bars, format, &selector, &sorter,
+            )?;
+        }
+        Command::Compare {
+            dbs,
+            baseline,
+            threshold,
+            confidence,
+            alpha,
+            exclude_code_regex,
+            include_code_regex,
+            exclude_type_regex,
+            include_type_regex,
+            git_revision_regex,
+            hostname_regex,
+            metadata_regex,
+        } => {
+            let selector = build_selector(
+                &exclude_code_regex,
+                &include_code_regex,
+                &exclude_type_regex,
+                &include_type_regex,
+                &git_revision_regex,
+                &hostname_regex,
+                &metadata_regex,
+            )?;
+            let options = CompareOptions {
+                confidence,
+                threshold: threshold / 100.0,
+                alpha,
+            };
+            let config = get_read_config(cli.default, cli.aggregation, dbs)?;
+            let baseline = if baseline.is_empty() {
+                None
+            } else {
+                Some(get_read_config(false, cli.aggregation, baseline)?)
+            };
+            compare(&config, baseline.as_ref(), &selector, &options)?;
+        }
+        Command::History {
+            dbs,
+            code,
+            group,
+            tag,
+            prefix,
+            bar,
+            format,
+            style,
+        } => {
+            let config =
+                get_read_config(cli.default, cli.aggregation, dbs)?.with_style(style.build()?);
+            history(
+                &config,
+                &code,
+                group.as_ref(),
+                tag,
+                prefix.as_deref(),
+                bar,
+                format,
             )?;
         }
+        Command::Add {
+            db,
+            git_revision,
+            hostname,
+            metadata,
+            datapoint,
+        } => {
+            let config =
+                get_write_config(db)?.with_metadata(run_metadata(git_revision, hostname, metadata));
+            add(&config, datapoint)?;
+        }
+        Command::Import {
+            db,
+            git_revision,
+            hostname,
+            metadata,
+            samples,
+        } => {
+            let config =
+                get_write_config(db)?.with_metadata(run_metadata(git_revision, hostname, metadata));
+            import(&config, samples)?;
+        }
+        Command::Init { database, format } => {
+            init(&database, format)?;
+        }
+        Command::Config { command } => {
+            edit_config(command)?;
+        }
+        Command::CheckConfig => {
+            check_config()?;
+        }
+        Command::Migrate { dbs, no_backup } => {
+            let dbs = if dbs.is_empty() {
+                vec![default_db_path()?]
+            } else {
+                dbs
+            };
+            migrate(&dbs, !no_backup)?;
+        }
         Command::Revert {
             db,
             code,
@@ -524,6 +1702,7 @@ fn status(config: &ReadConfig, selector: &Selector, sorter: &Sorter) -> Result<(
         .status(selector, sorter)?
         .into_iter()
         .map(|s| {
+            let last_run = s.last_run.as_ref();
             vec![
                 s.database.cell().justify(Justify::Center).bold(true),
                 s.exp_type.cell().justify(Justify::Center).bold(true),
@@ -531,6 +1710,33 @@ fn status(config: &ReadConfig, selector: &Selector, sorter: &Sorter) -> Result<(
                 s.exp_code.cell().justify(Justify::Center).bold(true),
                 s.n_active_datapoints.cell().justify(Justify::Right),
                 s.n_datapoints.cell().justify(Justify::Right),
+                last_run
+                    .and_then(|r| r.datetime())
+                    .unwrap_or_default()
+                    .cell()
+                    .justify(Justify::Center),
+                last_run
+                    .and_then(|r| r.git_revision.as_ref())
+                    .map(|r| r.chars().take(12).collect::<String>())
+                    .unwrap_or_default()
+                    .cell()
+                    .justify(Justify::Center),
+                last_run
+                    .and_then(|r| r.hostname.clone())
+                    .unwrap_or_default()
+                    .cell()
+                    .justify(Justify::Center),
+                last_run
+                    .map(|r| {
+                        r.values
+                            .iter()
+                            .map(|(k, v)| format!("{}={}", k, v))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    })
+                    .unwrap_or_default()
+                    .cell()
+                    .justify(Justify::Left),
             ]
         })
         .collect::<Vec<_>>()
@@ -545,6 +1751,10 @@ fn status(config: &ReadConfig, selector: &Selector, sorter: &Sorter) -> Result<(
                 .justify(Justify::Center)
                 .bold(true),
             "#Datapoints".cell().justify(Justify::Center).bold(true),
+            "Last Run".cell().justify(Justify::Center).bold(true),
+            "Revision".cell().justify(Justify::Center).bold(true),
+            "Host".cell().justify(Justify::Center).bold(true),
+            "Metadata".cell().justify(Justify::Center).bold(true),
         ])
         .bold(true);
 
@@ -652,13 +1862,72 @@ fn latex(
     Ok(())
 }
 
+fn export(
+    config: &ReadConfig,
+    exp_type: &str,
+    format: ExportFormat,
+    file: Option<&std::path::Path>,
+    selector: &Selector,
+    sorter: &Sorter,
+) -> Result<()> {
+    let linear_view = config.linear_experiment_view(exp_type, selector, sorter);
+    let xy_view = config.xy_experiment_view(exp_type, selector, sorter);
+
+    match (linear_view, xy_view) {
+        (Ok(_), Ok(_)) => {
+            // impossible, exp_type is known to be unique
+        }
+        (Ok(linear_view), Err(_)) => {
+            if let Some(path) = file {
+                let mut file = File::create(path)?;
+                linear_view.export(&mut file, format)?;
+            } else {
+                let mut stdout = std::io::stdout().lock();
+                linear_view.export(&mut stdout, format)?;
+            }
+        }
+        (Err(_), Ok(xy_view)) => {
+            if let Some(path) = file {
+                let mut file = File::create(path)?;
+                xy_view.export(&mut file, format)?;
+            } else {
+                let mut stdout = std::io::stdout().lock();
+                xy_view.export(&mut stdout, format)?;
+            }
+        }
+        (Err(linear_err), Err(xy_err)) => match (linear_err, xy_err) {
+            (
+                BencherError::ExperimentNotFound(_, available_linear),
+                BencherError::ExperimentNotFound(_, available_xy),
+            ) => {
+                return Err(BencherError::ExperimentNotFound(
+                    exp_type.to_string(),
+                    format!("{}, {}", available_linear, available_xy),
+                )
+                .into());
+            }
+            (e, BencherError::ExperimentNotFound(_, _)) => {
+                return Err(e.into());
+            }
+            (BencherError::ExperimentNotFound(_, _), e) => {
+                return Err(e.into());
+            }
+            (e, _) => {
+                return Err(e.into());
+            }
+        },
+    }
+
+    Ok(())
+}
+
 fn dat(
     config: &ReadConfig,
     exp_type: &str,
     prefix: &std::path::Path,
-    bar: Option<usize>,
-    xbar: Option<usize>,
-    ybar: Option<usize>,
+    bar: Option<Confidence>,
+    xbar: Option<Confidence>,
+    ybar: Option<Confidence>,
     selector: &Selector,
     sorter: &Sorter,
 ) -> Result<()> {
@@ -757,13 +2026,11 @@ fn plot(
     config: &ReadConfig,
     exp_type: &str,
     prefix: &std::path::Path,
-    bar: Option<usize>,
-    xbar: Option<usize>,
-    ybar: Option<usize>,
+    bars: Bars,
+    format: Option<ImageFormat>,
     selector: &Selector,
     sorter: &Sorter,
 ) -> Result<()> {
-    let bars = Bars::from_optionals(bar, xbar, ybar)?;
     let linear_view = config.linear_experiment_view(exp_type, selector, sorter);
     let xy_view = config.xy_experiment_view(exp_type, selector, sorter);
 
@@ -771,12 +2038,14 @@ fn plot(
         (Ok(_), Ok(_)) => {
             // impossible, exp_type is known to be unique
         }
-        (Ok(linear_view), Err(_)) => {
-            linear_view.plot(prefix, bars)?;
-        }
-        (Err(_), Ok(xy_view)) => {
-            xy_view.plot(prefix, bars)?;
-        }
+        (Ok(linear_view), Err(_)) => match format {
+            Some(format) => linear_view.image(prefix, format, bars)?,
+            None => linear_view.plot(prefix, bars)?,
+        },
+        (Err(_), Ok(xy_view)) => match format {
+            Some(format) => xy_view.image(prefix, format, bars)?,
+            None => xy_view.plot(prefix, bars)?,
+        },
         (Err(linear_err), Err(xy_err)) => match (linear_err, xy_err) {
             (
                 BencherError::ExperimentNotFound(_, available_linear),
@@ -803,6 +2072,435 @@ fn plot(
     Ok(())
 }
 
+fn compare(
+    config: &ReadConfig,
+    baseline: Option<&ReadConfig>,
+    selector: &Selector,
+    options: &CompareOptions,
+) -> Result<()> {
+    let comparisons = match baseline {
+        Some(baseline) => config.compare(baseline, selector, options)?,
+        None => config.compare_versions(selector, options)?,
+    };
+
+    let table = comparisons
+        .iter()
+        .map(|c| {
+            vec![
+                c.exp_type
+                    .clone()
+                    .cell()
+                    .justify(Justify::Center)
+                    .bold(true),
+                c.exp_label
+                    .clone()
+                    .cell()
+                    .justify(Justify::Center)
+                    .bold(true),
+                c.datapoint.clone().cell().justify(Justify::Right),
+                c.baseline.cell().justify(Justify::Right),
+                c.candidate.cell().justify(Justify::Right),
+                c.relative_change
+                    .map_or("-".to_string(), |r| format!("{:+.2}%", r * 100.0))
+                    .cell()
+                    .justify(Justify::Right),
+                match c.outside_noise {
+                    Some(true) => "outside",
+                    Some(false) => "within",
+                    None => "unknown",
+                }
+                .cell()
+                .justify(Justify::Center),
+                c.significance
+                    .map_or("-".to_string(), |s| {
+                        format!("{:.4}", s.mann_whitney.p_value)
+                    })
+                    .cell()
+                    .justify(Justify::Right),
+                c.significance
+                    .and_then(|s| s.welch)
+                    .map_or("-".to_string(), |w| format!("{:.4}", w.p_value))
+                    .cell()
+                    .justify(Justify::Right),
+                c.effect_size
+                    .map_or("-".to_string(), |d| format!("{:+.2}", d))
+                    .cell()
+                    .justify(Justify::Right),
+                c.significance
+                    .and_then(|s| s.median_ratio)
+                    .map_or("-".to_string(), |(lo, hi)| {
+                        format!("[{:.3}, {:.3}]", lo, hi)
+                    })
+                    .cell()
+                    .justify(Justify::Center),
+                if c.regression {
+                    "REGRESSION"
+                } else if c.improvement {
+                    "better"
+                } else if c.worse {
+                    "worse"
+                } else if c.baseline == c.candidate {
+                    "same"
+                } else {
+                    "noise"
+                }
+                .cell()
+                .justify(Justify::Center),
+            ]
+        })
+        .collect::<Vec<_>>()
+        .table()
+        .title(vec![
+            "Type".cell().justify(Justify::Center).bold(true),
+            "Label".cell().justify(Justify::Center).bold(true),
+            "Datapoint".cell().justify(Justify::Center).bold(true),
+            "Baseline".cell().justify(Justify::Center).bold(true),
+            "Candidate".cell().justify(Justify::Center).bold(true),
+            "Change".cell().justify(Justify::Center).bold(true),
+            format!("Noise ({})", options.confidence)
+                .cell()
+                .justify(Justify::Center)
+                .bold(true),
+            "p (Mann-Whitney)"
+                .cell()
+                .justify(Justify::Center)
+                .bold(true),
+            "p (Welch)".cell().justify(Justify::Center).bold(true),
+            "Effect size".cell().justify(Justify::Center).bold(true),
+            "Median ratio (95%)"
+                .cell()
+                .justify(Justify::Center)
+                .bold(true),
+            "Verdict".cell().justify(Justify::Center).bold(true),
+        ])
+        .bold(true);
+
+    cli_table::print_stdout(table)?;
+
+    let n_regressions = comparisons.iter().filter(|c| c.regression).count();
+    if n_regressions > 0 {
+        return Err(eyre::eyre!(
+            "{} of {} datapoints regressed by more than {}%",
+            n_regressions,
+            comparisons.len(),
+            options.threshold * 100.0
+        ));
+    }
+
+    Ok(())
+}
+
+fn history(
+    config: &ReadConfig,
+    exp_code: &str,
+    group: Option<&String>,
+    tag: Option<isize>,
+    prefix: Option<&std::path::Path>,
+    bar: Option<Confidence>,
+    format: Option<ImageFormat>,
+) -> Result<()> {
+    let view = match (group, tag) {
+        (None, None) => {
+            return Err(eyre::eyre!("to show a history a tag or group is required"));
+        }
+        (Some(_), Some(_)) => {
+            return Err(eyre::eyre!(
+                "cannot show the history of a tag and a group at the same time"
+            ));
+        }
+        (Some(group), None) => config.linear_history_view(exp_code, group)?,
+        (None, Some(tag)) => config.xy_history_view(exp_code, tag)?,
+    };
+
+    view.table(&mut std::io::stdout())?;
+    if let Some(prefix) = prefix {
+        let bars = Bars::from_optionals(bar, None, None)?;
+        match format {
+            Some(format) => view.image(prefix, format, bars)?,
+            None => view.plot(prefix, bars)?,
+        }
+    }
+
+    Ok(())
+}
+
+fn add(config: &WriteConfig, datapoint: AddCommand) -> Result<()> {
+    match datapoint {
+        AddCommand::Linear {
+            exp_type,
+            exp_label,
+            exp_code,
+            group,
+            value,
+            tag,
+            confidence,
+        } => {
+            let mut datapoint = LinearDatapoint::new(group, value);
+            for (c, lower, upper) in confidence {
+                datapoint.add_confidence(c, confidence_pair(value, lower, upper))?;
+            }
+            if let Some(tag) = tag {
+                datapoint = datapoint.tag(tag);
+            }
+
+            let linear_set = config.add_or_get_linear_set(&exp_type, &exp_label, &exp_code)?;
+            linear_set.add_datapoint(datapoint)?;
+        }
+        AddCommand::Xy {
+            exp_type,
+            exp_label,
+            exp_code,
+            x,
+            y,
+            tag,
+            xconfidence,
+            yconfidence,
+        } => {
+            let mut datapoint = XYDatapoint::new(x, y);
+            for (c, lower, upper) in xconfidence {
+                datapoint.add_x_confidence(c, confidence_pair(x, lower, upper))?;
+            }
+            for (c, lower, upper) in yconfidence {
+                datapoint.add_y_confidence(c, confidence_pair(y, lower, upper))?;
+            }
+            if let Some(tag) = tag {
+                datapoint = datapoint.tag(tag);
+            }
+
+            let xy_line = config.add_or_get_xy_line(&exp_type, &exp_label, &exp_code)?;
+            xy_line.add_datapoint(datapoint)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn import(config: &WriteConfig, samples: ImportCommand) -> Result<()> {
+    let n_datapoints = match samples {
+        ImportCommand::Linear {
+            exp_type,
+            exp_label,
+            exp_code,
+            file,
+            group_column,
+            value_column,
+            format,
+            aggregation,
+            percentile,
+            discard_samples,
+        } => {
+            let format = format.map_or_else(|| SampleFormat::from_path(&file), Ok)?;
+            let columns = LinearSampleColumns {
+                group: group_column,
+                value: value_column,
+            };
+            let raw_datapoints = read_linear_samples(&file, format, &columns)?;
+            if discard_samples {
+                let datapoints = raw_datapoints
+                    .iter()
+                    .filter_map(|d| d.aggregate(aggregation, &percentile).transpose())
+                    .collect::<Result<Vec<_>, _>>()?;
+                let n_datapoints = datapoints.len();
+                config.add_linear_datapoints(&exp_type, &exp_label, &exp_code, datapoints)?;
+                n_datapoints
+            } else {
+                config.add_linear_raw_datapoints(
+                    &exp_type,
+                    &exp_label,
+                    &exp_code,
+                    &raw_datapoints,
+                    aggregation,
+                    &percentile,
+                )?;
+                raw_datapoints.len()
+            }
+        }
+        ImportCommand::Xy {
+            exp_type,
+            exp_label,
+            exp_code,
+            file,
+            x_column,
+            y_column,
+            tag_column,
+            format,
+            aggregation,
+            percentile,
+            discard_samples,
+        } => {
+            let format = format.map_or_else(|| SampleFormat::from_path(&file), Ok)?;
+            let columns = XYSampleColumns {
+                x: x_column,
+                y: y_column,
+                tag: tag_column,
+            };
+            let raw_datapoints = read_xy_samples(&file, format, &columns)?;
+            if discard_samples {
+                let datapoints = raw_datapoints
+                    .iter()
+                    .filter_map(|d| d.aggregate(aggregation, &percentile).transpose())
+                    .collect::<Result<Vec<_>, _>>()?;
+                let n_datapoints = datapoints.len();
+                config.add_xy_datapoints(&exp_type, &exp_label, &exp_code, datapoints)?;
+                n_datapoints
+            } else {
+                config.add_xy_raw_datapoints(
+                    &exp_type,
+                    &exp_label,
+                    &exp_code,
+                    &raw_datapoints,
+                    aggregation,
+                    &percentile,
+                )?;
+                raw_datapoints.len()
+            }
+        }
+    };
+
+    println!("imported {} datapoints", n_datapoints);
+    Ok(())
+}
+
+fn init(database: &str, format: ConfigFormat) -> Result<()> {
+    let (config_path, db_path) = init_config(std::path::Path::new("."), database, format)?;
+    println!("created config at {}", config_path.to_string_lossy());
+    println!("created database at {}", db_path.to_string_lossy());
+    Ok(())
+}
+
+fn edit_config(command: ConfigCommand) -> Result<()> {
+    let mut editor = ConfigEditor::new()?;
+    match command {
+        ConfigCommand::AddLinear {
+            exp_type,
+            horizontal_label,
+            v_label,
+            v_units,
+            higher_is_better,
+            histogram_style,
+        } => {
+            editor.add_linear_experiment(
+                LinearExperiment::new(exp_type, horizontal_label, v_label, v_units)
+                    .higher_is_better(higher_is_better)
+                    .histogram_style(histogram_style),
+            );
+        }
+        ConfigCommand::AddXy {
+            exp_type,
+            x_label,
+            x_units,
+            y_label,
+            y_units,
+            higher_is_better,
+        } => {
+            editor.add_xy_experiment(
+                XYExperiment::new(exp_type, x_label, x_units, y_label, y_units)
+                    .higher_is_better(higher_is_better),
+            );
+        }
+        ConfigCommand::AddVirtual {
+            experiment:
+                AddVirtualCommand::Linear {
+                    exp_type,
+                    source,
+                    horizontal_label,
+                    v_label,
+                    v_units,
+                    v_operation,
+                    tag_operation,
+                    v_monotonicity,
+                    baseline_set,
+                    histogram_style,
+                },
+        } => {
+            editor.add_virtual_linear_experiment(
+                VirtualLinearExperiment::new(exp_type, source, horizontal_label, v_label, v_units)
+                    .v_operation(v_operation)
+                    .tag_operation(tag_operation)
+                    .v_monotonicity(v_monotonicity)
+                    .baseline_set(baseline_set)
+                    .histogram_style(histogram_style),
+            );
+        }
+        ConfigCommand::AddVirtual {
+            experiment:
+                AddVirtualCommand::Xy {
+                    exp_type,
+                    source,
+                    x_label,
+                    x_units,
+                    y_label,
+                    y_units,
+                    x_operation,
+                    y_operation,
+                    tag_operation,
+                    x_monotonicity,
+                    y_monotonicity,
+                    baseline_set,
+                },
+        } => {
+            editor.add_virtual_xy_experiment(
+                VirtualXYExperiment::new(exp_type, source, x_label, x_units, y_label, y_units)
+                    .x_operation(x_operation)
+                    .y_operation(y_operation)
+                    .tag_operation(tag_operation)
+                    .x_monotonicity(x_monotonicity)
+                    .y_monotonicity(y_monotonicity)
+                    .baseline_set(baseline_set),
+            );
+        }
+        ConfigCommand::Remove { exp_type } => {
+            editor.remove_experiment(&exp_type)?;
+        }
+    }
+
+    editor.save()?;
+    println!("updated {}", editor.path().to_string_lossy());
+    Ok(())
+}
+
+fn check_config() -> Result<()> {
+    let (path, problems) = bencher::check_config()?;
+    if problems.is_empty() {
+        println!("{}: ok", path.to_string_lossy());
+        return Ok(());
+    }
+
+    for problem in &problems {
+        println!("{}: {}", path.to_string_lossy(), problem);
+    }
+    Err(eyre::eyre!(
+        "{} problem(s) found in {}",
+        problems.len(),
+        path.to_string_lossy()
+    ))
+}
+
+fn migrate(dbs: &[std::path::PathBuf], backup: bool) -> Result<()> {
+    for db in dbs {
+        let report = WriteConfig::migrate(db, backup)?;
+        if report.from_version == report.to_version {
+            println!(
+                "{}: already at schema version {}",
+                report.database.to_string_lossy(),
+                report.to_version
+            );
+        } else {
+            println!(
+                "{}: migrated from schema version {} to {}",
+                report.database.to_string_lossy(),
+                report.from_version,
+                report.to_version
+            );
+        }
+        if let Some(backup) = report.backup {
+            println!("  backup at {}", backup.to_string_lossy());
+        }
+    }
+
+    Ok(())
+}
+
 fn revert(
     config: &WriteConfig,
     exp_code: &str,
@@ -0,0 +1,324 @@
+use crate::stat::{
+    bootstrap_median_ratio, mann_whitney_u, normal_quantile, welch_t_test, MannWhitneyU, WelchT,
+};
+use crate::*;
+
+/// Options to compare datapoints
+#[derive(Debug, Clone, Copy)]
+pub struct CompareOptions {
+    /// Confidence interval used to tell a change apart from noise
+    pub confidence: Confidence,
+
+    /// Relative change (e.g., 0.05 for 5%) above which a worse result is a regression
+    pub threshold: f64,
+
+    /// Significance level of the statistical tests, used when both datapoints have raw samples
+    pub alpha: f64,
+}
+
+impl Default for CompareOptions {
+    fn default() -> Self {
+        CompareOptions {
+            confidence: Confidence::FIVE,
+            threshold: 0.05,
+            alpha: 0.05,
+        }
+    }
+}
+
+/// Comparison of a datapoint between a baseline and a candidate
+///
+/// For bidimensional experiments, the y values are compared
+#[derive(Debug, Clone)]
+pub struct Comparison {
+    pub exp_type: String,
+    pub exp_label: String,
+
+    /// Group (linear) or tag (bidimensional) of the datapoint
+    pub datapoint: String,
+    pub baseline: Value,
+    pub candidate: Value,
+
+    /// (candidate - baseline) / baseline; None if the baseline is 0
+    pub relative_change: Option<f64>,
+
+    /// Whether the confidence intervals of the baseline and the candidate do not overlap;
+    /// None if either of them does not have the interval
+    pub outside_noise: Option<bool>,
+
+    /// Statistical tests on the raw samples; None if either datapoint does not have them
+    pub significance: Option<Significance>,
+
+    /// Standardized difference between the candidate and the baseline (Cohen's d), from the raw samples
+    /// or, failing that, estimated from the confidence intervals (assuming normal distributions)
+    pub effect_size: Option<f64>,
+
+    /// Whether the candidate is worse than the baseline
+    pub worse: bool,
+
+    /// Whether the candidate is worse than the baseline above the threshold, and the difference is
+    /// significant (if the samples are known) or outside noise (if the intervals are known)
+    pub regression: bool,
+
+    /// Whether the candidate is better than the baseline above the threshold, and the difference is
+    /// significant (if the samples are known) or outside noise (if the intervals are known)
+    pub improvement: bool,
+}
+
+/// Statistical tests of the difference between the raw samples of a baseline and a candidate
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Significance {
+    pub mann_whitney: MannWhitneyU,
+
+    /// None if either sample has less than two values
+    pub welch: Option<WelchT>,
+
+    /// 95% bootstrap confidence interval of the candidate median over the baseline median;
+    /// None if the baseline median is 0
+    pub median_ratio: Option<(f64, f64)>,
+}
+
+impl Significance {
+    const BOOTSTRAP_RESAMPLES: usize = 1000;
+
+    fn new(baseline: &[f64], candidate: &[f64]) -> Option<Self> {
+        Some(Significance {
+            mann_whitney: mann_whitney_u(baseline, candidate)?,
+            welch: welch_t_test(baseline, candidate),
+            median_ratio: bootstrap_median_ratio(
+                baseline,
+                candidate,
+                0.95,
+                Self::BOOTSTRAP_RESAMPLES,
+            ),
+        })
+    }
+
+    /// Whether the samples differ at significance level `alpha` (according to the Mann–Whitney U test)
+    pub fn is_significant(&self, alpha: f64) -> bool {
+        self.mann_whitney.p_value < alpha
+    }
+}
+
+/// One side of a comparison: a value, along with its confidence interval and raw sample (if known)
+pub(crate) struct ComparedValue {
+    pub(crate) v: Value,
+    pub(crate) interval: Option<(Value, Value)>,
+    pub(crate) sample: Option<Vec<f64>>,
+}
+
+impl ComparedValue {
+    pub(crate) fn new(v: Value, interval: Option<(Value, Value)>, sample: Option<&Sample>) -> Self {
+        ComparedValue {
+            v,
+            interval,
+            sample: sample.map(|s| match s {
+                either::Left(ints) => ints.iter().map(|&v| v as f64).collect(),
+                either::Right(floats) => floats.clone(),
+            }),
+        }
+    }
+
+    /// Standard deviation estimated from the confidence interval, assuming a normal distribution
+    fn interval_deviation(&self, confidence: Confidence) -> Option<f64> {
+        let (min, max) = self.interval?;
+        let spread = normal_quantile(confidence.upper() / 100.0)
+            - normal_quantile(confidence.lower() / 100.0);
+        (spread > 0.0).then(|| (max.as_float() - min.as_float()) / spread)
+    }
+}
+
+impl Comparison {
+    /// Compare two values, along with their confidence intervals and raw samples
+    pub(crate) fn new(
+        exp_type: &str,
+        exp_label: &str,
+        datapoint: String,
+        baseline: ComparedValue,
+        candidate: ComparedValue,
+        higher_is_better: bool,
+        options: &CompareOptions,
+    ) -> Self {
+        let (base, cand) = (baseline.v.as_float(), candidate.v.as_float());
+        let relative_change = (base != 0.0).then(|| (cand - base) / base.abs());
+
+        let outside_noise = match (baseline.interval, candidate.interval) {
+            (Some((base_min, base_max)), Some((cand_min, cand_max))) => {
+                Some(cand_min > base_max || cand_max < base_min)
+            }
+            _ => None,
+        };
+
+        let significance = match (&baseline.sample, &candidate.sample) {
+            (Some(base_sample), Some(cand_sample)) => Significance::new(base_sample, cand_sample),
+            _ => None,
+        };
+
+        let effect_size = significance
+            .and_then(|s| s.welch)
+            .map(|w| w.cohen_d)
+            .or_else(|| {
+                let base_sd = baseline.interval_deviation(options.confidence)?;
+                let cand_sd = candidate.interval_deviation(options.confidence)?;
+                let pooled_sd = ((base_sd.powi(2) + cand_sd.powi(2)) / 2.0).sqrt();
+                (pooled_sd > 0.0).then(|| (cand - base) / pooled_sd)
+            });
+
+        let (worse, better) = if higher_is_better {
+            (cand < base, cand > base)
+        } else {
+            (cand > base, cand < base)
+        };
+
+        let significant_change = relative_change.is_none_or(|c| c.abs() > options.threshold)
+            && significance
+                .map(|s| s.is_significant(options.alpha))
+                .or(outside_noise)
+                .unwrap_or(true);
+
+        Comparison {
+            exp_type: exp_type.to_string(),
+            exp_label: exp_label.to_string(),
+            datapoint,
+            baseline: baseline.v,
+            candidate: candidate.v,
+            relative_change,
+            outside_noise,
+            significance,
+            effect_size,
+            worse,
+            regression: worse && significant_change,
+            improvement: better && significant_change,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn value(v: f64, interval: Option<(f64, f64)>) -> ComparedValue {
+        ComparedValue::new(
+            Value::Float(v),
+            interval.map(|(min, max)| (Value::Float(min), Value::Float(max))),
+            None,
+        )
+    }
+
+    fn sampled(sample: &[f64]) -> ComparedValue {
+        let mut sorted = sample.to_vec();
+        sorted.sort_by(f64::total_cmp);
+        ComparedValue::new(
+            Value::Float(sorted[sorted.len() / 2]),
+            None,
+            Some(&either::Right(sample.to_vec())),
+        )
+    }
+
+    fn compare(
+        baseline: ComparedValue,
+        candidate: ComparedValue,
+        higher_is_better: bool,
+    ) -> Comparison {
+        Comparison::new(
+            "lat",
+            "Latency",
+            "get".to_string(),
+            baseline,
+            candidate,
+            higher_is_better,
+            &CompareOptions::default(),
+        )
+    }
+
+    #[test]
+    fn comparison_regression() {
+        let c = compare(value(100.0, None), value(120.0, None), false);
+        assert_eq!(c.relative_change, Some(0.2));
+        assert!(c.worse);
+        assert!(c.regression);
+        assert!(!c.improvement);
+
+        let c = compare(value(100.0, None), value(80.0, None), false);
+        assert!(!c.worse);
+        assert!(!c.regression);
+        assert!(c.improvement);
+
+        let c = compare(value(100.0, None), value(100.0, None), false);
+        assert!(!c.worse);
+        assert!(!c.regression);
+        assert!(!c.improvement);
+    }
+
+    #[test]
+    fn comparison_higher_is_better() {
+        let c = compare(value(100.0, None), value(120.0, None), true);
+        assert!(!c.worse);
+        assert!(!c.regression);
+        assert!(c.improvement);
+
+        let c = compare(value(100.0, None), value(80.0, None), true);
+        assert!(c.worse);
+        assert!(c.regression);
+        assert!(!c.improvement);
+    }
+
+    #[test]
+    fn comparison_threshold() {
+        // changes within the threshold (5%) are neither regressions nor improvements
+        let c = compare(value(100.0, None), value(104.0, None), false);
+        assert!(c.worse);
+        assert!(!c.regression);
+
+        let c = compare(value(100.0, None), value(96.0, None), false);
+        assert!(!c.worse);
+        assert!(!c.improvement);
+
+        // with a zero baseline there is no relative change, so any change counts
+        let c = compare(value(0.0, None), value(1.0, None), false);
+        assert_eq!(c.relative_change, None);
+        assert!(c.regression);
+    }
+
+    #[test]
+    fn comparison_noise() {
+        // overlapping intervals are noise, however large the change
+        let c = compare(
+            value(100.0, Some((80.0, 130.0))),
+            value(120.0, Some((110.0, 140.0))),
+            false,
+        );
+        assert_eq!(c.outside_noise, Some(false));
+        assert!(c.worse);
+        assert!(!c.regression);
+
+        let c = compare(
+            value(100.0, Some((95.0, 105.0))),
+            value(80.0, Some((75.0, 85.0))),
+            false,
+        );
+        assert_eq!(c.outside_noise, Some(true));
+        assert!(c.improvement);
+    }
+
+    #[test]
+    fn comparison_significance() {
+        // the samples overlap too much for the change to be significant
+        let c = compare(
+            sampled(&[10.0, 14.0, 11.0, 13.0, 12.0]),
+            sampled(&[11.0, 15.0, 12.0, 14.0, 13.0]),
+            false,
+        );
+        assert!(c.significance.is_some());
+        assert!(c.worse);
+        assert!(!c.regression);
+
+        let c = compare(
+            sampled(&[10.0, 11.0, 12.0, 13.0, 14.0, 10.5, 11.5, 12.5]),
+            sampled(&[20.0, 21.0, 22.0, 23.0, 24.0, 20.5, 21.5, 22.5]),
+            false,
+        );
+        assert!(c.significance.unwrap().is_significant(0.05));
+        assert!(c.regression);
+    }
+}
@@ -1,23 +1,58 @@
-use std::fs::File;
-use std::io::BufReader;
 use std::path::{Path, PathBuf};
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 use crate::model::*;
 use crate::*;
 
 pub(crate) const BENCHER_CONFIG_FILENAME: &str = ".bencher-config";
-pub(crate) const COLORS: [&str; 5] = ["f6511d", "ffb400", "00a6ed", "7fb800", "0d2c54"];
 
-fn find_config_dir() -> BencherResult<PathBuf> {
+/// Config filenames, in any of the supported formats
+/// (the format of `.bencher-config` is detected from its contents)
+const BENCHER_CONFIG_FILENAMES: [&str; 5] = [
+    BENCHER_CONFIG_FILENAME,
+    ".bencher-config.json",
+    ".bencher-config.toml",
+    ".bencher-config.yaml",
+    ".bencher-config.yml",
+];
+
+/// Configs in a directory
+fn configs_in_dir(dir: &Path) -> Vec<PathBuf> {
+    BENCHER_CONFIG_FILENAMES
+        .iter()
+        .map(|filename| dir.join(filename))
+        .filter(|file| file.exists())
+        .collect()
+}
+
+/// The config in a directory, if it has one
+///
+/// Fails if the directory has more than one config (e.g., both a JSON and a TOML one)
+fn config_in_dir(dir: &Path) -> BencherResult<Option<PathBuf>> {
+    let mut configs = configs_in_dir(dir);
+    match configs.len() {
+        0 => Ok(None),
+        1 => Ok(configs.pop()),
+        _ => Err(BencherError::MultipleConfigs(
+            dir.into(),
+            configs
+                .iter()
+                .filter_map(|c| c.file_name())
+                .map(|c| c.to_string_lossy().to_string())
+                .collect(),
+        )),
+    }
+}
+
+/// Find the config in the closest ancestor of the current dir which has one
+fn find_config() -> BencherResult<PathBuf> {
     let mut dir: PathBuf = Path::new(".")
         .canonicalize()
         .map_err(|e| BencherError::io_err(e, "failed to canonicalize current dir name"))?;
     loop {
-        let file = dir.join(BENCHER_CONFIG_FILENAME);
-        if file.exists() {
-            return Ok(dir);
+        if let Some(config) = config_in_dir(&dir)? {
+            return Ok(config);
         }
 
         if dir.parent().is_none() {
@@ -30,9 +65,343 @@ fn find_config_dir() -> BencherResult<PathBuf> {
     Err(BencherError::NotFound.into())
 }
 
+/// A config merged with the configs it includes and with the configs of its parent directories
+/// (see `ParsedConfig` for the precedence rules)
+#[derive(Debug)]
+struct MergedConfig {
+    config: ParsedConfig,
+
+    /// Path to the default database, resolved relative to the config which sets it
+    default_db_path: Option<PathBuf>,
+}
+
+impl MergedConfig {
+    /// Read the config at a given path and merge it
+    fn from_path(path: &Path) -> BencherResult<Self> {
+        Self::new(path, ParsedConfig::from_path(path)?)
+    }
+
+    /// Merge a config (as if read from `path`) with the configs it includes and
+    /// the configs of its parent directories, up to the first root config
+    fn new(path: &Path, config: ParsedConfig) -> BencherResult<Self> {
+        let mut merged = MergedConfig {
+            config: ParsedConfig::default(),
+            default_db_path: None,
+        };
+        let mut origins = HashMap::new();
+        let mut merged_paths = HashSet::new();
+
+        let path = canonical_config_path(path)?;
+        let mut root = config.root;
+        merged.add(&path, config, &mut origins, &mut merged_paths, &mut vec![])?;
+
+        let mut dir = path.clone();
+        dir.pop();
+        while !root && dir.pop() {
+            if let Some(parent_path) = config_in_dir(&dir)? {
+                let parent = ParsedConfig::from_path(&parent_path)?;
+                root = parent.root;
+                merged.add(
+                    &parent_path,
+                    parent,
+                    &mut origins,
+                    &mut merged_paths,
+                    &mut vec![],
+                )?;
+            }
+        }
+
+        Ok(merged)
+    }
+
+    /// Merge a config (with lower precedence than the ones merged so far), followed by the
+    /// configs it includes
+    ///
+    /// Configs included more than once are only merged once, but configs cannot include
+    /// themselves (directly or not)
+    fn add(
+        &mut self,
+        path: &Path,
+        config: ParsedConfig,
+        origins: &mut HashMap<String, PathBuf>,
+        merged_paths: &mut HashSet<PathBuf>,
+        including: &mut Vec<PathBuf>,
+    ) -> BencherResult<()> {
+        if let Some(start) = including.iter().position(|p| p == path) {
+            let mut cycle = including[start..].to_vec();
+            cycle.push(path.into());
+            return Err(BencherError::IncludeCycle(cycle));
+        }
+        if !merged_paths.insert(path.into()) {
+            return Ok(());
+        }
+
+        if self.default_db_path.is_none() {
+            self.default_db_path = config
+                .default_database_filepath
+                .as_ref()
+                .map(|db| path.with_file_name(db));
+        }
+        self.config.merge(&config, path, origins)?;
+
+        including.push(path.into());
+        for include in config.include.iter().flatten() {
+            let include_path = canonical_config_path(&path.with_file_name(include))?;
+            let included = ParsedConfig::from_path(&include_path)?;
+            self.add(&include_path, included, origins, merged_paths, including)?;
+        }
+        including.pop();
+
+        Ok(())
+    }
+
+    fn default_db_path(&self, path: &Path) -> BencherResult<PathBuf> {
+        self.default_db_path
+            .clone()
+            .ok_or_else(|| BencherError::NoDefaultDatabase(path.into()))
+    }
+}
+
+fn canonical_config_path(path: &Path) -> BencherResult<PathBuf> {
+    path.canonicalize()
+        .map_err(|e| BencherError::io_err(e, format!("failed to canonicalize {:?}", path)))
+}
+
+/// Path to the default database, as set in the config
+pub fn default_db_path() -> BencherResult<PathBuf> {
+    let config_path = find_config()?;
+    MergedConfig::from_path(&config_path)?.default_db_path(&config_path)
+}
+
+/// Check the config at the default path, without opening any database
+///
+/// Returns the path to the config, along with every problem with its experiment definitions
+pub fn check_config() -> BencherResult<(PathBuf, Vec<ConfigProblem>)> {
+    let config_path = find_config()?;
+
+    let merged = MergedConfig::from_path(&config_path)?;
+    Ok((config_path, merged.config.problems()))
+}
+
+/// Create a config in a given directory, along with its (empty) default database
+///
+/// JSON configs are written to `.bencher-config`, and other formats to `.bencher-config.<ext>`
+///
+/// Fails if there already is a config in the directory
+/// Returns the paths to the config and to the database
+pub fn init_config(
+    dir: &Path,
+    database: &str,
+    format: ConfigFormat,
+) -> BencherResult<(PathBuf, PathBuf)> {
+    if let Some(config_path) = configs_in_dir(dir).into_iter().next() {
+        return Err(BencherError::ConfigExists(config_path));
+    }
+
+    let config_path = match format {
+        ConfigFormat::Json => dir.join(BENCHER_CONFIG_FILENAME),
+        _ => dir.join(format!(
+            "{}.{}",
+            BENCHER_CONFIG_FILENAME,
+            format.extension()
+        )),
+    };
+    let db_path = dir.join(database);
+    WriteConfig::from_file(&db_path)?;
+    ParsedConfig::new(database).to_path(&config_path, format)?;
+    Ok((config_path, db_path))
+}
+
+/// Editor of the experiment definitions of a config
+///
+/// Changes are only written with `save`, which validates the config first
+#[derive(Debug)]
+pub struct ConfigEditor {
+    path: PathBuf,
+    config: ParsedConfig,
+
+    /// Format the config is written back in (the one it was read in)
+    format: ConfigFormat,
+}
+
+impl ConfigEditor {
+    /// Open the config at the default path
+    pub fn new() -> BencherResult<Self> {
+        let config_path = find_config()?;
+        Self::from_file(&config_path)
+    }
+
+    /// Open the config at a given path
+    pub fn from_file(path: &Path) -> BencherResult<Self> {
+        let (config, format) = ParsedConfig::from_path_with_format(path)?;
+        Ok(ConfigEditor {
+            path: path.into(),
+            config,
+            format,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn add_linear_experiment(&mut self, experiment: LinearExperiment) {
+        self.config
+            .linear_experiments
+            .get_or_insert_with(Vec::new)
+            .push(experiment);
+    }
+
+    pub fn add_xy_experiment(&mut self, experiment: XYExperiment) {
+        self.config
+            .xy_experiments
+            .get_or_insert_with(Vec::new)
+            .push(experiment);
+    }
+
+    pub fn add_virtual_linear_experiment(&mut self, experiment: VirtualLinearExperiment) {
+        self.config
+            .virtual_linear_experiments
+            .get_or_insert_with(Vec::new)
+            .push(experiment);
+    }
+
+    pub fn add_virtual_xy_experiment(&mut self, experiment: VirtualXYExperiment) {
+        self.config
+            .virtual_xy_experiments
+            .get_or_insert_with(Vec::new)
+            .push(experiment);
+    }
+
+    /// Remove the experiment (of any kind) with a given type
+    pub fn remove_experiment(&mut self, exp_type: &str) -> BencherResult<()> {
+        fn remove<T>(experiments: &mut Option<Vec<T>>, matches: impl Fn(&T) -> bool) -> usize {
+            let Some(experiments) = experiments else {
+                return 0;
+            };
+            let len = experiments.len();
+            experiments.retain(|e| !matches(e));
+            len - experiments.len()
+        }
+
+        let removed = remove(&mut self.config.linear_experiments, |e| {
+            e.exp_type == exp_type
+        }) + remove(&mut self.config.xy_experiments, |e| e.exp_type == exp_type)
+            + remove(&mut self.config.virtual_linear_experiments, |e| {
+                e.exp_type == exp_type
+            })
+            + remove(&mut self.config.virtual_xy_experiments, |e| {
+                e.exp_type == exp_type
+            });
+
+        if removed == 0 {
+            return Err(BencherError::ExperimentNotFound(
+                exp_type.to_string(),
+                self.experiments_as_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn experiments_as_string(&self) -> String {
+        let config = &self.config;
+        config
+            .linear_experiments
+            .iter()
+            .flatten()
+            .map(|e| e.exp_type.clone())
+            .chain(
+                config
+                    .xy_experiments
+                    .iter()
+                    .flatten()
+                    .map(|e| e.exp_type.clone()),
+            )
+            .chain(
+                config
+                    .virtual_linear_experiments
+                    .iter()
+                    .flatten()
+                    .map(|e| e.exp_type.clone()),
+            )
+            .chain(
+                config
+                    .virtual_xy_experiments
+                    .iter()
+                    .flatten()
+                    .map(|e| e.exp_type.clone()),
+            )
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+
+    /// Validate the config (merged with the configs it includes and the ones of its parent
+    /// directories) and write it back
+    /// Nothing is written if the config is invalid (e.g., an experiment type is duplicated,
+    /// or a virtual experiment is left without its source)
+    /// The config is written in the format it was read in, but comments are not kept
+    pub fn save(&self) -> BencherResult<()> {
+        MergedConfig::new(&self.path, self.config.clone())?
+            .config
+            .validate()?;
+        self.config.to_path(&self.path, self.format)
+    }
+}
+
+/// Copy a database to `<path>.v<version>.bak`
+///
+/// Existing files are never overwritten: if that backup already exists,
+/// the copy goes to the first free `<path>.v<version>.<n>.bak`
+fn backup_db(path: &Path, version: i64) -> BencherResult<PathBuf> {
+    let io_err = |e| BencherError::io_err(e, format!("backing up {}", path.to_string_lossy()));
+
+    let mut attempt = 0;
+    loop {
+        let mut backup_path = path.as_os_str().to_owned();
+        if attempt == 0 {
+            backup_path.push(format!(".v{}.bak", version));
+        } else {
+            backup_path.push(format!(".v{}.{}.bak", version, attempt));
+        }
+        let backup_path = PathBuf::from(backup_path);
+
+        match std::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&backup_path)
+        {
+            Ok(mut backup) => {
+                let mut db = std::fs::File::open(path).map_err(io_err)?;
+                std::io::copy(&mut db, &mut backup).map_err(io_err)?;
+                return Ok(backup_path);
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => attempt += 1,
+            Err(e) => return Err(io_err(e)),
+        }
+    }
+}
+
+/// Outcome of upgrading the schema of a database
+#[derive(Debug)]
+pub struct MigrationReport {
+    pub database: PathBuf,
+
+    /// Schema version before the migration
+    pub from_version: i64,
+
+    /// Schema version after the migration
+    pub to_version: i64,
+
+    /// Copy of the database before the migration, if one was made
+    pub backup: Option<PathBuf>,
+}
+
 #[derive(Debug)]
 pub struct WriteConfig {
     db: DbWriteBackend,
+
+    /// Run metadata recorded with every datapoint added through this config
+    metadata: RunMetadata,
 }
 
 impl WriteConfig {
@@ -43,18 +412,36 @@ impl WriteConfig {
     pub fn from_file(path: &std::path::Path) -> BencherResult<Self> {
         let db = DbWriteBackend::new(path)?;
 
-        Ok(Self { db })
+        Ok(Self {
+            db,
+            metadata: RunMetadata::default(),
+        })
     }
 
     /// Create a new config, looking at the default path for the filename
     pub fn new() -> BencherResult<Self> {
-        let mut config_path = find_config_dir()?;
-        config_path.push(BENCHER_CONFIG_FILENAME);
+        Self::from_file(&default_db_path()?)
+    }
 
-        let inner_config = ParsedConfig::from_path(&config_path)?;
-        config_path.set_file_name(inner_config.default_database_filepath);
+    /// Upgrade the schema of a database to the current version, in place
+    ///
+    /// If `backup` is set and the database needs upgrading,
+    /// it is first copied to `<path>.v<version>.bak` (see [`backup_db`])
+    pub fn migrate(path: &std::path::Path, backup: bool) -> BencherResult<MigrationReport> {
+        let from_version = db_schema_version(path)?;
+        let backup = if backup && from_version < SCHEMA_VERSION {
+            Some(backup_db(path, from_version)?)
+        } else {
+            None
+        };
 
-        Self::from_file(&config_path)
+        Self::from_file(path)?;
+        Ok(MigrationReport {
+            database: path.into(),
+            from_version,
+            to_version: SCHEMA_VERSION,
+            backup,
+        })
     }
 
     pub fn to_read_config(self, inner_config: ParsedConfig) -> BencherResult<ReadConfig> {
@@ -65,9 +452,17 @@ impl WriteConfig {
     pub fn from_conn_and_config(conn: rusqlite::Connection) -> BencherResult<Self> {
         Ok(Self {
             db: DbWriteBackend::from_conn(conn)?,
+            metadata: RunMetadata::default(),
         })
     }
 
+    /// Record the given run metadata with every datapoint added through this config
+    /// (and the handles it returns)
+    pub fn with_metadata(mut self, metadata: RunMetadata) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
     /// Linear Experiments
     ///
 
@@ -88,9 +483,29 @@ impl WriteConfig {
             .map(|x| x.expect("just inserted this linear set, it *should* exist"))
     }
 
+    /// Get a linear experiment, adding it if it does not exist yet
+    ///
+    /// Errors if the code already exists with a different type or label
+    pub fn add_or_get_linear_set(
+        &self,
+        exp_type: &str,
+        exp_label: &str,
+        exp_code: &str,
+    ) -> BencherResult<LinearSetHandle<'_>> {
+        if !self.db.experiment_exists(exp_type, exp_label, exp_code)? {
+            self.db.insert_linear_set(exp_type, exp_label, exp_code)?;
+        }
+
+        self.get_linear_set(exp_code)
+            .map(|x| x.expect("this linear set was just checked, it *should* exist"))
+    }
+
     /// Get the linear set handle
     pub fn get_linear_set(&self, exp_code: &str) -> BencherResult<Option<LinearSetHandle<'_>>> {
-        self.db.get_linear_set(exp_code)
+        Ok(self
+            .db
+            .get_linear_set(exp_code)?
+            .map(|handle| handle.with_metadata(self.metadata.clone())))
     }
 
     /// XY Experiments
@@ -113,13 +528,149 @@ impl WriteConfig {
             .map(|x| x.expect("just inserted this xy line, it *should* exist"))
     }
 
+    /// Get a bidimensional experiment, adding it if it does not exist yet
+    ///
+    /// Errors if the code already exists with a different type or label
+    pub fn add_or_get_xy_line(
+        &self,
+        exp_type: &str,
+        exp_label: &str,
+        exp_code: &str,
+    ) -> BencherResult<XYLineHandle<'_>> {
+        if !self.db.experiment_exists(exp_type, exp_label, exp_code)? {
+            self.db.insert_xy_line(exp_type, exp_label, exp_code)?;
+        }
+
+        self.get_xy_line(exp_code)
+            .map(|x| x.expect("this xy line was just checked, it *should* exist"))
+    }
+
     pub fn get_xy_line(&self, exp_code: &str) -> BencherResult<Option<XYLineHandle<'_>>> {
-        self.db.get_xy_line(exp_code)
+        Ok(self
+            .db
+            .get_xy_line(exp_code)?
+            .map(|handle| handle.with_metadata(self.metadata.clone())))
     }
 
     pub fn list_codes(&self) -> BencherResult<Vec<String>> {
         self.db.list_codes()
     }
+
+    /// Add a batch of datapoints to a linear experiment (adding it if needed)
+    ///
+    /// All the datapoints are written in a single transaction
+    pub fn add_linear_datapoints(
+        &self,
+        exp_type: &str,
+        exp_label: &str,
+        exp_code: &str,
+        datapoints: Vec<LinearDatapoint>,
+    ) -> BencherResult<()> {
+        let transaction = self.db.transaction()?;
+        let linear_set = self.add_or_get_linear_set(exp_type, exp_label, exp_code)?;
+        for datapoint in datapoints {
+            linear_set.add_datapoint(datapoint)?;
+        }
+        transaction.commit().map_err(|e| e.into())
+    }
+
+    /// Aggregate a batch of raw datapoints and add them to a linear experiment (adding it if needed),
+    /// keeping the raw samples
+    ///
+    /// The confidence intervals at the given percentiles are computed in addition to the default ones.
+    /// All the datapoints are written in a single transaction
+    pub fn add_linear_raw_datapoints(
+        &self,
+        exp_type: &str,
+        exp_label: &str,
+        exp_code: &str,
+        raw_datapoints: &[LinearRawDatapoint],
+        aggregation: Aggregation,
+        confidences: &[Confidence],
+    ) -> BencherResult<()> {
+        let transaction = self.db.transaction()?;
+        let linear_set = self.add_or_get_linear_set(exp_type, exp_label, exp_code)?;
+        for raw_datapoint in raw_datapoints {
+            linear_set.add_raw_datapoint(raw_datapoint, aggregation, confidences)?;
+        }
+        transaction.commit().map_err(|e| e.into())
+    }
+
+    /// Add a batch of datapoints to a bidimensional experiment (adding it if needed)
+    ///
+    /// All the datapoints are written in a single transaction
+    pub fn add_xy_datapoints(
+        &self,
+        exp_type: &str,
+        exp_label: &str,
+        exp_code: &str,
+        datapoints: Vec<XYDatapoint>,
+    ) -> BencherResult<()> {
+        let transaction = self.db.transaction()?;
+        let xy_line = self.add_or_get_xy_line(exp_type, exp_label, exp_code)?;
+        for datapoint in datapoints {
+            xy_line.add_datapoint(datapoint)?;
+        }
+        transaction.commit().map_err(|e| e.into())
+    }
+
+    /// Aggregate a batch of raw datapoints and add them to a bidimensional experiment (adding it if needed),
+    /// keeping the raw samples
+    ///
+    /// The confidence intervals at the given percentiles are computed in addition to the default ones.
+    /// All the datapoints are written in a single transaction
+    pub fn add_xy_raw_datapoints(
+        &self,
+        exp_type: &str,
+        exp_label: &str,
+        exp_code: &str,
+        raw_datapoints: &[XYRawDatapoint],
+        aggregation: Aggregation,
+        confidences: &[Confidence],
+    ) -> BencherResult<()> {
+        let transaction = self.db.transaction()?;
+        let xy_line = self.add_or_get_xy_line(exp_type, exp_label, exp_code)?;
+        for raw_datapoint in raw_datapoints {
+            xy_line.add_raw_datapoint(raw_datapoint, aggregation, confidences)?;
+        }
+        transaction.commit().map_err(|e| e.into())
+    }
+}
+
+/// Name of the source in the baseline set of a virtual experiment, in its expressions
+const BASELINE_VARIABLE: &str = "baseline";
+
+/// Datapoints of the sources of a virtual experiment, exposed to its expressions
+///
+/// The datapoints of the other sources are keyed by set (or line) label and group (or tag),
+/// to be joined with the datapoints of the primary source
+struct JoinedSources<K> {
+    /// Experiment type of the primary source
+    primary: String,
+
+    /// Variables of the datapoints of each of the other sources
+    others: Vec<HashMap<(String, K), ExpressionVariables>>,
+
+    /// Variables of the datapoints of the baseline set (or line) of the primary source,
+    /// by group (or tag)
+    baseline: Option<HashMap<K, ExpressionVariables>>,
+}
+
+impl<K: Clone + Eq + std::hash::Hash> JoinedSources<K> {
+    /// Variables of the datapoints of the other sources (and of the baseline) joined with a
+    /// datapoint of the primary one
+    /// None if any of them does not have a matching datapoint
+    fn variables(&self, label: &str, key: &K) -> Option<ExpressionVariables> {
+        let key = (label.to_string(), key.clone());
+        let mut variables = ExpressionVariables::new();
+        for source in &self.others {
+            variables.extend(source.get(&key)?);
+        }
+        if let Some(baseline) = &self.baseline {
+            variables.extend(baseline.get(&key.1)?);
+        }
+        Some(variables)
+    }
 }
 
 #[derive(Debug)]
@@ -129,6 +680,23 @@ pub struct ReadConfig {
     xy_experiments: Vec<XYExperiment>,
     virtual_linear_experiments: Vec<VirtualLinearExperiment>,
     virtual_xy_experiments: Vec<VirtualXYExperiment>,
+
+    /// If set, datapoints with stored samples are re-aggregated with this statistic
+    aggregation: Option<Aggregation>,
+
+    /// Overrides of the formats of the values (v or y) and of the x values of the experiments
+    value_format: AxisFormat,
+    x_format: AxisFormat,
+
+    /// Overrides of the styles of the value (v or y) axes and of the x axes of the experiments
+    value_axis: AxisStyle,
+    x_axis: AxisStyle,
+
+    /// If set, overrides the histogram style of the linear experiments
+    histogram_style: Option<HistogramStyle>,
+
+    /// Style of the plots: the one in the config, with the overrides
+    style: PlotStyle,
 }
 
 impl ReadConfig {
@@ -141,15 +709,11 @@ impl ReadConfig {
         db_paths: impl Iterator<Item = &'a std::path::Path>,
         with_default: bool,
     ) -> BencherResult<Self> {
-        let mut config_path: PathBuf = config_path.into();
-        let config_file = File::open(&config_path)
-            .map_err(|e| BencherError::io_err(e, format!("opening {:?}", &config_path)))?;
-        let reader = BufReader::new(config_file);
-        let inner_config: ParsedConfig = serde_json::from_reader(reader)?;
+        let merged = MergedConfig::from_path(config_path)?;
+        merged.config.validate()?;
 
         let db = if with_default {
-            config_path.set_file_name(inner_config.default_database_filepath);
-            let db = DbReadBackend::new(&config_path, db_paths)?;
+            let db = DbReadBackend::new(&merged.default_db_path(config_path)?, db_paths)?;
             db
         } else {
             DbReadBackend::from_paths(db_paths)?
@@ -157,10 +721,17 @@ impl ReadConfig {
 
         Ok(Self {
             db,
-            linear_experiments: inner_config.linear_experiments.unwrap_or(vec![]),
-            xy_experiments: inner_config.xy_experiments.unwrap_or(vec![]),
-            virtual_linear_experiments: inner_config.virtual_linear_experiments.unwrap_or(vec![]),
-            virtual_xy_experiments: inner_config.virtual_xy_experiments.unwrap_or(vec![]),
+            linear_experiments: merged.config.linear_experiments.unwrap_or(vec![]),
+            xy_experiments: merged.config.xy_experiments.unwrap_or(vec![]),
+            virtual_linear_experiments: merged.config.virtual_linear_experiments.unwrap_or(vec![]),
+            virtual_xy_experiments: merged.config.virtual_xy_experiments.unwrap_or(vec![]),
+            style: merged.config.style.unwrap_or_default(),
+            aggregation: None,
+            value_format: AxisFormat::new(),
+            x_format: AxisFormat::new(),
+            value_axis: AxisStyle::new(),
+            x_axis: AxisStyle::new(),
+            histogram_style: None,
         })
     }
 
@@ -168,8 +739,7 @@ impl ReadConfig {
     ///     looking at the default path for the config
     ///     and given a set of paths to DBs
     pub fn with_dbs<'a>(paths: impl Iterator<Item = &'a std::path::Path>) -> BencherResult<Self> {
-        let mut config_path = find_config_dir()?;
-        config_path.push(BENCHER_CONFIG_FILENAME);
+        let config_path = find_config()?;
         Self::from_files(&config_path, paths, false)
     }
 
@@ -180,8 +750,7 @@ impl ReadConfig {
     pub fn with_dbs_and_default<'a>(
         paths: impl Iterator<Item = &'a std::path::Path>,
     ) -> BencherResult<Self> {
-        let mut config_path = find_config_dir()?;
-        config_path.push(BENCHER_CONFIG_FILENAME);
+        let config_path = find_config()?;
         Self::from_files(&config_path, paths, true)
     }
 
@@ -189,8 +758,7 @@ impl ReadConfig {
     ///     looking at the default path for the config,
     ///     using the default db
     pub fn new() -> BencherResult<Self> {
-        let mut config_path = find_config_dir()?;
-        config_path.push(BENCHER_CONFIG_FILENAME);
+        let config_path = find_config()?;
         Self::from_files(&config_path, std::iter::empty(), true)
     }
 
@@ -199,15 +767,67 @@ impl ReadConfig {
         dbs: Vec<rusqlite::Connection>,
         inner_config: ParsedConfig,
     ) -> BencherResult<Self> {
+        inner_config.validate()?;
         Ok(Self {
             db: DbReadBackend::from_conns(dbs)?,
             linear_experiments: inner_config.linear_experiments.unwrap_or(vec![]),
             xy_experiments: inner_config.xy_experiments.unwrap_or(vec![]),
             virtual_linear_experiments: inner_config.virtual_linear_experiments.unwrap_or(vec![]),
             virtual_xy_experiments: inner_config.virtual_xy_experiments.unwrap_or(vec![]),
+            style: inner_config.style.unwrap_or_default(),
+            aggregation: None,
+            value_format: AxisFormat::new(),
+            x_format: AxisFormat::new(),
+            value_axis: AxisStyle::new(),
+            x_axis: AxisStyle::new(),
+            histogram_style: None,
         })
     }
 
+    /// Re-aggregate the datapoints which have stored samples with a given statistic
+    ///
+    /// Datapoints without stored samples are shown as they were added
+    pub fn with_aggregation(mut self, aggregation: Aggregation) -> Self {
+        self.aggregation = Some(aggregation);
+        self
+    }
+
+    /// Override the format of the values (v or y) of the experiments
+    pub fn with_value_format(mut self, value_format: AxisFormat) -> Self {
+        self.value_format = value_format;
+        self
+    }
+
+    /// Override the format of the x values of the bidimensional experiments
+    pub fn with_x_format(mut self, x_format: AxisFormat) -> Self {
+        self.x_format = x_format;
+        self
+    }
+
+    /// Override the style of the value (v or y) axes of the experiments
+    pub fn with_value_axis(mut self, value_axis: AxisStyle) -> Self {
+        self.value_axis = value_axis;
+        self
+    }
+
+    /// Override the style of the x axes of the bidimensional experiments
+    pub fn with_x_axis(mut self, x_axis: AxisStyle) -> Self {
+        self.x_axis = x_axis;
+        self
+    }
+
+    /// Override the histogram style of the linear experiments
+    pub fn with_histogram_style(mut self, histogram_style: HistogramStyle) -> Self {
+        self.histogram_style = Some(histogram_style);
+        self
+    }
+
+    /// Override the options of the style of the plots which are set by a given style
+    pub fn with_style(mut self, style: PlotStyle) -> Self {
+        self.style = self.style.overridden_by(style);
+        self
+    }
+
     pub fn status(
         &self,
         selector: &Selector,
@@ -216,6 +836,128 @@ impl ReadConfig {
         self.db.status(selector, sorter)
     }
 
+    /// Get the raw sample behind the active version of a linear datapoint, if it was stored
+    pub fn get_linear_sample(&self, exp_code: &str, group: &str) -> BencherResult<Option<Sample>> {
+        self.db.get_linear_sample(exp_code, group)
+    }
+
+    /// Get the raw x and y samples behind the active version of a bidimensional datapoint, if they were stored
+    pub fn get_xy_samples(
+        &self,
+        exp_code: &str,
+        tag: isize,
+    ) -> BencherResult<Option<(Sample, Sample)>> {
+        self.db.get_xy_samples(exp_code, tag)
+    }
+
+    /// Get every version of a linear datapoint (including reverted ones), in the order they were added
+    pub fn get_linear_history(
+        &self,
+        exp_code: &str,
+        group: &str,
+    ) -> BencherResult<Vec<DatapointVersion<LinearDatapoint>>> {
+        self.db.get_linear_history(exp_code, group)
+    }
+
+    /// Get every version of a bidimensional datapoint (including reverted ones), in the order they were added
+    pub fn get_xy_history(
+        &self,
+        exp_code: &str,
+        tag: isize,
+    ) -> BencherResult<Vec<DatapointVersion<XYDatapoint>>> {
+        self.db.get_xy_history(exp_code, tag)
+    }
+
+    pub fn linear_history_view(&self, exp_code: &str, group: &str) -> BencherResult<HistoryView> {
+        let (exp_type, exp_label) = self.db.get_experiment(exp_code)?;
+        let experiment = self.find_linear_experiment(&exp_type).ok_or_else(|| {
+            BencherError::ExperimentNotFound(exp_type.clone(), self.linear_experiments_as_string())
+        })?;
+
+        Ok(HistoryView::from_linear(
+            experiment,
+            exp_code,
+            &exp_label,
+            group,
+            self.get_linear_history(exp_code, group)?,
+        )?
+        .with_style(self.style.clone()))
+    }
+
+    pub fn xy_history_view(&self, exp_code: &str, tag: isize) -> BencherResult<HistoryView> {
+        let (exp_type, exp_label) = self.db.get_experiment(exp_code)?;
+        let experiment = self.find_xy_experiment(&exp_type).ok_or_else(|| {
+            BencherError::ExperimentNotFound(exp_type.clone(), self.xy_experiments_as_string())
+        })?;
+
+        Ok(HistoryView::from_xy(
+            experiment,
+            exp_code,
+            &exp_label,
+            tag,
+            self.get_xy_history(exp_code, tag)?,
+        )?
+        .with_style(self.style.clone()))
+    }
+
+    /// Get the active linear datapoints of an experiment code,
+    /// re-aggregating the ones with stored samples if requested
+    fn get_linear_datapoints(&self, exp_code: &str) -> BencherResult<Vec<LinearDatapoint>> {
+        let values = self.db.get_linear_datapoints(exp_code)?;
+        let aggregation = match self.aggregation {
+            Some(aggregation) => aggregation,
+            None => return Ok(values),
+        };
+
+        values
+            .into_iter()
+            .map(
+                |datapoint| match self.get_linear_sample(exp_code, &datapoint.group)? {
+                    Some(sample) => {
+                        let confidences =
+                            datapoint.v_confidence.keys().copied().collect::<Vec<_>>();
+                        Ok(LinearRawDatapoint::new(datapoint.group.clone(), sample)
+                            .tag(datapoint.tag.unwrap())
+                            .aggregate(aggregation, &confidences)?
+                            .unwrap_or(datapoint))
+                    }
+                    None => Ok(datapoint),
+                },
+            )
+            .collect()
+    }
+
+    /// Get the active xy datapoints of an experiment code,
+    /// re-aggregating the ones with stored samples if requested
+    fn get_xy_datapoints(&self, exp_code: &str) -> BencherResult<Vec<XYDatapoint>> {
+        let values = self.db.get_xy_datapoints(exp_code)?;
+        let aggregation = match self.aggregation {
+            Some(aggregation) => aggregation,
+            None => return Ok(values),
+        };
+
+        values
+            .into_iter()
+            .map(
+                |datapoint| match self.get_xy_samples(exp_code, datapoint.tag.unwrap())? {
+                    Some((x_sample, y_sample)) => {
+                        let confidences = datapoint
+                            .x_confidence
+                            .keys()
+                            .chain(datapoint.y_confidence.keys())
+                            .copied()
+                            .collect::<Vec<_>>();
+                        Ok(XYRawDatapoint::new(x_sample, y_sample)
+                            .tag(datapoint.tag.unwrap())
+                            .aggregate(aggregation, &confidences)?
+                            .unwrap_or(datapoint))
+                    }
+                    None => Ok(datapoint),
+                },
+            )
+            .collect()
+    }
+
     pub fn list_codes(&self) -> BencherResult<Vec<String>> {
         self.db.list_codes()
     }
@@ -307,7 +1049,7 @@ impl ReadConfig {
         let mut vec = codes_labels
             .into_iter()
             .map(|(code, set_label)| {
-                let mut values = self.db.get_linear_datapoints(&code)?;
+                let mut values = self.get_linear_datapoints(&code)?;
                 values.sort_by_key(|x| x.tag.unwrap());
                 Ok(LinearExperimentSet { values, set_label })
             })
@@ -317,8 +1059,67 @@ impl ReadConfig {
         Ok(vec)
     }
 
+    /// Get the linear experiment sets for a given (possibly virtual) experiment type
+    /// None if there is no such linear experiment
+    fn find_linear_experiment_sets(
+        &self,
+        exp_type: &str,
+        selector: &Selector,
+        sorter: &Sorter,
+    ) -> BencherResult<Option<Vec<LinearExperimentSet>>> {
+        if let Some(e) = self.find_virtual_linear_experiment(exp_type) {
+            self.get_virtual_linear_experiment_sets(e, selector, sorter)
+                .map(Some)
+        } else if let Some(e) = self.find_linear_experiment(exp_type) {
+            self.get_linear_experiment_sets(e, selector, sorter)
+                .map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Join the sources of a virtual experiment whose primary source is linear
+    /// The other sources must be linear as well, and are joined by set label and group
+    fn join_linear_sources(
+        &self,
+        primary: &str,
+        others: &[String],
+        selector: &Selector,
+        sorter: &Sorter,
+    ) -> BencherResult<JoinedSources<String>> {
+        let others = others
+            .iter()
+            .map(|exp_type| {
+                let sets = self
+                    .find_linear_experiment_sets(exp_type, selector, sorter)?
+                    .ok_or_else(|| {
+                        BencherError::ExperimentNotFound(
+                            exp_type.clone(),
+                            self.linear_experiments_as_string(),
+                        )
+                    })?;
+                Ok(sets
+                    .into_iter()
+                    .flat_map(|set| {
+                        let set_label = set.set_label;
+                        set.values.into_iter().map(move |dp| {
+                            let variables = dp.expression_variables(exp_type);
+                            ((set_label.clone(), dp.group), variables)
+                        })
+                    })
+                    .collect())
+            })
+            .collect::<BencherResult<Vec<_>>>()?;
+
+        Ok(JoinedSources {
+            primary: primary.to_string(),
+            others,
+            baseline: None,
+        })
+    }
+
     /// Get the linear experiment sets for a given virtual experiment type
-    /// This is done by getting the sets for the source and then transforming them
+    /// This is done by getting the sets for the sources and then transforming them
     fn get_virtual_linear_experiment_sets(
         &self,
         experiment: &VirtualLinearExperiment,
@@ -327,7 +1128,9 @@ impl ReadConfig {
     ) -> BencherResult<Vec<LinearExperimentSet>> {
         fn map_linear_datapoints(
             vec: Vec<LinearDatapoint>,
+            set_label: &str,
             virtual_experiment: &VirtualLinearExperiment,
+            sources: &JoinedSources<String>,
         ) -> BencherResult<Vec<LinearDatapoint>> {
             if vec.is_empty() {
                 return Ok(vec);
@@ -352,54 +1155,69 @@ impl ReadConfig {
                 )
             };
 
+            let v_expression = virtual_experiment.v_expression();
+            let tag_expression = virtual_experiment.tag_expression();
             vec.into_iter()
-                .map(|dp| {
-                    dp.map_expression(
-                        virtual_experiment.v_operation.as_ref().map(|x| x.as_str()),
-                        virtual_experiment
-                            .tag_operation
-                            .as_ref()
-                            .map(|x| x.as_str()),
-                        min,
-                        max,
-                        avg,
-                    )
+                .filter_map(|dp| {
+                    let mut variables = sources.variables(set_label, &dp.group)?;
+                    variables.extend(&dp.expression_variables(&sources.primary));
+                    variables.add("min", min);
+                    variables.add("max", max);
+                    variables.add("avg", avg);
+
+                    Some(dp.map_expression(
+                        v_expression.as_deref(),
+                        tag_expression.as_deref(),
+                        &variables,
+                        virtual_experiment.v_monotonicity,
+                    ))
                 })
                 .collect::<BencherResult<Vec<_>>>()
         }
 
-        if let Some(e) = self.find_virtual_linear_experiment(&experiment.source_exp_type) {
-            let source_sets = self.get_virtual_linear_experiment_sets(e, selector, sorter)?;
-            source_sets
-                .into_iter()
-                .map(|set| {
-                    let mut values = map_linear_datapoints(set.values, experiment)?;
-                    values.sort_by_key(|v| v.tag.unwrap());
-                    Ok(LinearExperimentSet {
-                        values,
-                        set_label: set.set_label,
-                    })
-                })
-                .collect::<BencherResult<Vec<_>>>()
-        } else if let Some(e) = self.find_linear_experiment(&experiment.source_exp_type) {
-            let source_sets = self.get_linear_experiment_sets(e, selector, sorter)?;
-            source_sets
-                .into_iter()
-                .map(|set| {
-                    let mut values = map_linear_datapoints(set.values, experiment)?;
-                    values.sort_by_key(|v| v.tag.unwrap());
-                    Ok(LinearExperimentSet {
-                        values,
-                        set_label: set.set_label,
-                    })
-                })
-                .collect::<BencherResult<Vec<_>>>()
-        } else {
-            Err(BencherError::ExperimentNotFound(
-                experiment.source_exp_type.clone(),
-                self.linear_experiments_as_string(),
-            ))
+        let (primary, others) = experiment
+            .source_exp_types
+            .split_first()
+            .ok_or_else(|| BencherError::NoSourceExperiments(experiment.exp_type.clone()))?;
+        let source_sets = self
+            .find_linear_experiment_sets(primary, selector, sorter)?
+            .ok_or_else(|| {
+                BencherError::ExperimentNotFound(
+                    primary.clone(),
+                    self.linear_experiments_as_string(),
+                )
+            })?;
+        let mut sources = self.join_linear_sources(primary, others, selector, sorter)?;
+        if let Some(baseline_set) = &experiment.baseline_set {
+            let set = source_sets
+                .iter()
+                .find(|set| &set.set_label == baseline_set)
+                .ok_or_else(|| {
+                    BencherError::BaselineNotFound(
+                        baseline_set.clone(),
+                        experiment.exp_type.clone(),
+                    )
+                })?;
+            sources.baseline = Some(
+                set.values
+                    .iter()
+                    .map(|dp| (dp.group.clone(), dp.expression_variables(BASELINE_VARIABLE)))
+                    .collect(),
+            );
         }
+
+        source_sets
+            .into_iter()
+            .map(|set| {
+                let mut values =
+                    map_linear_datapoints(set.values, &set.set_label, experiment, &sources)?;
+                values.sort_by_key(|v| v.tag.unwrap());
+                Ok(LinearExperimentSet {
+                    values,
+                    set_label: set.set_label,
+                })
+            })
+            .collect::<BencherResult<Vec<_>>>()
     }
 
     pub fn linear_experiment_view(
@@ -411,7 +1229,7 @@ impl ReadConfig {
         let linear_experiment = self.find_linear_experiment(exp_type);
         let virtual_linear_experiment = self.find_virtual_linear_experiment(exp_type);
 
-        match (linear_experiment, virtual_linear_experiment) {
+        let view = match (linear_experiment, virtual_linear_experiment) {
             (Some(linear_experiment), _) => {
                 let sets = self.get_linear_experiment_sets(linear_experiment, selector, sorter)?;
                 LinearExperimentView::from_linear(linear_experiment, sets)
@@ -428,7 +1246,15 @@ impl ReadConfig {
                 exp_type.to_string(),
                 self.linear_experiments_as_string(),
             )),
-        }
+        }?
+        .with_v_format(self.value_format)
+        .with_v_axis(self.value_axis)
+        .with_style(self.style.clone());
+
+        Ok(match self.histogram_style {
+            Some(histogram_style) => view.with_histogram_style(histogram_style),
+            None => view,
+        })
     }
 
     /// Bidimentional experiments
@@ -471,13 +1297,71 @@ impl ReadConfig {
         codes_labels
             .into_iter()
             .map(|(code, line_label)| {
-                let mut values = self.db.get_xy_datapoints(&code)?;
+                let mut values = self.get_xy_datapoints(&code)?;
                 values.sort_by_key(|v| v.tag.unwrap());
                 Ok(XYExperimentLine { values, line_label })
             })
             .collect::<BencherResult<_>>()
     }
 
+    /// Get the xy experiment lines for a given (possibly virtual) experiment type
+    /// None if there is no such xy experiment
+    fn find_xy_experiment_lines(
+        &self,
+        exp_type: &str,
+        selector: &Selector,
+        sorter: &Sorter,
+    ) -> BencherResult<Option<Vec<XYExperimentLine>>> {
+        if let Some(e) = self.find_virtual_xy_experiment(exp_type) {
+            self.get_virtual_xy_experiment_lines(e, selector, sorter)
+                .map(Some)
+        } else if let Some(e) = self.find_xy_experiment(exp_type) {
+            self.get_xy_experiment_lines(e, selector, sorter).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Join the sources of a virtual experiment whose primary source is bidimensional
+    /// The other sources must be bidimensional as well, and are joined by line label and tag
+    fn join_xy_sources(
+        &self,
+        primary: &str,
+        others: &[String],
+        selector: &Selector,
+        sorter: &Sorter,
+    ) -> BencherResult<JoinedSources<isize>> {
+        let others = others
+            .iter()
+            .map(|exp_type| {
+                let lines = self
+                    .find_xy_experiment_lines(exp_type, selector, sorter)?
+                    .ok_or_else(|| {
+                        BencherError::ExperimentNotFound(
+                            exp_type.clone(),
+                            self.xy_experiments_as_string(),
+                        )
+                    })?;
+                Ok(lines
+                    .into_iter()
+                    .flat_map(|line| {
+                        let line_label = line.line_label;
+                        line.values.into_iter().map(move |dp| {
+                            let variables = dp.expression_variables(exp_type);
+                            ((line_label.clone(), dp.tag.unwrap()), variables)
+                        })
+                    })
+                    .collect())
+            })
+            .collect::<BencherResult<Vec<_>>>()?;
+
+        Ok(JoinedSources {
+            primary: primary.to_string(),
+            others,
+            baseline: None,
+        })
+    }
+
     /// Get the xy experiment sets for a given virtual experiment type
     /// This is done by getting the source sets and then applying the transformation
     fn get_virtual_xy_experiment_lines(
@@ -488,7 +1372,9 @@ impl ReadConfig {
     ) -> BencherResult<Vec<XYExperimentLine>> {
         fn map_xy_datapoints(
             vec: Vec<XYDatapoint>,
+            line_label: &str,
             virtual_experiment: &VirtualXYExperiment,
+            sources: &JoinedSources<isize>,
         ) -> BencherResult<Vec<XYDatapoint>> {
             if vec.is_empty() {
                 return Ok(vec);
@@ -532,22 +1418,28 @@ impl ReadConfig {
                 )
             };
 
+            let x_expression = virtual_experiment.x_expression();
+            let y_expression = virtual_experiment.y_expression();
+            let tag_expression = virtual_experiment.tag_expression();
             vec.into_iter()
-                .map(|dp| {
-                    dp.map_expression(
-                        virtual_experiment.x_operation.as_ref().map(|x| x.as_str()),
-                        virtual_experiment.y_operation.as_ref().map(|x| x.as_str()),
-                        virtual_experiment
-                            .tag_operation
-                            .as_ref()
-                            .map(|x| x.as_str()),
-                        x_min,
-                        x_max,
-                        x_avg,
-                        y_min,
-                        y_max,
-                        y_avg,
-                    )
+                .filter_map(|dp| {
+                    let mut variables = sources.variables(line_label, &dp.tag.unwrap())?;
+                    variables.extend(&dp.expression_variables(&sources.primary));
+                    variables.add("xmin", x_min);
+                    variables.add("xmax", x_max);
+                    variables.add("xavg", x_avg);
+                    variables.add("ymin", y_min);
+                    variables.add("ymax", y_max);
+                    variables.add("yavg", y_avg);
+
+                    Some(dp.map_expression(
+                        x_expression.as_deref(),
+                        y_expression.as_deref(),
+                        tag_expression.as_deref(),
+                        &variables,
+                        virtual_experiment.x_monotonicity,
+                        virtual_experiment.y_monotonicity,
+                    ))
                 })
                 .collect::<BencherResult<Vec<_>>>()
         }
@@ -555,17 +1447,22 @@ impl ReadConfig {
         fn map_linear_sets_into_xy_lines(
             sets: Vec<LinearExperimentSet>,
             virtual_experiment: &VirtualXYExperiment,
+            sources: &JoinedSources<String>,
         ) -> BencherResult<Vec<XYExperimentLine>> {
             let mut group_map: HashMap<String, Vec<_>> = HashMap::new();
-            sets.into_iter()
-                .map(|set| set.values)
-                .flatten()
-                .for_each(|linear_dp| {
+            for set in sets {
+                for linear_dp in set.values {
+                    let Some(mut variables) = sources.variables(&set.set_label, &linear_dp.group)
+                    else {
+                        continue;
+                    };
+                    variables.extend(&linear_dp.expression_variables(&sources.primary));
                     group_map
                         .entry(linear_dp.group.clone())
-                        .and_modify(|v| v.push(linear_dp.clone()))
-                        .or_insert(vec![linear_dp.clone()]);
-                });
+                        .or_default()
+                        .push((linear_dp, variables));
+                }
+            }
 
             group_map
                 .into_iter()
@@ -580,19 +1477,19 @@ impl ReadConfig {
         }
 
         fn map_linear_datapoints(
-            vec: Vec<LinearDatapoint>,
+            vec: Vec<(LinearDatapoint, ExpressionVariables)>,
             virtual_experiment: &VirtualXYExperiment,
         ) -> BencherResult<Vec<XYDatapoint>> {
             if vec.is_empty() {
                 return Ok(vec![]);
             }
 
-            let min = vec.iter().map(|e| e.v).min().unwrap();
-            let max = vec.iter().map(|e| e.v).max().unwrap();
-            let avg = if vec.iter().all(|e| e.v.is_int()) {
+            let min = vec.iter().map(|(e, _)| e.v).min().unwrap();
+            let max = vec.iter().map(|(e, _)| e.v).max().unwrap();
+            let avg = if vec.iter().all(|(e, _)| e.v.is_int()) {
                 Value::Int(
                     vec.iter()
-                        .map(|e| e.v)
+                        .map(|(e, _)| e.v)
                         .map(|x| x.to_int().unwrap())
                         .sum::<i64>()
                         / vec.len() as i64,
@@ -600,55 +1497,62 @@ impl ReadConfig {
             } else {
                 Value::Float(
                     vec.iter()
-                        .map(|e| e.v.to_float().or(e.v.to_int().map(|x| x as f64)).unwrap())
+                        .map(|(e, _)| e.v.to_float().or(e.v.to_int().map(|x| x as f64)).unwrap())
                         .sum::<f64>()
                         / vec.len() as f64,
                 )
             };
 
+            let x_expression = virtual_experiment.x_expression();
+            let y_expression = virtual_experiment.y_expression();
+            let tag_expression = virtual_experiment.tag_expression();
             vec.into_iter()
-                .map(|dp| {
+                .map(|(dp, mut variables)| {
+                    variables.add("min", min);
+                    variables.add("max", max);
+                    variables.add("avg", avg);
+
                     dp.map_expression_to_xy(
-                        virtual_experiment.x_operation.as_ref().map(|x| x.as_str()),
-                        virtual_experiment.y_operation.as_ref().map(|x| x.as_str()),
-                        virtual_experiment
-                            .tag_operation
-                            .as_ref()
-                            .map(|x| x.as_str()),
-                        min,
-                        max,
-                        avg,
+                        x_expression.as_deref(),
+                        y_expression.as_deref(),
+                        tag_expression.as_deref(),
+                        &variables,
+                        virtual_experiment.x_monotonicity,
+                        virtual_experiment.y_monotonicity,
                     )
                 })
                 .collect::<BencherResult<Vec<_>>>()
         }
 
-        if let Some(e) = self.find_virtual_xy_experiment(&experiment.source_exp_type) {
-            let source_lines = self.get_virtual_xy_experiment_lines(e, selector, sorter)?;
-            source_lines
-                .into_iter()
-                .map(|line| {
-                    let mut values = map_xy_datapoints(line.values, experiment)?;
-                    values.sort_by_key(|v| v.tag.unwrap());
+        let (primary, others) = experiment
+            .source_exp_types
+            .split_first()
+            .ok_or_else(|| BencherError::NoSourceExperiments(experiment.exp_type.clone()))?;
 
-                    Ok(XYExperimentLine {
-                        values,
-                        line_label: line.line_label,
-                    })
-                })
-                .collect::<BencherResult<Vec<_>>>()
-        } else if let Some(e) = self.find_virtual_linear_experiment(&experiment.source_exp_type) {
-            let source_sets = self.get_virtual_linear_experiment_sets(e, selector, sorter)?;
-            map_linear_sets_into_xy_lines(source_sets, experiment)
-        } else if let Some(e) = self.find_linear_experiment(&experiment.source_exp_type) {
-            let source_sets = self.get_linear_experiment_sets(e, selector, sorter)?;
-            map_linear_sets_into_xy_lines(source_sets, experiment)
-        } else if let Some(e) = self.find_xy_experiment(&experiment.source_exp_type) {
-            let source_lines = self.get_xy_experiment_lines(e, selector, sorter)?;
+        if let Some(source_lines) = self.find_xy_experiment_lines(primary, selector, sorter)? {
+            let mut sources = self.join_xy_sources(primary, others, selector, sorter)?;
+            if let Some(baseline_line) = &experiment.baseline_set {
+                let line = source_lines
+                    .iter()
+                    .find(|line| &line.line_label == baseline_line)
+                    .ok_or_else(|| {
+                        BencherError::BaselineNotFound(
+                            baseline_line.clone(),
+                            experiment.exp_type.clone(),
+                        )
+                    })?;
+                sources.baseline = Some(
+                    line.values
+                        .iter()
+                        .map(|dp| (dp.tag.unwrap(), dp.expression_variables(BASELINE_VARIABLE)))
+                        .collect(),
+                );
+            }
             source_lines
                 .into_iter()
                 .map(|line| {
-                    let mut values = map_xy_datapoints(line.values, experiment)?;
+                    let mut values =
+                        map_xy_datapoints(line.values, &line.line_label, experiment, &sources)?;
                     values.sort_by_key(|v| v.tag.unwrap());
 
                     Ok(XYExperimentLine {
@@ -657,9 +1561,31 @@ impl ReadConfig {
                     })
                 })
                 .collect::<BencherResult<Vec<_>>>()
+        } else if let Some(source_sets) =
+            self.find_linear_experiment_sets(primary, selector, sorter)?
+        {
+            let mut sources = self.join_linear_sources(primary, others, selector, sorter)?;
+            if let Some(baseline_set) = &experiment.baseline_set {
+                let set = source_sets
+                    .iter()
+                    .find(|set| &set.set_label == baseline_set)
+                    .ok_or_else(|| {
+                        BencherError::BaselineNotFound(
+                            baseline_set.clone(),
+                            experiment.exp_type.clone(),
+                        )
+                    })?;
+                sources.baseline = Some(
+                    set.values
+                        .iter()
+                        .map(|dp| (dp.group.clone(), dp.expression_variables(BASELINE_VARIABLE)))
+                        .collect(),
+                );
+            }
+            map_linear_sets_into_xy_lines(source_sets, experiment, &sources)
         } else {
             Err(BencherError::ExperimentNotFound(
-                experiment.source_exp_type.clone(),
+                primary.clone(),
                 self.xy_experiments_as_string(),
             ))
         }
@@ -675,7 +1601,7 @@ impl ReadConfig {
         let xy_experiment = self.find_xy_experiment(exp_type);
         let virtual_xy_experiment = self.find_virtual_xy_experiment(exp_type);
 
-        match (xy_experiment, virtual_xy_experiment) {
+        let view = match (xy_experiment, virtual_xy_experiment) {
             (Some(xy_experiment), _) => {
                 let sets = self.get_xy_experiment_lines(xy_experiment, selector, sorter)?;
                 XYExperimentView::from_xy(xy_experiment, sets)
@@ -689,6 +1615,916 @@ impl ReadConfig {
                 exp_type.to_string(),
                 self.xy_experiments_as_string(),
             )),
+        }?;
+        Ok(view
+            .with_x_format(self.x_format)
+            .with_y_format(self.value_format)
+            .with_x_axis(self.x_axis)
+            .with_y_axis(self.value_axis)
+            .with_style(self.style.clone()))
+    }
+
+    /// Active datapoints of the linear experiments of a given type, by set label
+    fn linear_datapoints_by_label(
+        &self,
+        exp_type: &str,
+        selector: &Selector,
+    ) -> BencherResult<BTreeMap<String, (String, Vec<LinearDatapoint>)>> {
+        let mut map = BTreeMap::new();
+        for (code, label) in
+            self.db
+                .list_codes_labels_by_exp_type(exp_type, selector, &Sorter::new(vec![]))?
+        {
+            let datapoints = self.get_linear_datapoints(&code)?;
+            map.entry(label).or_insert((code, datapoints));
+        }
+        Ok(map)
+    }
+
+    /// Active datapoints of the bidimensional experiments of a given type, by line label
+    fn xy_datapoints_by_label(
+        &self,
+        exp_type: &str,
+        selector: &Selector,
+    ) -> BencherResult<BTreeMap<String, (String, Vec<XYDatapoint>)>> {
+        let mut map = BTreeMap::new();
+        for (code, label) in
+            self.db
+                .list_codes_labels_by_exp_type(exp_type, selector, &Sorter::new(vec![]))?
+        {
+            let datapoints = self.get_xy_datapoints(&code)?;
+            map.entry(label).or_insert((code, datapoints));
+        }
+        Ok(map)
+    }
+
+    /// Compare the active datapoints of this config (the candidate) with the ones of a baseline
+    ///
+    /// Experiments are matched by type and label, and datapoints by group (linear) or tag (bidimensional).
+    /// Datapoints which only exist on one side are ignored
+    pub fn compare(
+        &self,
+        baseline: &ReadConfig,
+        selector: &Selector,
+        options: &CompareOptions,
+    ) -> BencherResult<Vec<Comparison>> {
+        let mut comparisons = Vec::new();
+
+        for experiment in &self.linear_experiments {
+            let baseline_sets =
+                baseline.linear_datapoints_by_label(&experiment.exp_type, selector)?;
+            for (label, (code, datapoints)) in
+                self.linear_datapoints_by_label(&experiment.exp_type, selector)?
+            {
+                let (baseline_code, baseline_datapoints) = match baseline_sets.get(&label) {
+                    Some(set) => set,
+                    None => continue,
+                };
+
+                for candidate in datapoints {
+                    if let Some(base) = baseline_datapoints
+                        .iter()
+                        .find(|d| d.group == candidate.group)
+                    {
+                        let base_sample = baseline.get_linear_sample(baseline_code, &base.group)?;
+                        let cand_sample = self.get_linear_sample(&code, &candidate.group)?;
+                        comparisons.push(Comparison::new(
+                            &experiment.exp_type,
+                            &label,
+                            candidate.group.clone(),
+                            ComparedValue::new(
+                                base.v,
+                                base.get_confidence(options.confidence),
+                                base_sample.as_ref(),
+                            ),
+                            ComparedValue::new(
+                                candidate.v,
+                                candidate.get_confidence(options.confidence),
+                                cand_sample.as_ref(),
+                            ),
+                            experiment.higher_is_better,
+                            options,
+                        ));
+                    }
+                }
+            }
+        }
+
+        for experiment in &self.xy_experiments {
+            let baseline_lines = baseline.xy_datapoints_by_label(&experiment.exp_type, selector)?;
+            for (label, (code, datapoints)) in
+                self.xy_datapoints_by_label(&experiment.exp_type, selector)?
+            {
+                let (baseline_code, baseline_datapoints) = match baseline_lines.get(&label) {
+                    Some(line) => line,
+                    None => continue,
+                };
+
+                for candidate in datapoints {
+                    if let Some(base) = baseline_datapoints.iter().find(|d| d.tag == candidate.tag)
+                    {
+                        let tag = candidate.tag.unwrap_or_default();
+                        let base_sample = baseline.get_xy_samples(baseline_code, tag)?;
+                        let cand_sample = self.get_xy_samples(&code, tag)?;
+                        comparisons.push(Comparison::new(
+                            &experiment.exp_type,
+                            &label,
+                            tag.to_string(),
+                            ComparedValue::new(
+                                base.y,
+                                base.get_y_confidence(options.confidence),
+                                base_sample.as_ref().map(|(_, y)| y),
+                            ),
+                            ComparedValue::new(
+                                candidate.y,
+                                candidate.get_y_confidence(options.confidence),
+                                cand_sample.as_ref().map(|(_, y)| y),
+                            ),
+                            experiment.higher_is_better,
+                            options,
+                        ));
+                    }
+                }
+            }
+        }
+
+        Ok(comparisons)
+    }
+
+    /// Compare the active version of each datapoint with the version added before it
+    /// (skipping the reverted ones)
+    ///
+    /// Datapoints with a single version are ignored
+    pub fn compare_versions(
+        &self,
+        selector: &Selector,
+        options: &CompareOptions,
+    ) -> BencherResult<Vec<Comparison>> {
+        /// The active version and the one added before it which was not reverted
+        fn previous_and_active<D>(
+            history: Vec<DatapointVersion<D>>,
+        ) -> Option<(DatapointVersion<D>, DatapointVersion<D>)> {
+            let active_version = history
+                .iter()
+                .find(|v| v.status == VersionStatus::Active)?
+                .version;
+            let mut previous = None;
+            let mut active = None;
+            for version in history {
+                if version.version == active_version {
+                    active = Some(version);
+                } else if version.version < active_version
+                    && version.status != VersionStatus::Reverted
+                {
+                    previous = Some(version);
+                }
+            }
+            previous.zip(active)
+        }
+
+        let mut comparisons = Vec::new();
+
+        for experiment in &self.linear_experiments {
+            for (label, (code, datapoints)) in
+                self.linear_datapoints_by_label(&experiment.exp_type, selector)?
+            {
+                for datapoint in datapoints {
+                    let history = self.get_linear_history(&code, &datapoint.group)?;
+                    if let Some((base, candidate)) = previous_and_active(history) {
+                        let base_sample = self.db.get_linear_version_sample(
+                            &code,
+                            &datapoint.group,
+                            base.version,
+                        )?;
+                        let cand_sample = self.db.get_linear_version_sample(
+                            &code,
+                            &datapoint.group,
+                            candidate.version,
+                        )?;
+                        comparisons.push(Comparison::new(
+                            &experiment.exp_type,
+                            &label,
+                            datapoint.group.clone(),
+                            ComparedValue::new(
+                                base.datapoint.v,
+                                base.datapoint.get_confidence(options.confidence),
+                                base_sample.as_ref(),
+                            ),
+                            ComparedValue::new(
+                                candidate.datapoint.v,
+                                candidate.datapoint.get_confidence(options.confidence),
+                                cand_sample.as_ref(),
+                            ),
+                            experiment.higher_is_better,
+                            options,
+                        ));
+                    }
+                }
+            }
+        }
+
+        for experiment in &self.xy_experiments {
+            for (label, (code, datapoints)) in
+                self.xy_datapoints_by_label(&experiment.exp_type, selector)?
+            {
+                for datapoint in datapoints {
+                    let tag = datapoint.tag.unwrap_or_default();
+                    let history = self.get_xy_history(&code, tag)?;
+                    if let Some((base, candidate)) = previous_and_active(history) {
+                        let base_sample =
+                            self.db.get_xy_version_samples(&code, tag, base.version)?;
+                        let cand_sample =
+                            self.db
+                                .get_xy_version_samples(&code, tag, candidate.version)?;
+                        comparisons.push(Comparison::new(
+                            &experiment.exp_type,
+                            &label,
+                            tag.to_string(),
+                            ComparedValue::new(
+                                base.datapoint.y,
+                                base.datapoint.get_y_confidence(options.confidence),
+                                base_sample.as_ref().map(|(_, y)| y),
+                            ),
+                            ComparedValue::new(
+                                candidate.datapoint.y,
+                                candidate.datapoint.get_y_confidence(options.confidence),
+                                cand_sample.as_ref().map(|(_, y)| y),
+                            ),
+                            experiment.higher_is_better,
+                            options,
+                        ));
+                    }
+                }
+            }
+        }
+
+        Ok(comparisons)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::db::test::{temp_db_path, unversioned_db};
+
+    fn linear_datapoint(group: &str, v: f64, interval: (f64, f64)) -> LinearDatapoint {
+        let mut datapoint = LinearDatapoint::new(group, Value::Float(v));
+        datapoint.add_value_confidence(
+            Confidence::FIVE,
+            (Value::Float(interval.0), Value::Float(interval.1)),
+        );
+        datapoint
+    }
+
+    /// Read config over a temporary database with the linear experiments `lat` (sets A and B,
+    /// with 5% confidence intervals) and `ops` (only set A and group get), the bidimensional
+    /// experiment `tx` (line A) and the given virtual experiments (as JSON fields of the config)
+    fn virtual_config(
+        name: &str,
+        virtual_experiments: &str,
+    ) -> (PathBuf, BencherResult<ReadConfig>) {
+        let path = temp_db_path(name);
+        let config = WriteConfig::from_file(&path).unwrap();
+        config
+            .add_linear_datapoints(
+                "lat",
+                "A",
+                "lat_a",
+                vec![
+                    linear_datapoint("get", 10.0, (8.0, 12.0)),
+                    linear_datapoint("put", 20.0, (18.0, 24.0)),
+                ],
+            )
+            .unwrap();
+        config
+            .add_linear_datapoints(
+                "lat",
+                "B",
+                "lat_b",
+                vec![
+                    linear_datapoint("get", 5.0, (4.0, 6.0)),
+                    linear_datapoint("put", 40.0, (30.0, 50.0)),
+                ],
+            )
+            .unwrap();
+        config
+            .add_linear_datapoints(
+                "ops",
+                "A",
+                "ops_a",
+                vec![linear_datapoint("get", 2.0, (2.0, 2.0))],
+            )
+            .unwrap();
+        let mut point = XYDatapoint::new(Value::Float(4.0), Value::Float(100.0));
+        point
+            .add_y_confidence(Confidence::FIVE, either::Right((80.0, 120.0)))
+            .unwrap();
+        config
+            .add_xy_datapoints("tx", "A", "tx_a", vec![point])
+            .unwrap();
+
+        let parsed: ParsedConfig = serde_json::from_str(&format!(
+            r#"{{
+                "linear_experiments": [
+                    {{ "exp_type": "lat", "horizontal_label": "op", "v_label": "latency", "v_units": "ms" }},
+                    {{ "exp_type": "ops", "horizontal_label": "op", "v_label": "operations", "v_units": "" }}
+                ],
+                "xy_experiments": [
+                    {{ "exp_type": "tx", "x_label": "cores", "x_units": "", "y_label": "throughput", "y_units": "ops/s" }}
+                ],
+                {}
+            }}"#,
+            virtual_experiments
+        ))
+        .unwrap();
+        (path, config.to_read_config(parsed))
+    }
+
+    /// Value of a linear view, as a (set, group, v, 5% confidence interval) tuple
+    type LinearValue = (String, String, f64, (f64, f64));
+
+    /// JSON export of a linear view, as (set, group, v, 5% confidence interval) tuples
+    fn linear_values(config: &ReadConfig, exp_type: &str) -> BencherResult<Vec<LinearValue>> {
+        let view =
+            config.linear_experiment_view(exp_type, &Selector::default(), &Sorter::new(vec![]))?;
+        let mut json = vec![];
+        view.json(&mut json)?;
+        let json: serde_json::Value = serde_json::from_slice(&json)?;
+
+        let mut values = vec![];
+        for set in json["sets"].as_array().unwrap() {
+            for value in set["values"].as_array().unwrap() {
+                let confidence = &value["confidences"][0];
+                values.push((
+                    set["label"].as_str().unwrap().to_string(),
+                    value["group"].as_str().unwrap().to_string(),
+                    value["v"].as_f64().unwrap(),
+                    (
+                        confidence["min"].as_f64().unwrap(),
+                        confidence["max"].as_f64().unwrap(),
+                    ),
+                ));
+            }
         }
+        Ok(values)
+    }
+
+    #[test]
+    fn compare_versions_skips_reverted() {
+        let path = temp_db_path("compare-reverted.db");
+        let config = WriteConfig::from_file(&path).unwrap();
+        let add = |v: f64| {
+            config
+                .add_linear_datapoints(
+                    "lat",
+                    "A",
+                    "lat_a",
+                    vec![linear_datapoint("get", v, (v, v))],
+                )
+                .unwrap()
+        };
+        // version 2 is reverted before version 3 is added
+        add(10.0);
+        add(20.0);
+        config
+            .get_linear_set("lat_a")
+            .unwrap()
+            .unwrap()
+            .revert("get", Some(1))
+            .unwrap();
+        add(12.0);
+
+        let parsed: ParsedConfig = serde_json::from_value(serde_json::json!({
+            "linear_experiments": [
+                { "exp_type": "lat", "horizontal_label": "op", "v_label": "latency", "v_units": "ms" },
+            ],
+        }))
+        .unwrap();
+        let config = config.to_read_config(parsed).unwrap();
+        let comparisons = config
+            .compare_versions(&Selector::default(), &CompareOptions::default())
+            .unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(comparisons.len(), 1);
+        assert_eq!(comparisons[0].baseline, Value::Float(10.0));
+        assert_eq!(comparisons[0].candidate, Value::Float(12.0));
+    }
+
+    #[test]
+    fn virtual_linear_units_and_confidences() {
+        let (path, config) = virtual_config(
+            "virtual-units",
+            r#""virtual_linear_experiments": [
+                { "exp_type": "double", "source_exp_type": "lat", "horizontal_label": "op",
+                  "v_label": "doubled latency", "v_units": "us", "v_operation": "v * 2" },
+                { "exp_type": "rate", "source_exp_type": "lat", "horizontal_label": "op",
+                  "v_label": "rate", "v_units": "ops/ms", "v_operation": "100 / v" }
+            ]"#,
+        );
+        let config = config.unwrap();
+
+        let view = config
+            .linear_experiment_view("double", &Selector::default(), &Sorter::new(vec![]))
+            .unwrap();
+        let mut json = vec![];
+        view.json(&mut json).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&json).unwrap();
+        assert_eq!(json["v_label"], "doubled latency");
+        assert_eq!(json["v_units"], "us");
+
+        assert_eq!(
+            linear_values(&config, "double").unwrap(),
+            vec![
+                ("A".to_string(), "get".to_string(), 20.0, (16.0, 24.0)),
+                ("A".to_string(), "put".to_string(), 40.0, (36.0, 48.0)),
+                ("B".to_string(), "get".to_string(), 10.0, (8.0, 12.0)),
+                ("B".to_string(), "put".to_string(), 80.0, (60.0, 100.0)),
+            ]
+        );
+
+        // decreasing operations swap the bounds of the intervals
+        let rate = linear_values(&config, "rate").unwrap();
+        assert_eq!(rate[0].2, 10.0);
+        assert_eq!(rate[0].3, (100.0 / 12.0, 12.5));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn virtual_linear_sources() {
+        let (path, config) = virtual_config(
+            "virtual-sources",
+            r#""virtual_linear_experiments": [
+                { "exp_type": "per_op", "source_exp_types": ["lat", "ops"], "horizontal_label": "op",
+                  "v_label": "latency per operation", "v_units": "ms", "v_operation": "v / ops" }
+            ]"#,
+        );
+        let config = config.unwrap();
+
+        // datapoints without an operand in every source are left out
+        assert_eq!(
+            linear_values(&config, "per_op").unwrap(),
+            vec![("A".to_string(), "get".to_string(), 5.0, (4.0, 6.0))]
+        );
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn virtual_linear_baseline() {
+        let (path, config) = virtual_config(
+            "virtual-baseline",
+            r#""virtual_linear_experiments": [
+                { "exp_type": "speedup", "source_exp_type": "lat", "horizontal_label": "op",
+                  "v_label": "speedup", "v_units": "", "v_operation": "baseline / v",
+                  "baseline_set": "A" },
+                { "exp_type": "no_baseline", "source_exp_type": "lat", "horizontal_label": "op",
+                  "v_label": "no baseline", "v_units": "", "v_operation": "baseline / v",
+                  "baseline_set": "C" }
+            ]"#,
+        );
+        let config = config.unwrap();
+
+        // the baseline is joined by group, in every set (including its own)
+        let speedup = linear_values(&config, "speedup").unwrap();
+        assert_eq!(
+            speedup
+                .iter()
+                .map(|(set, group, v, _)| (set.as_str(), group.as_str(), *v))
+                .collect::<Vec<_>>(),
+            vec![
+                ("A", "get", 1.0),
+                ("A", "put", 1.0),
+                ("B", "get", 2.0),
+                ("B", "put", 0.5)
+            ]
+        );
+        // the confidence interval of the baseline is propagated along with the one of the set
+        let (_, _, _, (min, max)) = speedup[2];
+        assert!(
+            min < 2.0 && 2.0 < max,
+            "[{}, {}] does not contain 2",
+            min,
+            max
+        );
+
+        assert!(matches!(
+            linear_values(&config, "no_baseline"),
+            Err(BencherError::BaselineNotFound(set, exp_type)) if set == "C" && exp_type == "no_baseline"
+        ));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn virtual_linear_errors() {
+        let (path, config) = virtual_config(
+            "virtual-syntax",
+            r#""virtual_linear_experiments": [
+                { "exp_type": "broken", "source_exp_type": "lat", "horizontal_label": "op",
+                  "v_label": "broken", "v_units": "", "v_operation": "v * (" },
+                { "exp_type": "dangling", "source_exp_type": "missing", "horizontal_label": "op",
+                  "v_label": "dangling", "v_units": "" }
+            ]"#,
+        );
+        match config {
+            Err(BencherError::InvalidConfig(problems)) => {
+                assert_eq!(problems.len(), 2, "{:?}", problems);
+                assert!(matches!(
+                    &problems[0],
+                    ConfigProblem::InvalidExpression(exp_type, field, _)
+                        if exp_type == "broken" && field == "v_operation"
+                ));
+                assert!(matches!(
+                    &problems[1],
+                    ConfigProblem::DanglingSource(exp_type, source)
+                        if exp_type == "dangling" && source == "missing"
+                ));
+            }
+            other => panic!("expected an invalid config, got {:?}", other.map(|_| ())),
+        }
+        std::fs::remove_file(&path).unwrap();
+
+        let (path, config) = virtual_config(
+            "virtual-eval",
+            r#""virtual_linear_experiments": [
+                { "exp_type": "unknown", "source_exp_type": "lat", "horizontal_label": "op",
+                  "v_label": "unknown", "v_units": "", "v_operation": "v * nope" },
+                { "exp_type": "boolean", "source_exp_type": "lat", "horizontal_label": "op",
+                  "v_label": "boolean", "v_units": "", "v_operation": "v > 1" }
+            ]"#,
+        );
+        let config = config.unwrap();
+        assert!(matches!(
+            linear_values(&config, "unknown"),
+            Err(BencherError::ExpressionError(_))
+        ));
+        assert!(matches!(
+            linear_values(&config, "boolean"),
+            Err(BencherError::ExpressionConversionError(_))
+        ));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn virtual_xy_units_and_confidences() {
+        let (path, config) = virtual_config(
+            "virtual-xy",
+            r#""virtual_xy_experiments": [
+                { "exp_type": "per_core", "source_exp_type": "tx", "x_label": "cores", "x_units": "",
+                  "y_label": "throughput per core", "y_units": "ops/s/core", "y_operation": "y / x" }
+            ]"#,
+        );
+        let config = config.unwrap();
+
+        let view = config
+            .xy_experiment_view("per_core", &Selector::default(), &Sorter::new(vec![]))
+            .unwrap();
+        let mut json = vec![];
+        view.json(&mut json).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&json).unwrap();
+        assert_eq!(json["y_label"], "throughput per core");
+        assert_eq!(json["y_units"], "ops/s/core");
+
+        let point = &json["lines"][0]["values"][0];
+        assert_eq!(point["x"], 4.0);
+        assert_eq!(point["y"], 25.0);
+        assert_eq!(point["y_confidences"][0]["min"], 20.0);
+        assert_eq!(point["y_confidences"][0]["max"], 30.0);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    /// Write a tree of configs under a temporary directory (named after the test)
+    fn config_tree(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("bencher-{}-{}", std::process::id(), name));
+        let _ = std::fs::remove_dir_all(&dir);
+        for (path, contents) in files {
+            let path = dir.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, contents).unwrap();
+        }
+        dir.canonicalize().unwrap()
+    }
+
+    fn linear_experiment(exp_type: &str, units: &str) -> String {
+        format!(
+            r#"{{ "exp_type": "{}", "horizontal_label": "op", "v_label": "{0}", "v_units": "{}" }}"#,
+            exp_type, units
+        )
+    }
+
+    #[test]
+    fn merged_config_precedence() {
+        let dir = config_tree(
+            "merge-precedence",
+            &[
+                // above the root config: never merged (it would conflict otherwise)
+                (
+                    ".bencher-config",
+                    &format!(
+                        r#"{{ "default_database_filepath": "outer.db", "linear_experiments": [{}] }}"#,
+                        linear_experiment("lat", "s")
+                    ),
+                ),
+                (
+                    "project/.bencher-config.json",
+                    &format!(
+                        r#"{{
+                            "root": true,
+                            "default_database_filepath": "project.db",
+                            "include": ["shared/common.json"],
+                            "linear_experiments": [{}],
+                            "style": {{ "terminal": "svg", "font": "Helvetica,10" }}
+                        }}"#,
+                        linear_experiment("ops", "")
+                    ),
+                ),
+                (
+                    "project/shared/common.json",
+                    &format!(
+                        r#"{{
+                            "default_database_filepath": "common.db",
+                            "include": ["more/extra.toml"],
+                            "linear_experiments": [{}],
+                            "style": {{ "font": "Times,12", "line_width": 3.0 }}
+                        }}"#,
+                        linear_experiment("mem", "B")
+                    ),
+                ),
+                (
+                    "project/shared/more/extra.toml",
+                    "[[linear_experiments]]\nexp_type = \"lat\"\nhorizontal_label = \"op\"\nv_label = \"lat\"\nv_units = \"ms\"\n",
+                ),
+                (
+                    "project/bench/.bencher-config.yaml",
+                    "style:\n  terminal: pngcairo\n",
+                ),
+            ],
+        );
+
+        let merged =
+            MergedConfig::from_path(&dir.join("project/bench/.bencher-config.yaml")).unwrap();
+
+        // the closest config which sets the database wins, resolved relative to that config
+        assert_eq!(merged.default_db_path, Some(dir.join("project/project.db")));
+
+        // experiments come from the config, its parents and their (relative) includes
+        let mut experiments = merged
+            .config
+            .linear_experiments
+            .iter()
+            .flatten()
+            .map(|e| (e.exp_type.as_str(), e.v_units.as_str()))
+            .collect::<Vec<_>>();
+        experiments.sort();
+        assert_eq!(experiments, vec![("lat", "ms"), ("mem", "B"), ("ops", "")]);
+
+        // the style is merged option by option, closer configs first
+        let style = merged.config.style.unwrap();
+        assert_eq!(style.terminal, Some(Terminal::Pngcairo));
+        assert_eq!(style.font.as_deref(), Some("Helvetica,10"));
+        assert_eq!(style.line_width, Some(3.0));
+
+        // includes take precedence over parents
+        let merged = MergedConfig::from_path(&dir.join("project/shared/common.json")).unwrap();
+        assert_eq!(
+            merged.default_db_path,
+            Some(dir.join("project/shared/common.db"))
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn merged_config_root() {
+        let dir = config_tree(
+            "merge-root",
+            &[
+                (
+                    ".bencher-config",
+                    r#"{ "default_database_filepath": "outer.db" }"#,
+                ),
+                ("inner/.bencher-config", "root = true\n"),
+                ("inner/bench/.bencher-config", "{}"),
+            ],
+        );
+
+        let merged = MergedConfig::from_path(&dir.join("inner/bench/.bencher-config")).unwrap();
+        assert_eq!(merged.default_db_path, None);
+        assert!(matches!(
+            merged.default_db_path(&dir.join("inner/bench/.bencher-config")),
+            Err(BencherError::NoDefaultDatabase(_))
+        ));
+
+        // without the root, the parent database is found
+        std::fs::write(dir.join("inner/.bencher-config"), "root = false\n").unwrap();
+        let merged = MergedConfig::from_path(&dir.join("inner/bench/.bencher-config")).unwrap();
+        assert_eq!(merged.default_db_path, Some(dir.join("outer.db")));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn merged_config_conflicts() {
+        let dir = config_tree(
+            "merge-conflicts",
+            &[
+                (
+                    ".bencher-config",
+                    &format!(
+                        r#"{{ "root": true, "include": ["same.json", "other.json"], "linear_experiments": [{}] }}"#,
+                        linear_experiment("lat", "ms")
+                    ),
+                ),
+                (
+                    "same.json",
+                    &format!(
+                        r#"{{ "linear_experiments": [{}] }}"#,
+                        linear_experiment("lat", "ms")
+                    ),
+                ),
+                (
+                    "other.json",
+                    &format!(
+                        r#"{{ "linear_experiments": [{}] }}"#,
+                        linear_experiment("lat", "s")
+                    ),
+                ),
+            ],
+        );
+
+        // the same definition can be repeated, but not a different one
+        match MergedConfig::from_path(&dir.join(".bencher-config")) {
+            Err(BencherError::ConflictingExperiment(exp_type, origin, path)) => {
+                assert_eq!(exp_type, "lat");
+                assert_eq!(origin, dir.join(".bencher-config"));
+                assert_eq!(path, dir.join("other.json"));
+            }
+            other => panic!("expected a conflict, got {:?}", other),
+        }
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn merged_config_include_cycles() {
+        let dir = config_tree(
+            "merge-cycles",
+            &[
+                (
+                    "self/.bencher-config",
+                    r#"{ "root": true, "include": ["./.bencher-config"] }"#,
+                ),
+                (
+                    "pair/.bencher-config",
+                    r#"{ "root": true, "include": ["nested/other.json"] }"#,
+                ),
+                (
+                    "pair/nested/other.json",
+                    r#"{ "include": ["../.bencher-config"] }"#,
+                ),
+                (
+                    "diamond/.bencher-config",
+                    r#"{ "root": true, "include": ["a.json", "b.json"] }"#,
+                ),
+                ("diamond/a.json", r#"{ "include": ["c.json"] }"#),
+                ("diamond/b.json", r#"{ "include": ["c.json"] }"#),
+                (
+                    "diamond/c.json",
+                    r#"{ "default_database_filepath": "c.db" }"#,
+                ),
+            ],
+        );
+
+        match MergedConfig::from_path(&dir.join("self/.bencher-config")) {
+            Err(BencherError::IncludeCycle(cycle)) => assert_eq!(
+                cycle,
+                vec![
+                    dir.join("self/.bencher-config"),
+                    dir.join("self/.bencher-config")
+                ]
+            ),
+            other => panic!("expected an include cycle, got {:?}", other),
+        }
+        match MergedConfig::from_path(&dir.join("pair/.bencher-config")) {
+            Err(BencherError::IncludeCycle(cycle)) => assert_eq!(
+                cycle,
+                vec![
+                    dir.join("pair/.bencher-config"),
+                    dir.join("pair/nested/other.json"),
+                    dir.join("pair/.bencher-config")
+                ]
+            ),
+            other => panic!("expected an include cycle, got {:?}", other),
+        }
+
+        // a config included twice is not a cycle
+        let merged = MergedConfig::from_path(&dir.join("diamond/.bencher-config")).unwrap();
+        assert_eq!(merged.default_db_path, Some(dir.join("diamond/c.db")));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn edited_config_keeps_expression_comments() {
+        let dir = config_tree(
+            "edit-expression-comments",
+            &[(
+                ".bencher-config",
+                r#"root = true
+
+[[linear_experiments]]
+exp_type = "lat"
+horizontal_label = "op"
+v_label = "latency"
+v_units = "ms"
+
+[[virtual_linear_experiments]]
+exp_type = "double"
+source_exp_type = "lat"
+horizontal_label = "op"
+v_label = "double"
+v_units = "ms"
+v_operation = """
+v * 2 # double
++ 1 # and one
+"""
+"#,
+            )],
+        );
+        let path = dir.join(".bencher-config");
+        let mut editor = ConfigEditor::from_file(&path).unwrap();
+        editor.add_linear_experiment(LinearExperiment::new("ops", "op", "operations", ""));
+        editor.save().unwrap();
+
+        let config = ParsedConfig::from_path(&path).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        let experiments = config.virtual_linear_experiments.unwrap();
+        assert_eq!(
+            experiments[0].v_operation.as_deref(),
+            Some("v * 2 # double\n+ 1 # and one\n")
+        );
+        assert_eq!(config.linear_experiments.unwrap().len(), 2);
+    }
+
+    #[test]
+    fn migrate_with_backup() {
+        let path = temp_db_path("migrate-backup");
+        let backup_path = PathBuf::from(format!("{}.v0.bak", path.to_string_lossy()));
+        let _ = std::fs::remove_file(&backup_path);
+        unversioned_db(&path);
+        let original = std::fs::read(&path).unwrap();
+
+        let report = WriteConfig::migrate(&path, true).unwrap();
+        assert_eq!(report.from_version, 0);
+        assert_eq!(report.to_version, SCHEMA_VERSION);
+        assert_eq!(report.backup.as_ref(), Some(&backup_path));
+        assert_eq!(std::fs::read(&backup_path).unwrap(), original);
+        assert_eq!(db_schema_version(&path).unwrap(), SCHEMA_VERSION);
+
+        // an up to date database is not backed up again
+        let report = WriteConfig::migrate(&path, true).unwrap();
+        assert_eq!(report.from_version, SCHEMA_VERSION);
+        assert!(report.backup.is_none());
+
+        std::fs::remove_file(&path).unwrap();
+        std::fs::remove_file(&backup_path).unwrap();
+    }
+
+    #[test]
+    fn migrate_keeps_existing_backup() {
+        let path = temp_db_path("migrate-existing-backup");
+        let backup_path = PathBuf::from(format!("{}.v0.bak", path.to_string_lossy()));
+        let second_backup_path = PathBuf::from(format!("{}.v0.1.bak", path.to_string_lossy()));
+        let _ = std::fs::remove_file(&second_backup_path);
+        std::fs::write(&backup_path, "previous backup").unwrap();
+        unversioned_db(&path);
+
+        let report = WriteConfig::migrate(&path, true).unwrap();
+        assert_eq!(report.backup.as_ref(), Some(&second_backup_path));
+        assert_eq!(
+            std::fs::read_to_string(&backup_path).unwrap(),
+            "previous backup"
+        );
+        assert_eq!(db_schema_version(&second_backup_path).unwrap(), 0);
+
+        std::fs::remove_file(&path).unwrap();
+        std::fs::remove_file(&backup_path).unwrap();
+        std::fs::remove_file(&second_backup_path).unwrap();
+    }
+
+    #[test]
+    fn migrate_without_backup() {
+        let path = temp_db_path("migrate-no-backup");
+        unversioned_db(&path);
+
+        let report = WriteConfig::migrate(&path, false).unwrap();
+        assert_eq!(report.from_version, 0);
+        assert!(report.backup.is_none());
+        assert_eq!(db_schema_version(&path).unwrap(), SCHEMA_VERSION);
+
+        std::fs::remove_file(&path).unwrap();
     }
 }
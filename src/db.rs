@@ -22,7 +22,6 @@ impl From<DbWriteBackend> for rusqlite::Connection {
 impl DbWriteBackend {
     pub(crate) fn new(path: &std::path::Path) -> BencherResult<Self> {
         let db = open_db(path, true /* write */)?;
-        setup_db(&db)?;
         Ok(DbWriteBackend { db })
     }
 
@@ -31,6 +30,14 @@ impl DbWriteBackend {
         Ok(DbWriteBackend { db: conn })
     }
 
+    /// Start a transaction
+    ///
+    /// Everything written through this backend (and its handles) is part of the transaction
+    /// until it is committed; dropping it rolls back
+    pub(crate) fn transaction(&self) -> BencherResult<rusqlite::Transaction<'_>> {
+        self.db.unchecked_transaction().map_err(|e| e.into())
+    }
+
     pub(crate) fn experiment_exists(
         &self,
         exp_type: &str,
@@ -174,11 +181,13 @@ impl DbWriteBackend {
         Ok(new_version)
     }
 
+    /// Add a linear datapoint, returning the version it was added with
     pub(crate) fn add_linear_datapoint(
         &self,
         exp_code: &str,
         datapoint: LinearDatapoint,
-    ) -> BencherResult<()> {
+        metadata: &RunMetadata,
+    ) -> BencherResult<isize> {
         let version = self.get_new_linear_version(exp_code, &datapoint.group)?;
         let mut stmt = self.db.prepare(
             "insert into linear_results (
@@ -186,87 +195,126 @@ impl DbWriteBackend {
                     version,
                     tag,
                     v_group,
-
                     v_int,
-                    v_int_1,
-                    v_int_5,
-                    v_int_10,
-                    v_int_25,
-                    v_int_99,
-                    v_int_95,
-                    v_int_90,
-                    v_int_75,
-
                     v_float,
-                    v_float_1,
-                    v_float_5,
-                    v_float_10,
-                    v_float_25,
-                    v_float_99,
-                    v_float_95,
-                    v_float_90,
-                    v_float_75
+                    timestamp,
+                    git_revision,
+                    hostname
                 ) values (
                     :experiment_code,
                     :version,
                     :tag,
                     :v_group,
-
                     :v_int,
-                    :v_int_1,
-                    :v_int_5,
-                    :v_int_10,
-                    :v_int_25,
-                    :v_int_99,
-                    :v_int_95,
-                    :v_int_90,
-                    :v_int_75,
-
                     :v_float,
-                    :v_float_1,
-                    :v_float_5,
-                    :v_float_10,
-                    :v_float_25,
-                    :v_float_99,
-                    :v_float_95,
-                    :v_float_90,
-                    :v_float_75
+                    :timestamp,
+                    :git_revision,
+                    :hostname
                 )",
         )?;
 
         stmt.execute(rusqlite::named_params! {
             ":experiment_code": exp_code,
-            ":v_group": datapoint.group,
-            ":tag": datapoint.tag.unwrap(),
             ":version": version,
-
+            ":tag": datapoint.tag.unwrap(),
+            ":v_group": datapoint.group,
             ":v_int": datapoint.v.to_int(),
             ":v_float": datapoint.v.to_float(),
+            ":timestamp": metadata.timestamp.unwrap_or_else(now_timestamp),
+            ":git_revision": metadata.git_revision,
+            ":hostname": metadata.hostname,
+        })?;
 
-            ":v_int_1": datapoint.get_confidence(Confidence::One).clone().map(|val| val.0.to_int()).flatten(),
-            ":v_int_99": datapoint.get_confidence(Confidence::One).clone().map(|val| val.1.to_int()).flatten(),
-
-            ":v_int_5": datapoint.get_confidence(Confidence::Five).clone().map(|val| val.0.to_int()).flatten(),
-            ":v_int_95": datapoint.get_confidence(Confidence::Five).clone().map(|val| val.1.to_int()).flatten(),
-
-            ":v_int_10": datapoint.get_confidence(Confidence::Ten).clone().map(|val| val.0.to_int()).flatten(),
-            ":v_int_90": datapoint.get_confidence(Confidence::Ten).clone().map(|val| val.1.to_int()).flatten(),
+        let mut stmt = self.db.prepare_cached(
+            "insert into linear_metadata (
+                    experiment_code,
+                    v_group,
+                    version,
+                    key,
+                    value
+                ) values (
+                    :experiment_code,
+                    :v_group,
+                    :version,
+                    :key,
+                    :value
+                )",
+        )?;
 
-            ":v_int_25": datapoint.get_confidence(Confidence::TwentyFive).clone().map(|val| val.0.to_int()).flatten(),
-            ":v_int_75": datapoint.get_confidence(Confidence::TwentyFive).clone().map(|val| val.1.to_int()).flatten(),
+        for (key, value) in &metadata.values {
+            stmt.execute(rusqlite::named_params! {
+                ":experiment_code": exp_code,
+                ":v_group": datapoint.group,
+                ":version": version,
+                ":key": key,
+                ":value": value,
+            })?;
+        }
 
-            ":v_float_1": datapoint.get_confidence(Confidence::One).clone().map(|val| val.0.to_float()).flatten(),
-            ":v_float_99": datapoint.get_confidence(Confidence::One).clone().map(|val| val.1.to_float()).flatten(),
+        let mut stmt = self.db.prepare_cached(
+            "insert into linear_percentiles (
+                    experiment_code,
+                    v_group,
+                    version,
+                    percentile,
+                    v_int,
+                    v_float
+                ) values (
+                    :experiment_code,
+                    :v_group,
+                    :version,
+                    :percentile,
+                    :v_int,
+                    :v_float
+                )",
+        )?;
 
-            ":v_float_5": datapoint.get_confidence(Confidence::Five).clone().map(|val| val.0.to_float()).flatten(),
-            ":v_float_95": datapoint.get_confidence(Confidence::Five).clone().map(|val| val.1.to_float()).flatten(),
+        for (percentile, v) in percentile_values(&datapoint.v_confidence) {
+            stmt.execute(rusqlite::named_params! {
+                ":experiment_code": exp_code,
+                ":v_group": datapoint.group,
+                ":version": version,
+                ":percentile": percentile,
+                ":v_int": v.to_int(),
+                ":v_float": v.to_float(),
+            })?;
+        }
+        Ok(version)
+    }
 
-            ":v_float_10": datapoint.get_confidence(Confidence::Ten).clone().map(|val| val.0.to_float()).flatten(),
-            ":v_float_90": datapoint.get_confidence(Confidence::Ten).clone().map(|val| val.1.to_float()).flatten(),
+    /// Add the raw sample of a given version of a linear datapoint
+    pub(crate) fn add_linear_sample(
+        &self,
+        exp_code: &str,
+        group: &str,
+        version: isize,
+        sample: &Sample,
+    ) -> BencherResult<()> {
+        let mut stmt = self.db.prepare_cached(
+            "insert into linear_samples (
+                    experiment_code,
+                    v_group,
+                    version,
+                    v_int,
+                    v_float
+                ) values (
+                    :experiment_code,
+                    :v_group,
+                    :version,
+                    :v_int,
+                    :v_float
+                )",
+        )?;
 
-            ":v_float_25": datapoint.get_confidence(Confidence::TwentyFive).clone().map(|val| val.0.to_float()).flatten(),
-            ":v_float_75": datapoint.get_confidence(Confidence::TwentyFive).clone().map(|val| val.1.to_float()).flatten(),
-        })?;
+        for v in sample_values(sample) {
+            stmt.execute(rusqlite::named_params! {
+                ":experiment_code": exp_code,
+                ":v_group": group,
+                ":version": version,
+                ":v_int": v.to_int(),
+                ":v_float": v.to_float(),
+            })?;
+        }
         Ok(())
     }
 
@@ -307,101 +355,37 @@ impl DbWriteBackend {
             .map_err(|e| e.into())
     }
 
+    /// Add a bidimensional datapoint, returning the version it was added with
     pub(crate) fn add_xy_datapoint(
         &self,
         exp_code: &str,
         datapoint: XYDatapoint,
-    ) -> BencherResult<()> {
+        metadata: &RunMetadata,
+    ) -> BencherResult<isize> {
         let version = self.get_new_xy_version(exp_code, datapoint.tag.unwrap())?;
         let mut stmt = self.db.prepare(
             "insert into xy_results (
                     experiment_code,
                     tag,
                     version,
-
                     x_int,
-                    x_int_1,
-                    x_int_5,
-                    x_int_10,
-                    x_int_25,
-                    x_int_99,
-                    x_int_95,
-                    x_int_90,
-                    x_int_75,
-
                     y_int,
-                    y_int_1,
-                    y_int_5,
-                    y_int_10,
-                    y_int_25,
-                    y_int_99,
-                    y_int_95,
-                    y_int_90,
-                    y_int_75,
-
                     x_float,
-                    x_float_1,
-                    x_float_5,
-                    x_float_10,
-                    x_float_25,
-                    x_float_99,
-                    x_float_95,
-                    x_float_90,
-                    x_float_75,
-
                     y_float,
-                    y_float_1,
-                    y_float_5,
-                    y_float_10,
-                    y_float_25,
-                    y_float_99,
-                    y_float_95,
-                    y_float_90,
-                    y_float_75
+                    timestamp,
+                    git_revision,
+                    hostname
                 ) values (
                     :experiment_code,
                     :tag,
                     :version,
-
                     :x_int,
-                    :x_int_1,
-                    :x_int_5,
-                    :x_int_10,
-                    :x_int_25,
-                    :x_int_99,
-                    :x_int_95,
-                    :x_int_90,
-                    :x_int_75,
-
                     :y_int,
-                    :y_int_1,
-                    :y_int_5,
-                    :y_int_10,
-                    :y_int_25,
-                    :y_int_99,
-                    :y_int_95,
-                    :y_int_90,
-                    :y_int_75,
-
                     :x_float,
-                    :x_float_1,
-                    :x_float_5,
-                    :x_float_10,
-                    :x_float_25,
-                    :x_float_99,
-                    :x_float_95,
-                    :x_float_90,
-                    :x_float_75,
-
                     :y_float,
-                    :y_float_1,
-                    :y_float_5,
-                    :y_float_10,
-                    :y_float_25,
-                    :y_float_99,
-                    :y_float_95,
-                    :y_float_90,
-                    :y_float_75
+                    :timestamp,
+                    :git_revision,
+                    :hostname
                 )",
         )?;
 
@@ -409,60 +393,119 @@ impl DbWriteBackend {
             ":experiment_code": exp_code,
             ":tag": datapoint.tag.unwrap(),
             ":version": version,
-
             ":x_int": datapoint.x.to_int(),
-            ":x_float": datapoint.x.to_float(),
             ":y_int": datapoint.y.to_int(),
+            ":x_float": datapoint.x.to_float(),
             ":y_float": datapoint.y.to_float(),
+            ":timestamp": metadata.timestamp.unwrap_or_else(now_timestamp),
+            ":git_revision": metadata.git_revision,
+            ":hostname": metadata.hostname,
+        })?;
 
-            ":x_int_1": datapoint.get_x_confidence(1.try_into().unwrap()).clone().map(|val| val.0.to_int()).flatten(),
-            ":x_int_99": datapoint.get_x_confidence(1.try_into().unwrap()).clone().map(|val| val.1.to_int()).flatten(),
-
-            ":x_int_5": datapoint.get_x_confidence(5.try_into().unwrap()).clone().map(|val| val.0.to_int()).flatten(),
-            ":x_int_95": datapoint.get_x_confidence(5.try_into().unwrap()).clone().map(|val| val.1.to_int()).flatten(),
-
-            ":x_int_10": datapoint.get_x_confidence(10.try_into().unwrap()).clone().map(|val| val.0.to_int()).flatten(),
-            ":x_int_90": datapoint.get_x_confidence(10.try_into().unwrap()).clone().map(|val| val.1.to_int()).flatten(),
-
-            ":x_int_25": datapoint.get_x_confidence(25.try_into().unwrap()).clone().map(|val| val.0.to_int()).flatten(),
-            ":x_int_75": datapoint.get_x_confidence(25.try_into().unwrap()).clone().map(|val| val.1.to_int()).flatten(),
-
-            ":x_float_1": datapoint.get_x_confidence(1.try_into().unwrap()).clone().map(|val| val.0.to_float()).flatten(),
-            ":x_float_99": datapoint.get_x_confidence(1.try_into().unwrap()).clone().map(|val| val.1.to_float()).flatten(),
-
-            ":x_float_5": datapoint.get_x_confidence(5.try_into().unwrap()).clone().map(|val| val.0.to_float()).flatten(),
-            ":x_float_95": datapoint.get_x_confidence(5.try_into().unwrap()).clone().map(|val| val.1.to_float()).flatten(),
-
-            ":x_float_10": datapoint.get_x_confidence(10.try_into().unwrap()).clone().map(|val| val.0.to_float()).flatten(),
-            ":x_float_90": datapoint.get_x_confidence(10.try_into().unwrap()).clone().map(|val| val.1.to_float()).flatten(),
-
-            ":x_float_25": datapoint.get_x_confidence(25.try_into().unwrap()).clone().map(|val| val.0.to_float()).flatten(),
-            ":x_float_75": datapoint.get_x_confidence(25.try_into().unwrap()).clone().map(|val| val.1.to_float()).flatten(),
-
-            ":y_int_1": datapoint.get_y_confidence(1.try_into().unwrap()).clone().map(|val| val.0.to_int()).flatten(),
-            ":y_int_99": datapoint.get_y_confidence(1.try_into().unwrap()).clone().map(|val| val.1.to_int()).flatten(),
-
-            ":y_int_5": datapoint.get_y_confidence(5.try_into().unwrap()).clone().map(|val| val.0.to_int()).flatten(),
-            ":y_int_95": datapoint.get_y_confidence(5.try_into().unwrap()).clone().map(|val| val.1.to_int()).flatten(),
-
-            ":y_int_10": datapoint.get_y_confidence(10.try_into().unwrap()).clone().map(|val| val.0.to_int()).flatten(),
-            ":y_int_90": datapoint.get_y_confidence(10.try_into().unwrap()).clone().map(|val| val.1.to_int()).flatten(),
+        let mut stmt = self.db.prepare_cached(
+            "insert into xy_metadata (
+                    experiment_code,
+                    tag,
+                    version,
+                    key,
+                    value
+                ) values (
+                    :experiment_code,
+                    :tag,
+                    :version,
+                    :key,
+                    :value
+                )",
+        )?;
 
-            ":y_int_25": datapoint.get_y_confidence(25.try_into().unwrap()).clone().map(|val| val.0.to_int()).flatten(),
-            ":y_int_75": datapoint.get_y_confidence(25.try_into().unwrap()).clone().map(|val| val.1.to_int()).flatten(),
+        for (key, value) in &metadata.values {
+            stmt.execute(rusqlite::named_params! {
+                ":experiment_code": exp_code,
+                ":tag": datapoint.tag.unwrap(),
+                ":version": version,
+                ":key": key,
+                ":value": value,
+            })?;
+        }
 
-            ":y_float_1": datapoint.get_y_confidence(1.try_into().unwrap()).clone().map(|val| val.0.to_float()).flatten(),
-            ":y_float_99": datapoint.get_y_confidence(1.try_into().unwrap()).clone().map(|val| val.1.to_float()).flatten(),
+        let mut stmt = self.db.prepare_cached(
+            "insert into xy_percentiles (
+                    experiment_code,
+                    tag,
+                    version,
+                    axis,
+                    percentile,
+                    v_int,
+                    v_float
+                ) values (
+                    :experiment_code,
+                    :tag,
+                    :version,
+                    :axis,
+                    :percentile,
+                    :v_int,
+                    :v_float
+                )",
+        )?;
 
-            ":y_float_5": datapoint.get_y_confidence(5.try_into().unwrap()).clone().map(|val| val.0.to_float()).flatten(),
-            ":y_float_95": datapoint.get_y_confidence(5.try_into().unwrap()).clone().map(|val| val.1.to_float()).flatten(),
+        for (axis, confidences) in [
+            ("x", &datapoint.x_confidence),
+            ("y", &datapoint.y_confidence),
+        ] {
+            for (percentile, v) in percentile_values(confidences) {
+                stmt.execute(rusqlite::named_params! {
+                    ":experiment_code": exp_code,
+                    ":tag": datapoint.tag.unwrap(),
+                    ":version": version,
+                    ":axis": axis,
+                    ":percentile": percentile,
+                    ":v_int": v.to_int(),
+                    ":v_float": v.to_float(),
+                })?;
+            }
+        }
+        Ok(version)
+    }
 
-            ":y_float_10": datapoint.get_y_confidence(10.try_into().unwrap()).clone().map(|val| val.0.to_float()).flatten(),
-            ":y_float_90": datapoint.get_y_confidence(10.try_into().unwrap()).clone().map(|val| val.1.to_float()).flatten(),
+    /// Add the raw samples of a given version of a bidimensional datapoint
+    pub(crate) fn add_xy_samples(
+        &self,
+        exp_code: &str,
+        tag: isize,
+        version: isize,
+        x_sample: &Sample,
+        y_sample: &Sample,
+    ) -> BencherResult<()> {
+        let mut stmt = self.db.prepare_cached(
+            "insert into xy_samples (
+                    experiment_code,
+                    tag,
+                    version,
+                    axis,
+                    v_int,
+                    v_float
+                ) values (
+                    :experiment_code,
+                    :tag,
+                    :version,
+                    :axis,
+                    :v_int,
+                    :v_float
+                )",
+        )?;
 
-            ":y_float_25": datapoint.get_y_confidence(25.try_into().unwrap()).clone().map(|val| val.0.to_float()).flatten(),
-            ":y_float_75": datapoint.get_y_confidence(25.try_into().unwrap()).clone().map(|val| val.1.to_float()).flatten(),
-        })?;
+        for (axis, sample) in [("x", x_sample), ("y", y_sample)] {
+            for v in sample_values(sample) {
+                stmt.execute(rusqlite::named_params! {
+                    ":experiment_code": exp_code,
+                    ":tag": tag,
+                    ":version": version,
+                    ":axis": axis,
+                    ":v_int": v.to_int(),
+                    ":v_float": v.to_float(),
+                })?;
+            }
+        }
         Ok(())
     }
 
@@ -566,24 +609,19 @@ impl DbReadBackend {
         Self::from_conns(dbs)
     }
 
+    fn get_db(&self, code: &str) -> BencherResult<&rusqlite::Connection> {
+        self.code_map
+            .get(code)
+            .map(|idx| &self.dbs[*idx])
+            .ok_or_else(|| BencherError::UnknownCode(code.to_string()))
+    }
+
     pub(crate) fn get_linear_datapoints(&self, code: &str) -> BencherResult<Vec<LinearDatapoint>> {
         let mut vec = vec![];
 
-        let mut stmt = self.dbs[self.code_map[code]].prepare(
-            "select v_group, v_int, v_float,
-                    v_int_1,    v_int_99,
-                    v_float_1,  v_float_99,
-
-                    v_int_5,    v_int_95,
-                    v_float_5,  v_float_95,
-
-                    v_int_10,   v_int_90,
-                    v_float_10, v_float_90,
-
-                    v_int_25,   v_int_75,
-                    v_float_25, v_float_75,
-
-                    tag, max(version)
+        let db = self.get_db(code)?;
+        let mut stmt = db.prepare(
+            "select v_group, v_int, v_float, tag, max(version)
              from linear_results
              where experiment_code = :code
              group by v_group
@@ -591,9 +629,13 @@ impl DbReadBackend {
         )?;
 
         for datapoint in stmt.query_map(rusqlite::named_params! { ":code": code }, |row| {
-            LinearDatapoint::try_from(row).map_err(|e| e.into())
+            Ok((LinearDatapoint::try_from(row), row.get::<usize, isize>(4)?))
         })? {
-            vec.push(datapoint?);
+            let (datapoint, version) = datapoint?;
+            let mut datapoint = datapoint?;
+            datapoint.v_confidence =
+                self.get_linear_confidences(db, code, &datapoint.group, version)?;
+            vec.push(datapoint);
         }
 
         vec.sort_by_key(|d| d.group.clone());
@@ -601,51 +643,454 @@ impl DbReadBackend {
         Ok(vec)
     }
 
+    /// Get the confidence intervals of a given version of a linear datapoint
+    fn get_linear_confidences(
+        &self,
+        db: &rusqlite::Connection,
+        code: &str,
+        group: &str,
+        version: isize,
+    ) -> BencherResult<BTreeMap<Confidence, (Value, Value)>> {
+        let mut stmt = db.prepare_cached(
+            "select percentile, v_int, v_float
+             from linear_percentiles
+             where experiment_code = :code and v_group = :v_group and version = abs(:version)
+             ",
+        )?;
+
+        let percentiles = stmt
+            .query_map(
+                rusqlite::named_params! { ":code": code, ":v_group": group, ":version": version },
+                |row| Ok((row.get(0)?, Value::new(row.get(1)?, row.get(2)?))),
+            )?
+            .map(|p| p.map_err(|e| e.into()).and_then(|(p, v)| Ok((p, v?))))
+            .collect::<BencherResult<Vec<_>>>()?;
+
+        confidences_from_percentiles(percentiles)
+    }
+
     pub(crate) fn get_xy_datapoints(&self, code: &str) -> BencherResult<Vec<XYDatapoint>> {
         let mut vec = vec![];
 
-        let mut stmt = self.dbs[self.code_map[code]].prepare(
-            "select x_int, x_float,
-                y_int, y_float,
-                x_int_1,    x_int_99,
-                x_float_1,  x_float_99,
+        let db = self.get_db(code)?;
+        let mut stmt = db.prepare(
+            "select x_int, x_float, y_int, y_float, tag, max(version)
+             from xy_results
+             where experiment_code = :code
+             group by tag
+             ",
+        )?;
+
+        for datapoint in stmt.query_map(rusqlite::named_params! { ":code": code }, |row| {
+            Ok((XYDatapoint::try_from(row), row.get::<usize, isize>(5)?))
+        })? {
+            let (datapoint, version) = datapoint?;
+            let mut datapoint = datapoint?;
+            let tag = datapoint.tag.unwrap();
+            datapoint.x_confidence = self.get_xy_confidences(db, code, tag, version, "x")?;
+            datapoint.y_confidence = self.get_xy_confidences(db, code, tag, version, "y")?;
+            vec.push(datapoint);
+        }
 
-                x_int_5,    x_int_95,
-                x_float_5,  x_float_95,
+        vec.sort_by_key(|d| d.tag);
+        Ok(vec)
+    }
 
-                x_int_10,   x_int_90,
-                x_float_10, x_float_90,
+    /// Get the confidence intervals of one axis of a given version of a bidimensional datapoint
+    fn get_xy_confidences(
+        &self,
+        db: &rusqlite::Connection,
+        code: &str,
+        tag: isize,
+        version: isize,
+        axis: &str,
+    ) -> BencherResult<BTreeMap<Confidence, (Value, Value)>> {
+        let mut stmt = db.prepare_cached(
+            "select percentile, v_int, v_float
+             from xy_percentiles
+             where experiment_code = :code and tag = :tag and version = abs(:version) and axis = :axis
+             ",
+        )?;
 
-                x_int_25,   x_int_75,
-                x_float_25, x_float_75,
+        let percentiles = stmt
+            .query_map(
+                rusqlite::named_params! { ":code": code, ":tag": tag, ":version": version, ":axis": axis },
+                |row| Ok((row.get(0)?, Value::new(row.get(1)?, row.get(2)?))),
+            )?
+            .map(|p| p.map_err(|e| e.into()).and_then(|(p, v)| Ok((p, v?))))
+            .collect::<BencherResult<Vec<_>>>()?;
 
-                y_int_1,    y_int_99,
-                y_float_1,  y_float_99,
+        confidences_from_percentiles(percentiles)
+    }
 
-                y_int_5,    y_int_95,
-                y_float_5,  y_float_95,
+    /// Get every version of a linear datapoint, in the order they were added
+    pub(crate) fn get_linear_history(
+        &self,
+        code: &str,
+        group: &str,
+    ) -> BencherResult<Vec<DatapointVersion<LinearDatapoint>>> {
+        let db = self.get_db(code)?;
+        let mut stmt = db.prepare(
+            "select v_group, v_int, v_float, tag, version, timestamp, git_revision, hostname
+             from linear_results
+             where experiment_code = :code and v_group = :v_group
+             order by abs(version)
+             ",
+        )?;
 
-                y_int_10,   y_int_90,
-                y_float_10, y_float_90,
+        let rows = stmt
+            .query_map(
+                rusqlite::named_params! { ":code": code, ":v_group": group },
+                |row| {
+                    Ok((
+                        LinearDatapoint::try_from(row),
+                        row.get::<usize, isize>(4)?,
+                        row.get::<usize, Option<i64>>(5)?,
+                        row.get::<usize, Option<String>>(6)?,
+                        row.get::<usize, Option<String>>(7)?,
+                    ))
+                },
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let active_version = rows.iter().map(|row| row.1).max();
+        let mut history = Vec::with_capacity(rows.len());
+        for (datapoint, version, timestamp, git_revision, hostname) in rows {
+            let mut datapoint = datapoint?;
+            datapoint.v_confidence = self.get_linear_confidences(db, code, group, version)?;
+            history.push(DatapointVersion {
+                version: version.unsigned_abs(),
+                status: VersionStatus::new(version, active_version),
+                datapoint,
+                run: RunMetadata {
+                    timestamp,
+                    git_revision,
+                    hostname,
+                    values: Self::run_metadata_values(
+                        db,
+                        "linear",
+                        "v_group",
+                        code,
+                        group.to_string().into(),
+                        version,
+                    )?,
+                },
+            });
+        }
 
-                y_int_25,   y_int_75,
-                y_float_25, y_float_75,
+        Ok(history)
+    }
 
-                tag, max(version)
-         from xy_results
-         where experiment_code = :code
-         group by tag
-         ",
+    /// Get every version of a bidimensional datapoint, in the order they were added
+    pub(crate) fn get_xy_history(
+        &self,
+        code: &str,
+        tag: isize,
+    ) -> BencherResult<Vec<DatapointVersion<XYDatapoint>>> {
+        let db = self.get_db(code)?;
+        let mut stmt = db.prepare(
+            "select x_int, x_float, y_int, y_float, tag, version, timestamp, git_revision, hostname
+             from xy_results
+             where experiment_code = :code and tag = :tag
+             order by abs(version)
+             ",
         )?;
 
-        for datapoint in stmt.query_map(rusqlite::named_params! { ":code": code }, |row| {
-            XYDatapoint::try_from(row).map_err(|e| e.into())
-        })? {
-            vec.push(datapoint?);
+        let rows = stmt
+            .query_map(
+                rusqlite::named_params! { ":code": code, ":tag": tag },
+                |row| {
+                    Ok((
+                        XYDatapoint::try_from(row),
+                        row.get::<usize, isize>(5)?,
+                        row.get::<usize, Option<i64>>(6)?,
+                        row.get::<usize, Option<String>>(7)?,
+                        row.get::<usize, Option<String>>(8)?,
+                    ))
+                },
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let active_version = rows.iter().map(|row| row.1).max();
+        let mut history = Vec::with_capacity(rows.len());
+        for (datapoint, version, timestamp, git_revision, hostname) in rows {
+            let mut datapoint = datapoint?;
+            datapoint.x_confidence = self.get_xy_confidences(db, code, tag, version, "x")?;
+            datapoint.y_confidence = self.get_xy_confidences(db, code, tag, version, "y")?;
+            history.push(DatapointVersion {
+                version: version.unsigned_abs(),
+                status: VersionStatus::new(version, active_version),
+                datapoint,
+                run: RunMetadata {
+                    timestamp,
+                    git_revision,
+                    hostname,
+                    values: Self::run_metadata_values(
+                        db,
+                        "xy",
+                        "tag",
+                        code,
+                        (tag as i64).into(),
+                        version,
+                    )?,
+                },
+            });
         }
 
-        vec.sort_by_key(|d| d.tag);
-        Ok(vec)
+        Ok(history)
+    }
+
+    /// Get the type and label of an experiment
+    pub(crate) fn get_experiment(&self, code: &str) -> BencherResult<(String, String)> {
+        self.get_db(code)?
+            .query_row(
+                "select experiment_type, experiment_label from experiments where experiment_code = :code",
+                rusqlite::named_params! { ":code": code },
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .map_err(|e| e.into())
+    }
+
+    /// Get the raw sample of the active version of a linear datapoint, if it was stored
+    pub(crate) fn get_linear_sample(
+        &self,
+        code: &str,
+        group: &str,
+    ) -> BencherResult<Option<Sample>> {
+        let version: Option<usize> = self
+            .get_db(code)?
+            .query_row(
+                "select abs(max(version)) from linear_results where experiment_code = :code and v_group = :v_group",
+                rusqlite::named_params! { ":code": code, ":v_group": group },
+                |row| row.get(0),
+            )?;
+
+        match version {
+            Some(version) => self.get_linear_version_sample(code, group, version),
+            None => Ok(None),
+        }
+    }
+
+    /// Get the raw sample of a version of a linear datapoint, if it was stored
+    pub(crate) fn get_linear_version_sample(
+        &self,
+        code: &str,
+        group: &str,
+        version: usize,
+    ) -> BencherResult<Option<Sample>> {
+        let db = self.get_db(code)?;
+        if !table_exists(db, "linear_samples")? {
+            return Ok(None);
+        }
+
+        let mut stmt = db.prepare(
+            "select v_int, v_float
+             from linear_samples
+             where experiment_code = :code and v_group = :v_group and version = :version
+             ",
+        )?;
+
+        let values = stmt
+            .query_map(
+                rusqlite::named_params! { ":code": code, ":v_group": group, ":version": version },
+                |row| Value::new(row.get(0)?, row.get(1)?).map_err(|e| e.into()),
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok((!values.is_empty()).then(|| sample_from_values(values)))
+    }
+
+    /// Get the raw x and y samples of the active version of a bidimensional datapoint, if they were stored
+    pub(crate) fn get_xy_samples(
+        &self,
+        code: &str,
+        tag: isize,
+    ) -> BencherResult<Option<(Sample, Sample)>> {
+        let version: Option<usize> = self.get_db(code)?.query_row(
+            "select abs(max(version)) from xy_results where experiment_code = :code and tag = :tag",
+            rusqlite::named_params! { ":code": code, ":tag": tag },
+            |row| row.get(0),
+        )?;
+
+        match version {
+            Some(version) => self.get_xy_version_samples(code, tag, version),
+            None => Ok(None),
+        }
+    }
+
+    /// Get the raw x and y samples of a version of a bidimensional datapoint, if they were stored
+    pub(crate) fn get_xy_version_samples(
+        &self,
+        code: &str,
+        tag: isize,
+        version: usize,
+    ) -> BencherResult<Option<(Sample, Sample)>> {
+        let db = self.get_db(code)?;
+        if !table_exists(db, "xy_samples")? {
+            return Ok(None);
+        }
+
+        let mut stmt = db.prepare(
+            "select v_int, v_float
+             from xy_samples
+             where experiment_code = :code and tag = :tag and axis = :axis and version = :version
+             ",
+        )?;
+
+        let mut samples = Vec::new();
+        for axis in ["x", "y"] {
+            let values = stmt
+                .query_map(
+                    rusqlite::named_params! { ":code": code, ":tag": tag, ":axis": axis, ":version": version },
+                    |row| Value::new(row.get(0)?, row.get(1)?).map_err(|e| e.into()),
+                )?
+                .collect::<Result<Vec<_>, _>>()?;
+            samples.push(values);
+        }
+
+        let y_values = samples.pop().unwrap();
+        let x_values = samples.pop().unwrap();
+        if x_values.is_empty() || y_values.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some((
+                sample_from_values(x_values),
+                sample_from_values(y_values),
+            )))
+        }
+    }
+
+    /// Get the run metadata of the active versions of an experiment's datapoints
+    fn active_run_metadata(
+        db: &rusqlite::Connection,
+        code: &str,
+    ) -> BencherResult<Vec<RunMetadata>> {
+        let mut runs = vec![];
+        for (kind, key) in [("linear", "v_group"), ("xy", "tag")] {
+            let mut stmt = db.prepare_cached(&format!(
+                "select timestamp, git_revision, hostname, {1}, version
+                 from {0}_results
+                 where experiment_code = :code and ({1}, version) in
+                    (select {1}, max(version) from {0}_results where experiment_code = :code group by {1})
+                 ",
+                kind, key
+            ))?;
+
+            let rows = stmt
+                .query_map(rusqlite::named_params! { ":code": code }, |row| {
+                    Ok((
+                        row.get::<usize, Option<i64>>(0)?,
+                        row.get::<usize, Option<String>>(1)?,
+                        row.get::<usize, Option<String>>(2)?,
+                        row.get::<usize, rusqlite::types::Value>(3)?,
+                        row.get::<usize, isize>(4)?,
+                    ))
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+
+            for (timestamp, git_revision, hostname, key_value, version) in rows {
+                runs.push(RunMetadata {
+                    timestamp,
+                    git_revision,
+                    hostname,
+                    values: Self::run_metadata_values(db, kind, key, code, key_value, version)?,
+                });
+            }
+        }
+
+        Ok(runs)
+    }
+
+    /// Get the run metadata of the most recently added version of an experiment's datapoints
+    fn last_run_metadata(
+        db: &rusqlite::Connection,
+        code: &str,
+    ) -> BencherResult<Option<RunMetadata>> {
+        let mut last: Option<(RunMetadata, i64)> = None;
+        for (kind, key) in [("linear", "v_group"), ("xy", "tag")] {
+            let mut stmt = db.prepare_cached(&format!(
+                "select timestamp, git_revision, hostname, {1}, version
+                 from {0}_results
+                 where experiment_code = :code
+                 order by timestamp is null, timestamp desc, rowid desc
+                 limit 1
+                 ",
+                kind, key
+            ))?;
+
+            let row = stmt
+                .query_row(rusqlite::named_params! { ":code": code }, |row| {
+                    Ok((
+                        row.get::<usize, Option<i64>>(0)?,
+                        row.get::<usize, Option<String>>(1)?,
+                        row.get::<usize, Option<String>>(2)?,
+                        row.get::<usize, rusqlite::types::Value>(3)?,
+                        row.get::<usize, isize>(4)?,
+                    ))
+                })
+                .optional()?;
+
+            if let Some((timestamp, git_revision, hostname, key_value, version)) = row {
+                if last
+                    .as_ref()
+                    .is_none_or(|(_, t)| timestamp.unwrap_or(i64::MIN) > *t)
+                {
+                    let run = RunMetadata {
+                        timestamp,
+                        git_revision,
+                        hostname,
+                        values: Self::run_metadata_values(db, kind, key, code, key_value, version)?,
+                    };
+                    last = Some((run, timestamp.unwrap_or(i64::MIN)));
+                }
+            }
+        }
+
+        Ok(last.map(|(run, _)| run))
+    }
+
+    /// Get the key/value metadata of a given version of a datapoint
+    ///
+    /// `kind` is either `linear` or `xy`, with datapoints identified by `key` (`v_group` or `tag`)
+    fn run_metadata_values(
+        db: &rusqlite::Connection,
+        kind: &str,
+        key: &str,
+        code: &str,
+        key_value: rusqlite::types::Value,
+        version: isize,
+    ) -> BencherResult<BTreeMap<String, String>> {
+        let mut stmt = db.prepare_cached(&format!(
+            "select key, value
+             from {0}_metadata
+             where experiment_code = :code and {1} = :key_value and version = abs(:version)
+             ",
+            kind, key
+        ))?;
+
+        let values = stmt
+            .query_map(
+                rusqlite::named_params! { ":code": code, ":key_value": key_value, ":version": version },
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )?
+            .collect::<Result<BTreeMap<_, _>, _>>()?;
+
+        Ok(values)
+    }
+
+    /// Whether an experiment has a run which matches the metadata filters of the selector
+    fn filter_run_metadata(
+        db: &rusqlite::Connection,
+        code: &str,
+        selector: &Selector,
+    ) -> BencherResult<bool> {
+        if !selector.filters_metadata() {
+            return Ok(true);
+        }
+
+        Ok(Self::active_run_metadata(db, code)?
+            .iter()
+            .any(|run| selector.filter_metadata(run)))
     }
 
     pub(crate) fn status(
@@ -667,11 +1112,15 @@ impl DbReadBackend {
                     exp_code: row.get(0).unwrap_or("".to_string()),
                     n_datapoints: 0,
                     n_active_datapoints: 0,
+                    last_run: None,
                 })
             })? {
-                let status = status.unwrap();
-                if selector.filter_code(&status.exp_code) && selector.filter_type(&status.exp_type)
+                let mut status = status.unwrap();
+                if selector.filter_code(&status.exp_code)
+                    && selector.filter_type(&status.exp_type)
+                    && Self::filter_run_metadata(db, &status.exp_code, selector)?
                 {
+                    status.last_run = Self::last_run_metadata(db, &status.exp_code)?;
                     map.insert(status.exp_code.clone(), status);
                 }
             }
@@ -685,25 +1134,29 @@ impl DbReadBackend {
                 ))
             })? {
                 let (code, n_datapoints) = status.unwrap();
-                map.get_mut(&code).map(|s| s.n_datapoints = n_datapoints);
+                if let Some(s) = map.get_mut(&code) {
+                    s.n_datapoints = n_datapoints;
+                }
             }
 
             let mut stmt =
                 db.prepare("select experiment_code, tag, max(version) from xy_results")?;
             for code in stmt.query_map([], |row| Ok(row.get(0).unwrap_or("".to_string())))? {
-                map.get_mut(&code.unwrap())
-                    .map(|s| s.n_active_datapoints += 1);
+                if let Some(s) = map.get_mut(&code.unwrap()) {
+                    s.n_active_datapoints += 1;
+                }
             }
 
             let mut stmt = db
                 .prepare("select experiment_code, v_group, max(version) from linear_results group by experiment_code, v_group")?;
             for code in stmt.query_map([], |row| Ok(row.get(0).unwrap_or("".to_string())))? {
-                map.get_mut(&code.unwrap())
-                    .map(|s| s.n_active_datapoints += 1);
+                if let Some(s) = map.get_mut(&code.unwrap()) {
+                    s.n_active_datapoints += 1;
+                }
             }
         }
 
-        let mut vector: Vec<_> = map.into_iter().map(|(_, v)| v).collect();
+        let mut vector: Vec<_> = map.into_values().collect();
         // rust sort is stable
 
         vector.sort_by(|a, b| a.exp_code.cmp(&b.exp_code));
@@ -745,13 +1198,13 @@ impl DbReadBackend {
 
                 for virt_exp in virtual_linear_experiments
                     .iter()
-                    .filter(|v| v.source_exp_type == exp_type)
+                    .filter(|v| v.source_exp_types.first() == Some(&exp_type))
                 {
                     infos.push(LinearExperimentInfo {
                         database: database.clone(),
                         exp_code: row.get(0).unwrap_or("".to_string()),
                         exp_label: row.get(1).unwrap_or("".to_string()),
-                        exp_type: virt_exp.source_exp_type.clone(),
+                        exp_type: exp_type.clone(),
                         horizontal_label: virt_exp.horizontal_label.clone(),
                         v_label: virt_exp.v_label.clone(),
                         v_units: virt_exp.v_units.clone(),
@@ -761,7 +1214,9 @@ impl DbReadBackend {
                 Ok(infos)
             })? {
                 for info in infos.unwrap() {
-                    if selector.filter_code(&info.exp_code) && selector.filter_type(&info.exp_type)
+                    if selector.filter_code(&info.exp_code)
+                        && selector.filter_type(&info.exp_type)
+                        && Self::filter_run_metadata(db, &info.exp_code, selector)?
                     {
                         list.push(info);
                     }
@@ -808,13 +1263,13 @@ impl DbReadBackend {
 
                 for virt_exp in virtual_xy_experiments
                     .iter()
-                    .filter(|v| v.source_exp_type == exp_type)
+                    .filter(|v| v.source_exp_types.first() == Some(&exp_type))
                 {
                     infos.push(XYExperimentInfo {
                         database: database.clone(),
                         exp_code: row.get(0).unwrap_or("".to_string()),
                         exp_label: row.get(1).unwrap_or("".to_string()),
-                        exp_type: virt_exp.source_exp_type.clone(),
+                        exp_type: exp_type.clone(),
                         x_label: virt_exp.x_label.clone(),
                         x_units: virt_exp.x_units.clone(),
                         y_label: virt_exp.y_label.clone(),
@@ -825,7 +1280,9 @@ impl DbReadBackend {
                 Ok(infos)
             })? {
                 for info in infos.unwrap() {
-                    if selector.filter_code(&info.exp_code) && selector.filter_type(&info.exp_type)
+                    if selector.filter_code(&info.exp_code)
+                        && selector.filter_type(&info.exp_type)
+                        && Self::filter_run_metadata(db, &info.exp_code, selector)?
                     {
                         list.push(info);
                     }
@@ -884,6 +1341,15 @@ impl DbReadBackend {
                 })
                 .map(|x| x.map_err(|e| e.into()))
                 .collect::<BencherResult<Vec<_>>>()?;
+            if selector.filters_metadata() {
+                let mut selected = Vec::with_capacity(inner.len());
+                for (exp_code, exp_label) in inner {
+                    if Self::filter_run_metadata(db, &exp_code, selector)? {
+                        selected.push((exp_code, exp_label));
+                    }
+                }
+                inner = selected;
+            }
             vec.append(&mut inner);
         }
 
@@ -892,78 +1358,58 @@ impl DbReadBackend {
     }
 }
 
-fn create_confidence_arg(
-    min_int: Option<i64>,
-    max_int: Option<i64>,
-    min_float: Option<f64>,
-    max_float: Option<f64>,
-) -> Option<Either<(i64, i64), (f64, f64)>> {
-    if let (Some(lower), Some(upper)) = (min_int, max_int) {
-        Some(Either::Left((lower, upper)))
-    } else if let (Some(lower), Some(upper)) = (min_float, max_float) {
-        Some(Either::Right((lower, upper)))
-    } else {
-        None
+/// Flatten a sample into values, to be stored
+fn sample_values(sample: &Sample) -> Vec<Value> {
+    match sample {
+        Either::Left(s) => s.iter().map(|v| Value::Int(*v)).collect(),
+        Either::Right(s) => s.iter().map(|v| Value::Float(*v)).collect(),
+    }
+}
+
+/// Pair up the values at each percentile into confidence intervals
+///
+/// Percentiles without their mirror (e.g., a p99 without the p1) are ignored
+fn confidences_from_percentiles(
+    percentiles: Vec<(f64, Value)>,
+) -> BencherResult<BTreeMap<Confidence, (Value, Value)>> {
+    let mut bounds: BTreeMap<Confidence, (Option<Value>, Option<Value>)> = BTreeMap::new();
+    for (percentile, value) in percentiles {
+        let entry = bounds.entry(Confidence::new(percentile)?).or_default();
+        if percentile < 50.0 {
+            entry.0 = Some(value);
+        } else {
+            entry.1 = Some(value);
+        }
     }
+
+    Ok(bounds
+        .into_iter()
+        .filter_map(|(confidence, bounds)| match bounds {
+            (Some(lower), Some(upper)) => Some((confidence, (lower, upper))),
+            _ => None,
+        })
+        .collect())
+}
+
+/// Flatten confidence intervals into the values at each percentile, to be stored
+fn percentile_values(confidences: &BTreeMap<Confidence, (Value, Value)>) -> Vec<(f64, Value)> {
+    confidences
+        .iter()
+        .flat_map(|(confidence, (lower, upper))| {
+            [(confidence.lower(), *lower), (confidence.upper(), *upper)]
+        })
+        .collect()
 }
 
 impl TryFrom<&rusqlite::Row<'_>> for LinearDatapoint {
     type Error = BencherError;
     fn try_from(row: &rusqlite::Row) -> BencherResult<Self> {
-        let mut datapoint = LinearDatapoint::new(
+        let datapoint = LinearDatapoint::new(
             row.get::<usize, String>(0).unwrap(),
             Value::new(row.get(1).unwrap(), row.get(2).unwrap())?,
         );
 
-        // x 1 - 99
-        if let Some(e) = create_confidence_arg(
-            row.get(3).unwrap(),
-            row.get(4).unwrap(),
-            row.get(5).unwrap(),
-            row.get(6).unwrap(),
-        ) {
-            datapoint
-                .add_confidence(Confidence::One, e)
-                .expect("the type of the values should be the same");
-        }
-
-        // x 5 - 95
-        if let Some(e) = create_confidence_arg(
-            row.get(7).unwrap(),
-            row.get(8).unwrap(),
-            row.get(9).unwrap(),
-            row.get(10).unwrap(),
-        ) {
-            datapoint
-                .add_confidence(Confidence::Five, e)
-                .expect("the type of the values should be the same");
-        }
-
-        // x 10 - 90
-        if let Some(e) = create_confidence_arg(
-            row.get(11).unwrap(),
-            row.get(12).unwrap(),
-            row.get(13).unwrap(),
-            row.get(14).unwrap(),
-        ) {
-            datapoint
-                .add_confidence(Confidence::Ten, e)
-                .expect("the type of the values should be the same");
-        }
-
-        // x 25 - 75
-        if let Some(e) = create_confidence_arg(
-            row.get(15).unwrap(),
-            row.get(16).unwrap(),
-            row.get(17).unwrap(),
-            row.get(18).unwrap(),
-        ) {
-            datapoint
-                .add_confidence(Confidence::TwentyFive, e)
-                .expect("the type of the values should be the same");
-        }
-
-        Ok(if let Some(tag) = row.get(19).unwrap() {
+        Ok(if let Some(tag) = row.get(3).unwrap() {
             datapoint.tag(tag)
         } else {
             datapoint
@@ -974,108 +1420,12 @@ impl TryFrom<&rusqlite::Row<'_>> for LinearDatapoint {
 impl TryFrom<&rusqlite::Row<'_>> for XYDatapoint {
     type Error = BencherError;
     fn try_from(row: &rusqlite::Row) -> BencherResult<Self> {
-        let mut datapoint = XYDatapoint::new(
+        let datapoint = XYDatapoint::new(
             Value::new(row.get(0).unwrap(), row.get(1).unwrap())?,
             Value::new(row.get(2).unwrap(), row.get(3).unwrap())?,
         );
 
-        // x 1 - 99
-        if let Some(e) = create_confidence_arg(
-            row.get(4).unwrap(),
-            row.get(5).unwrap(),
-            row.get(6).unwrap(),
-            row.get(7).unwrap(),
-        ) {
-            datapoint
-                .add_x_confidence(Confidence::One, e)
-                .expect("the type of the values should be the same");
-        }
-
-        // x 5 - 95
-        if let Some(e) = create_confidence_arg(
-            row.get(8).unwrap(),
-            row.get(9).unwrap(),
-            row.get(10).unwrap(),
-            row.get(11).unwrap(),
-        ) {
-            datapoint
-                .add_x_confidence(Confidence::Five, e)
-                .expect("the type of the values should be the same");
-        }
-
-        // x 10 - 90
-        if let Some(e) = create_confidence_arg(
-            row.get(12).unwrap(),
-            row.get(13).unwrap(),
-            row.get(14).unwrap(),
-            row.get(15).unwrap(),
-        ) {
-            datapoint
-                .add_x_confidence(Confidence::Ten, e)
-                .expect("the type of the values should be the same");
-        }
-
-        // x 25 - 75
-        if let Some(e) = create_confidence_arg(
-            row.get(16).unwrap(),
-            row.get(17).unwrap(),
-            row.get(18).unwrap(),
-            row.get(19).unwrap(),
-        ) {
-            datapoint
-                .add_x_confidence(Confidence::TwentyFive, e)
-                .expect("the type of the values should be the same");
-        }
-
-        // y 1 - 99
-        if let Some(e) = create_confidence_arg(
-            row.get(20).unwrap(),
-            row.get(21).unwrap(),
-            row.get(22).unwrap(),
-            row.get(23).unwrap(),
-        ) {
-            datapoint
-                .add_y_confidence(Confidence::One, e)
-                .expect("the type of the values should be the same");
-        }
-
-        // y 5 - 95
-        if let Some(e) = create_confidence_arg(
-            row.get(24).unwrap(),
-            row.get(25).unwrap(),
-            row.get(26).unwrap(),
-            row.get(27).unwrap(),
-        ) {
-            datapoint
-                .add_y_confidence(Confidence::Five, e)
-                .expect("the type of the values should be the same");
-        }
-
-        // y 10 - 90
-        if let Some(e) = create_confidence_arg(
-            row.get(28).unwrap(),
-            row.get(29).unwrap(),
-            row.get(30).unwrap(),
-            row.get(31).unwrap(),
-        ) {
-            datapoint
-                .add_y_confidence(Confidence::Ten, e)
-                .expect("the type of the values should be the same");
-        }
-
-        // y 25 - 75
-        if let Some(e) = create_confidence_arg(
-            row.get(32).unwrap(),
-            row.get(33).unwrap(),
-            row.get(34).unwrap(),
-            row.get(35).unwrap(),
-        ) {
-            datapoint
-                .add_y_confidence(Confidence::TwentyFive, e)
-                .expect("the type of the values should be the same");
-        }
-
-        Ok(if let Some(tag) = row.get(36).unwrap() {
+        Ok(if let Some(tag) = row.get(4).unwrap() {
             datapoint.tag(tag)
         } else {
             datapoint
@@ -1106,23 +1456,36 @@ fn open_db(db_path: &Path, write: bool) -> BencherResult<rusqlite::Connection> {
 
     let conn = rusqlite::Connection::open_with_flags(db_path, flags)
         .map_err(|e| BencherError::Database(e))?;
-    setup_db(&conn)?;
+    if write {
+        setup_db(&conn)?;
+    }
     Ok(conn)
 }
 
+fn column_exists(db: &rusqlite::Connection, table: &str, column: &str) -> BencherResult<bool> {
+    Ok(db
+        .query_row(
+            "select name from pragma_table_info(:table) where name=:column",
+            rusqlite::named_params! { ":table": table, ":column": column },
+            |_| Ok(()),
+        )
+        .optional()?
+        .is_some())
+}
+
+fn table_exists(db: &rusqlite::Connection, name: &str) -> BencherResult<bool> {
+    Ok(db
+        .query_row(
+            "select name from sqlite_schema where type=:type and name=:name",
+            rusqlite::named_params! { ":type": "table", ":name": name },
+            |_| Ok(()),
+        )
+        .optional()?
+        .is_some())
+}
+
 /// Check
 fn check_compatible_db(db: &rusqlite::Connection) -> BencherResult<()> {
-    fn table_exists(db: &rusqlite::Connection, name: &str) -> BencherResult<bool> {
-        Ok(db
-            .query_row(
-                "select name from sqlite_schema where type=:type and name=:name",
-                rusqlite::named_params! { ":type": "table", ":name": name },
-                |_| Ok(()),
-            )
-            .optional()?
-            .is_some())
-    }
-
     if !table_exists(db, "experiments")? {
         return Err(BencherError::SchemaMissingTable(
             "experiments".to_string(),
@@ -1144,10 +1507,82 @@ fn check_compatible_db(db: &rusqlite::Connection) -> BencherResult<()> {
         ));
     }
 
+    let version = schema_version(db)?;
+    if version < SCHEMA_VERSION {
+        return Err(BencherError::OutdatedSchema(
+            db.path().unwrap_or("<unknown db name>").to_owned(),
+            version,
+        ));
+    } else if version > SCHEMA_VERSION {
+        return Err(BencherError::UnsupportedSchema(
+            db.path().unwrap_or("<unknown db name>").to_owned(),
+            version,
+            SCHEMA_VERSION,
+        ));
+    }
+
     Ok(())
 }
 
+/// Version of the schema, as created by this version of bencher
+///
+/// It is stored in the database header (as the `user_version` pragma);
+/// databases created before the schema was versioned have version 0
+pub(crate) const SCHEMA_VERSION: i64 = 2;
+
+/// Migrations between schema versions, in order
+///
+/// The migration at index `i` upgrades a database from version `i` to version `i + 1`.
+/// Migrations which rebuild tables do so with the current schema,
+/// so later migrations must not assume their changes are missing
+const MIGRATIONS: [fn(&rusqlite::Connection) -> BencherResult<()>; SCHEMA_VERSION as usize] =
+    [migrate_unversioned_schema, migrate_run_metadata];
+
+fn schema_version(db: &rusqlite::Connection) -> BencherResult<i64> {
+    db.pragma_query_value(None, "user_version", |row| row.get(0))
+        .map_err(|e| e.into())
+}
+
+/// Get the schema version of a database file, without modifying it
+pub(crate) fn db_schema_version(db_path: &Path) -> BencherResult<i64> {
+    schema_version(&open_db(db_path, false /* write */)?)
+}
+
+/// Create the schema of a new database, or upgrade the schema of an existing one
 fn setup_db(db: &rusqlite::Connection) -> BencherResult<()> {
+    let version = schema_version(db)?;
+    if version > SCHEMA_VERSION {
+        return Err(BencherError::UnsupportedSchema(
+            db.path().unwrap_or("<unknown db name>").to_owned(),
+            version,
+            SCHEMA_VERSION,
+        ));
+    }
+
+    if version < SCHEMA_VERSION {
+        // foreign keys cannot be toggled inside a transaction,
+        // and tables are rebuilt while migrating
+        db.pragma_update(None, "foreign_keys", false)?;
+
+        let transaction = db.unchecked_transaction()?;
+        if table_exists(&transaction, "experiments")? {
+            for migration in &MIGRATIONS[version as usize..] {
+                migration(&transaction)?;
+            }
+        } else {
+            create_schema(&transaction)?;
+        }
+        check_foreign_keys(&transaction)?;
+        transaction.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+        transaction.commit()?;
+    }
+
+    db.pragma_update(None, "foreign_keys", true)?;
+    Ok(())
+}
+
+/// Create the current schema
+fn create_schema(db: &rusqlite::Connection) -> BencherResult<()> {
     db.execute(
         "create table if not exists experiments (
             experiment_code text not null primary key,
@@ -1159,51 +1594,19 @@ fn setup_db(db: &rusqlite::Connection) -> BencherResult<()> {
 
     db.execute(
         "create table if not exists xy_results (
-            experiment_code text not null,
+            experiment_code text not null references experiments (experiment_code),
             tag int not null,
             version int not null,
 
             x_int int,
-            x_int_1 int,
-            x_int_5 int,
-            x_int_10 int,
-            x_int_25 int,
-            x_int_99 int,
-            x_int_95 int,
-            x_int_90 int,
-            x_int_75 int,
-
             y_int int,
-            y_int_1 int,
-            y_int_5 int,
-            y_int_10 int,
-            y_int_25 int,
-            y_int_99 int,
-            y_int_95 int,
-            y_int_90 int,
-            y_int_75 int,
-
             x_float float,
-            x_float_1 float,
-            x_float_5 float,
-            x_float_10 float,
-            x_float_25 float,
-            x_float_99 float,
-            x_float_95 float,
-            x_float_90 float,
-            x_float_75 float,
-
             y_float float,
-            y_float_1 float,
-            y_float_5 float,
-            y_float_10 float,
-            y_float_25 float,
-            y_float_99 float,
-            y_float_95 float,
-            y_float_90 float,
-            y_float_75 float,
-
-            foreing key experiment_code references experiments,
+
+            timestamp int,
+            git_revision text,
+            hostname text,
+
             primary key (experiment_code, tag, version)
         )",
         [],
@@ -1211,35 +1614,454 @@ fn setup_db(db: &rusqlite::Connection) -> BencherResult<()> {
 
     db.execute(
         "create table if not exists linear_results (
-            experiment_code text not null,
+            experiment_code text not null references experiments (experiment_code),
             v_group text not null,
             tag int not null,
             version int not null,
 
             v_int int,
-            v_int_1 int,
-            v_int_5 int,
-            v_int_10 int,
-            v_int_25 int,
-            v_int_99 int,
-            v_int_95 int,
-            v_int_90 int,
-            v_int_75 int,
-
             v_float float,
-            v_float_1 float,
-            v_float_5 float,
-            v_float_10 float,
-            v_float_25 float,
-            v_float_99 float,
-            v_float_95 float,
-            v_float_90 float,
-            v_float_75 float,
-
-            foreing key experiment_code references experiments,
+
+            timestamp int,
+            git_revision text,
+            hostname text,
+
             primary key (experiment_code, v_group, version)
         )",
         [],
     )?;
+
+    db.execute(
+        "create table if not exists xy_percentiles (
+            experiment_code text not null references experiments (experiment_code),
+            tag int not null,
+            version int not null,
+            axis text not null,
+            percentile float not null,
+
+            v_int int,
+            v_float float,
+
+            primary key (experiment_code, tag, version, axis, percentile)
+        )",
+        [],
+    )?;
+
+    db.execute(
+        "create table if not exists linear_percentiles (
+            experiment_code text not null references experiments (experiment_code),
+            v_group text not null,
+            version int not null,
+            percentile float not null,
+
+            v_int int,
+            v_float float,
+
+            primary key (experiment_code, v_group, version, percentile)
+        )",
+        [],
+    )?;
+
+    db.execute(
+        "create table if not exists linear_samples (
+            experiment_code text not null references experiments (experiment_code),
+            v_group text not null,
+            version int not null,
+
+            v_int int,
+            v_float float
+        )",
+        [],
+    )?;
+
+    db.execute(
+        "create index if not exists linear_samples_idx on linear_samples (experiment_code, v_group, version)",
+        [],
+    )?;
+
+    db.execute(
+        "create table if not exists xy_samples (
+            experiment_code text not null references experiments (experiment_code),
+            tag int not null,
+            version int not null,
+            axis text not null,
+
+            v_int int,
+            v_float float
+        )",
+        [],
+    )?;
+
+    db.execute(
+        "create index if not exists xy_samples_idx on xy_samples (experiment_code, tag, version)",
+        [],
+    )?;
+
+    db.execute(
+        "create table if not exists linear_metadata (
+            experiment_code text not null references experiments (experiment_code),
+            v_group text not null,
+            version int not null,
+            key text not null,
+            value text not null,
+
+            primary key (experiment_code, v_group, version, key)
+        )",
+        [],
+    )?;
+
+    db.execute(
+        "create table if not exists xy_metadata (
+            experiment_code text not null references experiments (experiment_code),
+            tag int not null,
+            version int not null,
+            key text not null,
+            value text not null,
+
+            primary key (experiment_code, tag, version, key)
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+/// Fail if any row references an experiment which does not exist
+fn check_foreign_keys(db: &rusqlite::Connection) -> BencherResult<()> {
+    let violation: Option<String> = db
+        .query_row("pragma foreign_key_check", [], |row| row.get(0))
+        .optional()?;
+
+    match violation {
+        Some(table) => Err(BencherError::ForeignKeyViolation(
+            db.path().unwrap_or("<unknown db name>").to_owned(),
+            table,
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Tables holding results, with the columns kept when migrating them from an unversioned database
+const UNVERSIONED_TABLES: [(&str, &str); 6] = [
+    (
+        "linear_results",
+        "experiment_code, v_group, tag, version, v_int, v_float",
+    ),
+    (
+        "xy_results",
+        "experiment_code, tag, version, x_int, y_int, x_float, y_float",
+    ),
+    (
+        "linear_percentiles",
+        "experiment_code, v_group, version, percentile, v_int, v_float",
+    ),
+    (
+        "xy_percentiles",
+        "experiment_code, tag, version, axis, percentile, v_int, v_float",
+    ),
+    (
+        "linear_samples",
+        "experiment_code, v_group, version, v_int, v_float",
+    ),
+    (
+        "xy_samples",
+        "experiment_code, tag, version, axis, v_int, v_float",
+    ),
+];
+
+/// Percentiles which unversioned databases stored as columns of the result tables
+const CONFIDENCE_COLUMNS: [(f64, &str); 8] = [
+    (1.0, "1"),
+    (99.0, "99"),
+    (5.0, "5"),
+    (95.0, "95"),
+    (10.0, "10"),
+    (90.0, "90"),
+    (25.0, "25"),
+    (75.0, "75"),
+];
+
+/// Migrate a database created before the schema was versioned (version 0 to 1)
+///
+/// These databases declared their foreign keys as a column (misspelled `foreing`), so the
+/// result tables are rebuilt with proper foreign keys.
+/// Confidence intervals which were stored in the `*_1` to `*_99` columns of the result tables
+/// are moved into the percentile tables
+fn migrate_unversioned_schema(db: &rusqlite::Connection) -> BencherResult<()> {
+    db.execute("drop index if exists linear_samples_idx", [])?;
+    db.execute("drop index if exists xy_samples_idx", [])?;
+
+    let mut legacy_tables = vec![];
+    for (table, columns) in UNVERSIONED_TABLES {
+        if table_exists(db, table)? {
+            db.execute(&format!("alter table {0} rename to legacy_{0}", table), [])?;
+            legacy_tables.push((table, columns));
+        }
+    }
+
+    create_schema(db)?;
+
+    for (table, columns) in &legacy_tables {
+        db.execute(
+            &format!(
+                "insert into {0} ({1}) select {1} from legacy_{0}",
+                table, columns
+            ),
+            [],
+        )?;
+    }
+
+    if column_exists(db, "legacy_linear_results", "v_int_1")? {
+        for (percentile, column) in CONFIDENCE_COLUMNS {
+            db.execute(
+                &format!(
+                    "insert or ignore into linear_percentiles
+                     select experiment_code, v_group, abs(version), :percentile, v_int_{0}, v_float_{0}
+                     from legacy_linear_results
+                     where v_int_{0} is not null or v_float_{0} is not null",
+                    column
+                ),
+                rusqlite::named_params! { ":percentile": percentile },
+            )?;
+        }
+    }
+
+    if column_exists(db, "legacy_xy_results", "x_int_1")? {
+        for axis in ["x", "y"] {
+            for (percentile, column) in CONFIDENCE_COLUMNS {
+                db.execute(
+                    &format!(
+                        "insert or ignore into xy_percentiles
+                         select experiment_code, tag, abs(version), :axis, :percentile, {0}_int_{1}, {0}_float_{1}
+                         from legacy_xy_results
+                         where {0}_int_{1} is not null or {0}_float_{1} is not null",
+                        axis, column
+                    ),
+                    rusqlite::named_params! { ":axis": axis, ":percentile": percentile },
+                )?;
+            }
+        }
+    }
+
+    for (table, _) in legacy_tables {
+        db.execute(&format!("drop table legacy_{}", table), [])?;
+    }
+
     Ok(())
 }
+
+/// Columns with the run metadata of each result (added in version 2)
+const RUN_METADATA_COLUMNS: [(&str, &str); 3] = [
+    ("timestamp", "int"),
+    ("git_revision", "text"),
+    ("hostname", "text"),
+];
+
+/// Record the run metadata of each datapoint version (version 1 to 2)
+///
+/// Results added before this migration have no run metadata
+fn migrate_run_metadata(db: &rusqlite::Connection) -> BencherResult<()> {
+    for table in ["linear_results", "xy_results"] {
+        for (column, column_type) in RUN_METADATA_COLUMNS {
+            if !column_exists(db, table, column)? {
+                db.execute(
+                    &format!(
+                        "alter table {} add column {} {}",
+                        table, column, column_type
+                    ),
+                    [],
+                )?;
+            }
+        }
+    }
+
+    create_schema(db)
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::*;
+
+    /// Path of a temporary database (named after the test), removing any leftover copy
+    pub(crate) fn temp_db_path(name: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("bencher-{}-{}.db", std::process::id(), name));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    /// Columns of a value in the unversioned schema, with its confidence columns
+    fn unversioned_value_columns(prefix: &str, sql_type: &str) -> String {
+        std::iter::once(format!("{} {}", prefix, sql_type))
+            .chain(
+                CONFIDENCE_COLUMNS
+                    .iter()
+                    .map(|(_, column)| format!("{}_{} {}", prefix, column, sql_type)),
+            )
+            .collect::<Vec<_>>()
+            .join(",\n")
+    }
+
+    /// Create a database with the schema used before it was versioned
+    ///
+    /// It holds a linear experiment `lat` (group `get` in two versions, the first reverted)
+    /// and a bidimensional experiment `tx` (one point), both with confidence columns
+    pub(crate) fn unversioned_db(path: &Path) {
+        let db = rusqlite::Connection::open(path).unwrap();
+        db.execute(
+            "create table experiments (
+                experiment_code text not null primary key,
+                experiment_type text not null,
+                experiment_label text not null
+            )",
+            [],
+        )
+        .unwrap();
+        db.execute(
+            &format!(
+                "create table xy_results (
+                    experiment_code text not null,
+                    tag int not null,
+                    version int not null,
+                    {}, {}, {}, {},
+                    foreing key experiment_code references experiments,
+                    primary key (experiment_code, tag, version)
+                )",
+                unversioned_value_columns("x_int", "int"),
+                unversioned_value_columns("y_int", "int"),
+                unversioned_value_columns("x_float", "float"),
+                unversioned_value_columns("y_float", "float"),
+            ),
+            [],
+        )
+        .unwrap();
+        db.execute(
+            &format!(
+                "create table linear_results (
+                    experiment_code text not null,
+                    v_group text not null,
+                    tag int not null,
+                    version int not null,
+                    {}, {},
+                    foreing key experiment_code references experiments,
+                    primary key (experiment_code, v_group, version)
+                )",
+                unversioned_value_columns("v_int", "int"),
+                unversioned_value_columns("v_float", "float"),
+            ),
+            [],
+        )
+        .unwrap();
+
+        db.execute_batch(
+            "insert into experiments values ('lat', 'linear', 'Latency');
+             insert into experiments values ('tx', 'xy', 'Throughput');
+             insert into linear_results (experiment_code, v_group, tag, version, v_int, v_int_1, v_int_99)
+                 values ('lat', 'get', 0, -1, 12, 6, 18);
+             insert into linear_results (experiment_code, v_group, tag, version, v_int, v_int_5, v_int_95)
+                 values ('lat', 'get', 0, 2, 10, 8, 13);
+             insert into xy_results (experiment_code, tag, version, x_int, y_float, y_float_25, y_float_75)
+                 values ('tx', 0, 1, 4, 100.5, 90.0, 110.0);",
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn migrate_unversioned_db() {
+        let path = temp_db_path("migrate-unversioned");
+        unversioned_db(&path);
+        assert_eq!(db_schema_version(&path).unwrap(), 0);
+
+        let db = open_db(&path, true /* write */).unwrap();
+        assert_eq!(schema_version(&db).unwrap(), SCHEMA_VERSION);
+        check_compatible_db(&db).unwrap();
+
+        let linear: Vec<(String, i64, i64)> = db
+            .prepare("select v_group, version, v_int from linear_results order by version")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(
+            linear,
+            vec![("get".to_string(), -1, 12), ("get".to_string(), 2, 10)]
+        );
+
+        // the confidence columns moved to the percentile table, indexed by the absolute version
+        let percentiles: Vec<(i64, f64, i64)> = db
+            .prepare(
+                "select version, percentile, v_int from linear_percentiles order by version, percentile",
+            )
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(
+            percentiles,
+            vec![(1, 1.0, 6), (1, 99.0, 18), (2, 5.0, 8), (2, 95.0, 13)]
+        );
+
+        let xy_percentiles: Vec<(String, f64, f64)> = db
+            .prepare("select axis, percentile, v_float from xy_percentiles order by percentile")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(
+            xy_percentiles,
+            vec![
+                ("y".to_string(), 25.0, 90.0),
+                ("y".to_string(), 75.0, 110.0)
+            ]
+        );
+
+        // results from before the run metadata was recorded have none
+        for (column, _) in RUN_METADATA_COLUMNS {
+            assert!(column_exists(&db, "linear_results", column).unwrap());
+            assert!(column_exists(&db, "xy_results", column).unwrap());
+        }
+        let timestamp: Option<i64> = db
+            .query_row("select timestamp from xy_results", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(timestamp, None);
+
+        drop(db);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn migrated_db_is_stable() {
+        let path = temp_db_path("migrate-twice");
+        unversioned_db(&path);
+        drop(open_db(&path, true /* write */).unwrap());
+
+        // setting up an up to date database leaves it untouched
+        let db = open_db(&path, true /* write */).unwrap();
+        assert_eq!(schema_version(&db).unwrap(), SCHEMA_VERSION);
+        let n_results: i64 = db
+            .query_row("select count(*) from linear_results", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(n_results, 2);
+
+        drop(db);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn newer_schema_is_rejected() {
+        let path = temp_db_path("migrate-newer");
+        unversioned_db(&path);
+        drop(open_db(&path, true /* write */).unwrap());
+        rusqlite::Connection::open(&path)
+            .unwrap()
+            .pragma_update(None, "user_version", SCHEMA_VERSION + 1)
+            .unwrap();
+
+        assert!(matches!(
+            open_db(&path, true /* write */),
+            Err(BencherError::UnsupportedSchema(_, version, SCHEMA_VERSION)) if version == SCHEMA_VERSION + 1
+        ));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -16,6 +16,9 @@ pub enum BencherError {
     #[error("Cannot convert a {0:?} into a bencher value")]
     ExpressionConversionError(evalexpr::Value),
 
+    #[error("Cannot parse `{0}` as a bencher value")]
+    InvalidValue(String),
+
     #[error("SQLite error")]
     Database(#[from] rusqlite::Error),
 
@@ -29,8 +32,10 @@ pub enum BencherError {
     #[error("Unknown experiment type: {0}")]
     UnknownExperimentType(String),
 
-    #[error("Invalid confidence level: {0}")]
-    InvalidConfidence(usize),
+    #[error(
+        "Invalid confidence level: {0} (must be a percentile between 0 and 100, other than 50)"
+    )]
+    InvalidConfidence(f64),
 
     #[error("Point type and error bar type do not match")]
     MismatchedBarTypes,
@@ -61,12 +66,86 @@ pub enum BencherError {
     #[error("No sets found for experiment type {0}")]
     NoSets(String),
 
+    #[error("No versions found for {1} in experiment {0}")]
+    NoVersions(String, String),
+
+    #[error("Experiment code `{0}` not found")]
+    UnknownCode(String),
+
     #[error("Experiment `{0}` not found. Available experiments: {1}")]
     ExperimentNotFound(String, String),
 
+    #[error("Virtual experiment `{0}` has no source experiments")]
+    NoSourceExperiments(String),
+
+    #[error("Baseline `{0}` of virtual experiment `{1}` not found")]
+    BaselineNotFound(String, String),
+
+    #[error("Cannot map the confidence intervals through `{0}`: {1} (consider declaring the monotonicity of the operation)")]
+    UnboundedExpression(String, String),
+
     #[error("Deserialization Error")]
     Serde(#[from] serde_json::Error),
 
+    #[error("TOML deserialization error")]
+    TomlDeserialize(#[from] toml::de::Error),
+
+    #[error("TOML serialization error")]
+    TomlSerialize(#[from] toml::ser::Error),
+
+    #[error("YAML error")]
+    Yaml(#[from] serde_yaml::Error),
+
+    #[error("Unknown config format: {0}. Available formats: json, toml, yaml")]
+    UnknownConfigFormat(String),
+
+    #[error("Experiment `{0}` is defined differently in {1:?} and {2:?}")]
+    ConflictingExperiment(String, std::path::PathBuf, std::path::PathBuf),
+
+    #[error("Configs include each other: {0:?}")]
+    IncludeCycle(Vec<std::path::PathBuf>),
+
+    #[error("No default database set by {0:?} (or by the configs merged into it)")]
+    NoDefaultDatabase(std::path::PathBuf),
+
+    #[error("Multiple configs in {0:?}: {1:?} (keep only one of them)")]
+    MultipleConfigs(std::path::PathBuf, Vec<String>),
+
+    #[error("CSV Error")]
+    Csv(#[from] csv::Error),
+
+    #[error("Unknown sample file format: {0}. Available formats: csv, jsonl")]
+    UnknownSampleFormat(String),
+
+    #[error("Unknown image format: {0}. Available formats: svg, png")]
+    UnknownImageFormat(String),
+
+    #[error("Unknown export format: {0}. Available formats: json, csv")]
+    UnknownExportFormat(String),
+
+    #[error("Render error: {0}")]
+    RenderError(String),
+
+    #[error("Unknown magnitude: {0}. Available magnitudes: pico (p), nano (n), micro (u), milli (m), normal, kilo (K), mega (M), giga (G), tera (T), peta (P)")]
+    UnknownMagnitude(String),
+
+    #[error("Unknown aggregation: {0}. Available aggregations: median, avg")]
+    UnknownAggregation(String),
+
+    #[error("Column `{0}` not found in {1}")]
+    MissingColumn(String, String),
+
+    #[error("Invalid value `{value}` in column `{column}` of row {row} of {path}: values must be finite numbers")]
+    InvalidSampleValue {
+        path: String,
+        row: usize,
+        column: String,
+        value: String,
+    },
+
+    #[error("Invalid tag `{0}`: tags must be integers")]
+    InvalidTag(String),
+
     #[error("Incompatible databases for view: {db} has codes which are already in other databases: {codes:?}")]
     IncompatibleDbs {
         db: std::path::PathBuf,
@@ -79,8 +158,81 @@ pub enum BencherError {
     #[error("Schema error: missing table {0} in db {1}")]
     SchemaMissingTable(String, String),
 
+    #[error("Schema error: db {0} uses an outdated schema (version {1}), run `bencher migrate` to upgrade it")]
+    OutdatedSchema(String, i64),
+
+    #[error("Schema error: db {0} uses schema version {1}, but this version of bencher only supports up to version {2}")]
+    UnsupportedSchema(String, i64, i64),
+
+    #[error("Schema error: db {0} has rows in table {1} which reference unknown experiments")]
+    ForeignKeyViolation(String, String),
+
     #[error("Failed to create path from prefix {}: cannot add extension {}", .0.to_string_lossy(), .1)]
     PathCreateError(std::path::PathBuf, String),
+
+    #[error("Config already exists at {}", .0.to_string_lossy())]
+    ConfigExists(std::path::PathBuf),
+
+    #[error(
+        "Unknown monotonicity `{0}` (expected increasing, decreasing, monotonic or non_monotonic)"
+    )]
+    UnknownMonotonicity(String),
+
+    #[error(
+        "Unknown histogram style `{0}` (expected clustered, row_stacked, column_stacked or percent_stacked)"
+    )]
+    UnknownHistogramStyle(String),
+
+    #[error("Invalid axis: {0}")]
+    InvalidAxis(String),
+
+    #[error("Unknown terminal `{0}` (expected eps, epslatex, pdfcairo, pngcairo or svg)")]
+    UnknownTerminal(String),
+
+    #[error(
+        "Unknown legend position `{0}` (expected outside_above, outside_below, outside_right, inside_top_left, inside_top_right, inside_bottom_left, inside_bottom_right or hidden)"
+    )]
+    UnknownLegendPosition(String),
+
+    #[error("Invalid plot style: {0}")]
+    InvalidStyle(String),
+
+    #[error("Cannot have error bars in {0} histograms")]
+    StackedErrorBars(crate::HistogramStyle),
+
+    #[error("Invalid config:{}", .0.iter().map(|p| format!("\n  - {}", p)).collect::<String>())]
+    InvalidConfig(Vec<ConfigProblem>),
+}
+
+/// A problem with the experiment definitions of a config
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ConfigProblem {
+    #[error("experiment type `{0}` is defined {1} times")]
+    DuplicateExperiment(String, usize),
+
+    #[error("virtual experiment `{0}` has no source experiments")]
+    NoSources(String),
+
+    #[error("source `{1}` of virtual experiment `{0}` is not defined")]
+    DanglingSource(String, String),
+
+    #[error("source `{1}` of virtual experiment `{0}` is not a {2} experiment")]
+    MismatchedSource(String, String, String),
+
+    #[error("virtual experiments depend on each other: {}", .0.join(" -> "))]
+    Cycle(Vec<String>),
+
+    #[error("{1} of virtual experiment `{0}` does not parse: {2}")]
+    InvalidExpression(String, String, String),
+
+    #[error("{1} of experiment `{0}` sets both the precision and the significant figures")]
+    ConflictingFormat(String, String),
+
+    #[error("{1} of experiment `{0}` is invalid: {2}")]
+    InvalidAxis(String, String, String),
+
+    #[error("the plot style is invalid: {0}")]
+    InvalidStyle(String),
 }
 
 impl BencherError {
@@ -1,3 +1,4 @@
+use std::collections::{BTreeMap, BTreeSet};
 use std::io::Write;
 
 use crate::*;
@@ -5,10 +6,10 @@ use crate::*;
 #[derive(Debug, Copy, Clone)]
 pub enum Bars {
     None,
-    Linear(usize),
-    X(usize),
-    Y(usize),
-    XY(usize, usize),
+    Linear(Confidence),
+    X(Confidence),
+    Y(Confidence),
+    XY(Confidence, Confidence),
 }
 
 impl Default for Bars {
@@ -22,9 +23,9 @@ impl Bars {
     /// Will error on incompatible options
     ///
     pub fn from_optionals(
-        bar: Option<usize>,
-        xbar: Option<usize>,
-        ybar: Option<usize>,
+        bar: Option<Confidence>,
+        xbar: Option<Confidence>,
+        ybar: Option<Confidence>,
     ) -> BencherResult<Self> {
         match (bar, xbar, ybar) {
             (None, None, None) => Ok(Bars::None),
@@ -45,16 +46,34 @@ impl Bars {
     pub fn from_bools(bar: bool, xbar: bool, ybar: bool) -> BencherResult<Self> {
         match (bar, xbar, ybar) {
             (false, false, false) => Ok(Bars::None),
-            (true, false, false) => Ok(Bars::Linear(5)),
-            (false, true, false) => Ok(Bars::X(5)),
-            (false, false, true) => Ok(Bars::Y(5)),
-            (false, true, true) => Ok(Bars::XY(5, 5)),
+            (true, false, false) => Ok(Bars::Linear(Confidence::FIVE)),
+            (false, true, false) => Ok(Bars::X(Confidence::FIVE)),
+            (false, false, true) => Ok(Bars::Y(Confidence::FIVE)),
+            (false, true, true) => Ok(Bars::XY(Confidence::FIVE, Confidence::FIVE)),
             (true, true, _) => Err(BencherError::IncompatibleBarTypes),
             (true, _, true) => Err(BencherError::IncompatibleBarTypes),
         }
     }
 }
 
+/// Machine-readable formats an experiment view can be exported to
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ExportFormat {
+    Json,
+    Csv,
+}
+
+impl std::str::FromStr for ExportFormat {
+    type Err = BencherError;
+    fn from_str(s: &str) -> BencherResult<ExportFormat> {
+        match s.to_lowercase().as_str() {
+            "json" => Ok(ExportFormat::Json),
+            "csv" => Ok(ExportFormat::Csv),
+            _ => Err(BencherError::UnknownExportFormat(s.to_string())),
+        }
+    }
+}
+
 /// This trait represents an experiment that can be plotted, etc.
 ///
 /// Represents a group of values/lines
@@ -67,6 +86,89 @@ pub trait ExperimentView {
         self.dat(prefix, bar)
     }
 
+    /// Render the plot directly as an image (`prefix`.svg or `prefix`.png), without gnuplot
+    fn image(&self, prefix: &std::path::Path, format: ImageFormat, bar: Bars) -> BencherResult<()>;
+
     fn table<W: Write>(&self, writer: &mut W) -> BencherResult<()>;
     fn latex_table<W: Write>(&self, writer: &mut W) -> BencherResult<()>;
+
+    /// Write every datapoint, unscaled (in its units) and with all its confidences, as a JSON document
+    ///
+    /// The magnitude and units the values are displayed in (by the tables and plots) are given
+    /// alongside, as `display_magnitude` and `display_units`
+    fn json<W: Write>(&self, writer: &mut W) -> BencherResult<()>;
+
+    /// Write every datapoint, unscaled (in its units) and with all its confidences,
+    /// as CSV (one row per datapoint)
+    ///
+    /// Each row also holds the magnitude and units the values are displayed in
+    /// (`display_magnitude` and `display_units`)
+    fn csv<W: Write>(&self, writer: &mut W) -> BencherResult<()>;
+
+    fn export<W: Write>(&self, writer: &mut W, format: ExportFormat) -> BencherResult<()> {
+        match format {
+            ExportFormat::Json => self.json(writer),
+            ExportFormat::Csv => self.csv(writer),
+        }
+    }
+}
+
+/// JSON number of a value
+pub(crate) fn json_value(v: &Value) -> serde_json::Value {
+    match v {
+        Value::Int(i) => serde_json::json!(i),
+        Value::Float(f) => serde_json::json!(f),
+    }
+}
+
+/// JSON list of confidence intervals, as lower/upper percentiles and min/max values
+pub(crate) fn json_confidences(
+    confidences: &BTreeMap<Confidence, (Value, Value)>,
+) -> serde_json::Value {
+    confidences
+        .iter()
+        .map(|(confidence, (min, max))| {
+            serde_json::json!({
+                "lower": confidence.lower(),
+                "upper": confidence.upper(),
+                "min": json_value(min),
+                "max": json_value(max),
+            })
+        })
+        .collect()
+}
+
+/// CSV column names of a set of confidences (a min and a max column for each of them)
+pub(crate) fn csv_confidence_header(
+    confidences: &BTreeSet<Confidence>,
+    prefix: &str,
+) -> Vec<String> {
+    confidences
+        .iter()
+        .flat_map(|c| {
+            [
+                format!("{}{} min", prefix, c),
+                format!("{}{} max", prefix, c),
+            ]
+        })
+        .collect()
+}
+
+/// CSV cells of the confidences of a datapoint, empty for the ones it does not have
+pub(crate) fn csv_confidence_cells(
+    confidences: &BTreeMap<Confidence, (Value, Value)>,
+    columns: &BTreeSet<Confidence>,
+) -> Vec<String> {
+    columns
+        .iter()
+        .flat_map(|c| match confidences.get(c) {
+            Some((min, max)) => [min.to_string(), max.to_string()],
+            None => [String::new(), String::new()],
+        })
+        .collect()
+}
+
+pub(crate) fn write_json<W: Write>(writer: &mut W, json: &serde_json::Value) -> BencherResult<()> {
+    serde_json::to_writer_pretty(&mut *writer, json)?;
+    writeln!(writer).map_err(|e| BencherError::io_err(e, "writing json"))
 }
@@ -4,6 +4,7 @@ use crate::*;
 pub struct LinearSetHandle<'a> {
     db: &'a DbWriteBackend,
     exp_code: String,
+    metadata: RunMetadata,
 }
 
 impl<'a> LinearSetHandle<'a> {
@@ -11,9 +12,18 @@ impl<'a> LinearSetHandle<'a> {
         LinearSetHandle {
             db,
             exp_code: exp_code.to_string(),
+            metadata: RunMetadata::default(),
         }
     }
 
+    /// Record the given run metadata with every datapoint added through this handle
+    ///
+    /// Datapoints are timestamped with the time they are added, unless the metadata has a timestamp
+    pub fn with_metadata(mut self, metadata: RunMetadata) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
     /// Tag untagged datapoint (with the next point in set)
     fn tag_datapoint(&self, datapoint: LinearDatapoint) -> BencherResult<LinearDatapoint> {
         if let None = &datapoint.tag {
@@ -27,7 +37,40 @@ impl<'a> LinearSetHandle<'a> {
 
     pub fn add_datapoint(&self, datapoint: LinearDatapoint) -> BencherResult<()> {
         let datapoint = self.tag_datapoint(datapoint)?;
-        self.db.add_linear_datapoint(&self.exp_code, datapoint)
+        self.db
+            .add_linear_datapoint(&self.exp_code, datapoint, &self.metadata)?;
+        Ok(())
+    }
+
+    /// Add a datapoint along with the raw sample it was aggregated from
+    pub fn add_datapoint_with_sample(
+        &self,
+        datapoint: LinearDatapoint,
+        sample: &Sample,
+    ) -> BencherResult<()> {
+        let datapoint = self.tag_datapoint(datapoint)?;
+        let group = datapoint.group.clone();
+        let version = self
+            .db
+            .add_linear_datapoint(&self.exp_code, datapoint, &self.metadata)?;
+        self.db
+            .add_linear_sample(&self.exp_code, &group, version, sample)
+    }
+
+    /// Aggregate a raw datapoint and add it, keeping the raw sample
+    ///
+    /// The confidence intervals at the given percentiles are computed in addition to the default ones.
+    /// Empty samples are ignored
+    pub fn add_raw_datapoint(
+        &self,
+        raw_datapoint: &LinearRawDatapoint,
+        aggregation: Aggregation,
+        confidences: &[Confidence],
+    ) -> BencherResult<()> {
+        if let Some(datapoint) = raw_datapoint.aggregate(aggregation, confidences)? {
+            self.add_datapoint_with_sample(datapoint, &raw_datapoint.v)?;
+        }
+        Ok(())
     }
 
     pub fn revert(&self, group: &str, version: Option<usize>) -> BencherResult<()> {
@@ -39,6 +82,7 @@ impl<'a> LinearSetHandle<'a> {
 pub struct XYLineHandle<'a> {
     db: &'a DbWriteBackend,
     exp_code: String,
+    metadata: RunMetadata,
 }
 
 impl<'a> XYLineHandle<'a> {
@@ -46,9 +90,18 @@ impl<'a> XYLineHandle<'a> {
         XYLineHandle {
             db,
             exp_code: exp_code.to_string(),
+            metadata: RunMetadata::default(),
         }
     }
 
+    /// Record the given run metadata with every datapoint added through this handle
+    ///
+    /// Datapoints are timestamped with the time they are added, unless the metadata has a timestamp
+    pub fn with_metadata(mut self, metadata: RunMetadata) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
     /// Tag untagged datapoint (with the next point in line)
     fn tag_datapoint(&self, datapoint: XYDatapoint) -> BencherResult<XYDatapoint> {
         if let None = &datapoint.tag {
@@ -62,7 +115,41 @@ impl<'a> XYLineHandle<'a> {
 
     pub fn add_datapoint(&self, datapoint: XYDatapoint) -> BencherResult<()> {
         let datapoint = self.tag_datapoint(datapoint)?;
-        self.db.add_xy_datapoint(&self.exp_code, datapoint)
+        self.db
+            .add_xy_datapoint(&self.exp_code, datapoint, &self.metadata)?;
+        Ok(())
+    }
+
+    /// Add a datapoint along with the raw samples it was aggregated from
+    pub fn add_datapoint_with_samples(
+        &self,
+        datapoint: XYDatapoint,
+        x_sample: &Sample,
+        y_sample: &Sample,
+    ) -> BencherResult<()> {
+        let datapoint = self.tag_datapoint(datapoint)?;
+        let tag = datapoint.tag.unwrap();
+        let version = self
+            .db
+            .add_xy_datapoint(&self.exp_code, datapoint, &self.metadata)?;
+        self.db
+            .add_xy_samples(&self.exp_code, tag, version, x_sample, y_sample)
+    }
+
+    /// Aggregate a raw datapoint and add it, keeping the raw samples
+    ///
+    /// The confidence intervals at the given percentiles are computed in addition to the default ones.
+    /// Empty samples are ignored
+    pub fn add_raw_datapoint(
+        &self,
+        raw_datapoint: &XYRawDatapoint,
+        aggregation: Aggregation,
+        confidences: &[Confidence],
+    ) -> BencherResult<()> {
+        if let Some(datapoint) = raw_datapoint.aggregate(aggregation, confidences)? {
+            self.add_datapoint_with_samples(datapoint, &raw_datapoint.x, &raw_datapoint.y)?;
+        }
+        Ok(())
     }
 
     pub fn revert(&self, tag: isize, version: Option<usize>) -> BencherResult<()> {
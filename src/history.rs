@@ -0,0 +1,601 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::File;
+use std::io::Write;
+
+use crate::*;
+
+use cli_table::{format::Justify, Cell, Style, Table};
+
+/// One version of the datapoint followed by a history
+struct HistoryEntry {
+    version: usize,
+    status: VersionStatus,
+    run: RunMetadata,
+
+    /// X value of bidimensional datapoints
+    x: Option<Value>,
+    v: Value,
+    v_confidence: BTreeMap<Confidence, (Value, Value)>,
+}
+
+/// The history of a single datapoint (a group of a linear set or a tag of an xy line):
+/// every version it was added with, in order
+///
+/// For bidimensional datapoints, the history follows the y value
+pub struct HistoryView {
+    entries: Vec<HistoryEntry>,
+    scale: Scale,
+
+    /// Following the linear example, this could be "A (a): get"
+    title: String,
+
+    /// Label, units and format of the x value of bidimensional datapoints
+    x_axis: Option<(String, String, AxisFormat)>,
+    v_label: String,
+    v_units: String,
+    style: PlotStyle,
+}
+
+impl HistoryView {
+    pub(crate) fn from_linear(
+        experiment: &LinearExperiment,
+        exp_code: &str,
+        exp_label: &str,
+        group: &str,
+        versions: Vec<DatapointVersion<LinearDatapoint>>,
+    ) -> BencherResult<Self> {
+        if versions.is_empty() {
+            return Err(BencherError::NoVersions(
+                exp_code.to_string(),
+                format!("group {}", group),
+            ));
+        }
+
+        let entries = versions
+            .into_iter()
+            .map(|v| HistoryEntry {
+                version: v.version,
+                status: v.status,
+                run: v.run,
+                x: None,
+                v: v.datapoint.v,
+                v_confidence: v.datapoint.v_confidence,
+            })
+            .collect::<Vec<_>>();
+
+        Ok(Self {
+            scale: choose_scale(
+                &experiment.v_units,
+                entries.iter(),
+                &experiment.v_format.unwrap_or_default(),
+            ),
+            entries,
+            title: format!("{} ({}): {}", exp_label, exp_code, group),
+            x_axis: None,
+            v_label: experiment.v_label.clone(),
+            v_units: experiment.v_units.clone(),
+            style: PlotStyle::default(),
+        })
+    }
+
+    pub(crate) fn from_xy(
+        experiment: &XYExperiment,
+        exp_code: &str,
+        exp_label: &str,
+        tag: isize,
+        versions: Vec<DatapointVersion<XYDatapoint>>,
+    ) -> BencherResult<Self> {
+        if versions.is_empty() {
+            return Err(BencherError::NoVersions(
+                exp_code.to_string(),
+                format!("tag {}", tag),
+            ));
+        }
+
+        let entries = versions
+            .into_iter()
+            .map(|v| HistoryEntry {
+                version: v.version,
+                status: v.status,
+                run: v.run,
+                x: Some(v.datapoint.x),
+                v: v.datapoint.y,
+                v_confidence: v.datapoint.y_confidence,
+            })
+            .collect::<Vec<_>>();
+
+        Ok(Self {
+            scale: choose_scale(
+                &experiment.y_units,
+                entries.iter(),
+                &experiment.y_format.unwrap_or_default(),
+            ),
+            entries,
+            title: format!("{} ({}): tag {}", exp_label, exp_code, tag),
+            x_axis: Some((
+                experiment.x_label.clone(),
+                experiment.x_units.clone(),
+                experiment.x_format.unwrap_or_default(),
+            )),
+            v_label: experiment.y_label.clone(),
+            v_units: experiment.y_units.clone(),
+            style: PlotStyle::default(),
+        })
+    }
+
+    /// Set the style of the plots (from the config and the command line)
+    pub(crate) fn with_style(mut self, style: PlotStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Styles of the followed versions and of the reverted ones
+    fn series_styles(&self) -> (SeriesStyle, SeriesStyle) {
+        (
+            self.style.indexed_series_style(0, 4),
+            self.style.indexed_series_style(1, 2),
+        )
+    }
+
+    /// Versions are plotted against time if all of them were timestamped,
+    /// otherwise against the version number
+    fn has_timestamps(&self) -> bool {
+        self.entries.iter().all(|e| e.run.timestamp.is_some())
+    }
+}
+
+/// Confidence of the error bars of a history (shown on the followed value)
+fn history_confidence(bar: Bars) -> Option<Confidence> {
+    match bar {
+        Bars::Linear(c) | Bars::Y(c) => Some(c),
+        _ => None,
+    }
+}
+
+impl ExperimentView for HistoryView {
+    fn gnuplot(&self, prefix: &std::path::Path, bar: Bars) -> BencherResult<()> {
+        let mut gnu_path: std::path::PathBuf = prefix.into();
+        if !gnu_path.set_extension("gnu") {
+            return Err(BencherError::PathCreateError(gnu_path, "gnu".to_string()));
+        }
+        let mut dat_path: std::path::PathBuf = prefix.into();
+        if !dat_path.set_extension("dat") {
+            return Err(BencherError::PathCreateError(dat_path, "dat".to_string()));
+        }
+        let output_path = self.style.output_path(prefix)?;
+        let (versions, reverted) = self.series_styles();
+
+        let mut file = File::create(&gnu_path).map_err(|e| {
+            BencherError::io_err(e, format!("creating {}", gnu_path.to_string_lossy()))
+        })?;
+        write!(
+            &mut file,
+            "{0}set datafile missing '?'
+
+# Set color of linestyle 1 to #{1}
+set style line 1 linecolor rgb '#{1}' linetype 2 linewidth {3} pointtype {4} pointsize {6}{7}
+# Set color of linestyle 2 (reverted versions) to #{2}
+set style line 2 linecolor rgb '#{2}' linetype 2 linewidth {3} pointtype {5} pointsize {6}{8}
+",
+            self.style.gnuplot_preamble(&output_path),
+            versions.color,
+            reverted.color,
+            self.style.line_width_or(2.5),
+            versions.point_type,
+            reverted.point_type,
+            self.style.point_size_or(2.0),
+            versions.gnuplot_dash_type(),
+            reverted.gnuplot_dash_type(),
+        )
+        .map_err(|e| BencherError::io_err(e, "writing gnu to file"))?;
+
+        let x_column = if self.has_timestamps() {
+            writeln!(
+                &mut file,
+                "
+# set axis
+set tics scale 0.75
+set xdata time
+set timefmt '%s'
+set format x '%Y-%m-%d'
+set xtics rotate by 45 right
+set xlabel 'Date'"
+            )
+            .map_err(|e| BencherError::io_err(e, "writing gnu to file"))?;
+            2
+        } else {
+            writeln!(
+                &mut file,
+                "
+# set axis
+set tics scale 0.75
+set xlabel 'Version'"
+            )
+            .map_err(|e| BencherError::io_err(e, "writing gnu to file"))?;
+            1
+        };
+
+        write!(
+            &mut file,
+            "set ylabel '{} ({})'
+set xrange [*:*]
+set yrange [*:*]
+",
+            self.v_label,
+            self.scale.units(),
+        )
+        .map_err(|e| BencherError::io_err(e, "writing gnu to file"))?;
+
+        match history_confidence(bar) {
+            Some(_) => writeln!(
+                &mut file,
+                "plot '{0}' using {1}:3:4:5 title '{2}' with yerrorlines linestyle 1, '' using {1}:(strcol(6) eq 'reverted' ? $3 : 1/0) title 'reverted' with points linestyle 2",
+                dat_path.to_string_lossy(),
+                x_column,
+                self.title,
+            )
+            .map_err(|e| BencherError::io_err(e, "writing gnu to file"))?,
+            None => writeln!(
+                &mut file,
+                "plot '{0}' using {1}:3 title '{2}' with linespoint linestyle 1, '' using {1}:(strcol(4) eq 'reverted' ? $3 : 1/0) title 'reverted' with points linestyle 2",
+                dat_path.to_string_lossy(),
+                x_column,
+                self.title,
+            )
+            .map_err(|e| BencherError::io_err(e, "writing gnu to file"))?,
+        }
+
+        Ok(())
+    }
+
+    fn dat(&self, prefix: &std::path::Path, bar: Bars) -> BencherResult<()> {
+        let mut dat_path: std::path::PathBuf = prefix.into();
+        if !dat_path.set_extension("dat") {
+            return Err(BencherError::PathCreateError(dat_path, "dat".to_string()));
+        }
+        let mut file = File::create(&dat_path).map_err(|e| {
+            BencherError::io_err(e, format!("creating {}", dat_path.to_string_lossy()))
+        })?;
+
+        let confidence = history_confidence(bar);
+        let confidence_str = match confidence {
+            Some(c) => format!("confidence interval: {}% - {}%", c.lower(), c.upper()),
+            None => "".to_string(),
+        };
+
+        writeln!(
+            &mut file,
+            "#begin {} {}",
+            dat_path.to_string_lossy(),
+            confidence_str
+        )
+        .map_err(|e| BencherError::io_err(e, "writing dat to file"))?;
+
+        // header, as a comment (the columns are plotted by number)
+        write!(
+            &mut file,
+            "#{:>33} {:>34} {:>34} ",
+            "\"version\"", "\"timestamp\"", "\"value\""
+        )
+        .map_err(|e| BencherError::io_err(e, "writing dat to file"))?;
+        if confidence.is_some() {
+            write!(&mut file, "{:>34} {:>34} ", "\"min\"", "\"max\"")
+                .map_err(|e| BencherError::io_err(e, "writing dat to file"))?;
+        }
+        write!(&mut file, "{:>34} ", "\"status\"")
+            .map_err(|e| BencherError::io_err(e, "writing dat to file"))?;
+
+        for entry in &self.entries {
+            write!(
+                &mut file,
+                "\n{:>34} {:>34} {:>34} ",
+                entry.version,
+                entry
+                    .run
+                    .timestamp
+                    .map_or("?".to_string(), |t| t.to_string()),
+                entry.v.display_with_scale(&self.scale)
+            )
+            .map_err(|e| BencherError::io_err(e, "writing dat to file"))?;
+
+            if let Some(confidence) = confidence {
+                let (min, max) = entry
+                    .v_confidence
+                    .get(&confidence)
+                    .cloned()
+                    .unwrap_or((entry.v, entry.v));
+                write!(
+                    &mut file,
+                    "{:>34} {:>34} ",
+                    min.display_with_scale(&self.scale),
+                    max.display_with_scale(&self.scale)
+                )
+                .map_err(|e| BencherError::io_err(e, "writing dat to file"))?;
+            }
+
+            write!(&mut file, "{:>34} ", entry.status.to_string())
+                .map_err(|e| BencherError::io_err(e, "writing dat to file"))?;
+        }
+
+        writeln!(&mut file, "\n#end")
+            .map_err(|e| BencherError::io_err(e, "writing dat to file"))?;
+        Ok(())
+    }
+
+    fn image(&self, prefix: &std::path::Path, format: ImageFormat, bar: Bars) -> BencherResult<()> {
+        let has_timestamps = self.has_timestamps();
+        let confidence = history_confidence(bar);
+        let point = |entry: &HistoryEntry| LinePoint {
+            x: if has_timestamps {
+                entry.run.timestamp.unwrap_or_default() as f64
+            } else {
+                entry.version as f64
+            },
+            y: self.scale.scale(entry.v.as_float()),
+            x_bar: None,
+            y_bar: confidence
+                .and_then(|c| entry.v_confidence.get(&c))
+                .map(|(min, max)| {
+                    (
+                        self.scale.scale(min.as_float()),
+                        self.scale.scale(max.as_float()),
+                    )
+                }),
+        };
+
+        let (versions, reverted) = self.series_styles();
+        let lines = [
+            Line {
+                label: self.title.clone(),
+                style: versions,
+                points: self.entries.iter().map(point).collect(),
+                joined: true,
+            },
+            Line {
+                label: "reverted".to_string(),
+                style: reverted,
+                points: self
+                    .entries
+                    .iter()
+                    .filter(|e| e.status == VersionStatus::Reverted)
+                    .map(point)
+                    .collect(),
+                joined: false,
+            },
+        ];
+
+        let date = |x: &f64| {
+            RunMetadata::default()
+                .timestamp(*x as i64)
+                .datetime()
+                .map(|d| d[..10].to_string())
+                .unwrap_or_default()
+        };
+        let version = |x: &f64| {
+            if x.fract() == 0.0 {
+                format!("{}", x)
+            } else {
+                String::new()
+            }
+        };
+
+        render_lines(
+            prefix,
+            format,
+            (
+                if has_timestamps { "Date" } else { "Version" },
+                &format!("{} ({})", self.v_label, self.scale.units(),),
+            ),
+            &lines,
+            Some(if has_timestamps { &date } else { &version }),
+            (&PlotAxis::default(), &PlotAxis::default()),
+            &self.style,
+        )
+    }
+
+    fn json<W: Write>(&self, writer: &mut W) -> BencherResult<()> {
+        let versions = self
+            .entries
+            .iter()
+            .map(|entry| {
+                serde_json::json!({
+                    "version": entry.version,
+                    "status": entry.status.to_string(),
+                    "timestamp": entry.run.timestamp,
+                    "git_revision": entry.run.git_revision,
+                    "hostname": entry.run.hostname,
+                    "metadata": entry.run.values,
+                    "x": entry.x.as_ref().map(json_value),
+                    "v": json_value(&entry.v),
+                    "confidences": json_confidences(&entry.v_confidence),
+                })
+            })
+            .collect::<Vec<_>>();
+
+        write_json(
+            writer,
+            &serde_json::json!({
+                "title": self.title,
+                "x_label": self.x_axis.as_ref().map(|(label, _, _)| label),
+                "x_units": self.x_axis.as_ref().map(|(_, units, _)| units),
+                "v_label": self.v_label,
+                "v_units": self.v_units,
+                "display_magnitude": self.scale.prefix(),
+                "display_units": self.scale.units(),
+                "versions": versions,
+            }),
+        )
+    }
+
+    fn csv<W: Write>(&self, writer: &mut W) -> BencherResult<()> {
+        let confidences = self
+            .entries
+            .iter()
+            .flat_map(|e| e.v_confidence.keys().copied())
+            .collect::<BTreeSet<_>>();
+
+        let mut csv_writer = csv::Writer::from_writer(writer);
+        let mut header = vec![
+            "version".to_string(),
+            "status".to_string(),
+            "timestamp".to_string(),
+            "git_revision".to_string(),
+            "hostname".to_string(),
+        ];
+        if self.x_axis.is_some() {
+            header.push("x".to_string());
+        }
+        header.extend([
+            "v".to_string(),
+            "units".to_string(),
+            "display_magnitude".to_string(),
+            "display_units".to_string(),
+        ]);
+        header.extend(csv_confidence_header(&confidences, ""));
+        csv_writer.write_record(&header)?;
+
+        for entry in &self.entries {
+            let mut record = vec![
+                entry.version.to_string(),
+                entry.status.to_string(),
+                entry.run.timestamp.map_or(String::new(), |t| t.to_string()),
+                entry.run.git_revision.clone().unwrap_or_default(),
+                entry.run.hostname.clone().unwrap_or_default(),
+            ];
+            if self.x_axis.is_some() {
+                record.push(entry.x.map_or(String::new(), |x| x.to_string()));
+            }
+            record.extend([
+                entry.v.to_string(),
+                self.v_units.clone(),
+                self.scale.prefix().to_string(),
+                self.scale.units(),
+            ]);
+            record.extend(csv_confidence_cells(&entry.v_confidence, &confidences));
+            csv_writer.write_record(&record)?;
+        }
+
+        csv_writer
+            .flush()
+            .map_err(|e| BencherError::io_err(e, "writing csv"))
+    }
+
+    fn table<W: Write>(&self, writer: &mut W) -> BencherResult<()> {
+        let x_scale = self
+            .x_axis
+            .as_ref()
+            .map(|(_, x_units, x_format)| choose_x_scale(x_units, self.entries.iter(), x_format));
+        let rows = self.entries.iter().map(|entry| {
+            let mut row = vec![
+                entry.version.cell().justify(Justify::Right),
+                entry.status.to_string().cell().justify(Justify::Center),
+                entry
+                    .run
+                    .datetime()
+                    .unwrap_or_default()
+                    .cell()
+                    .justify(Justify::Center),
+                entry
+                    .run
+                    .git_revision
+                    .as_ref()
+                    .map(|r| r.chars().take(12).collect::<String>())
+                    .unwrap_or_default()
+                    .cell()
+                    .justify(Justify::Center),
+                entry
+                    .run
+                    .hostname
+                    .clone()
+                    .unwrap_or_default()
+                    .cell()
+                    .justify(Justify::Center),
+            ];
+            if let (Some(x), Some(x_scale)) = (entry.x, &x_scale) {
+                row.push(x.display_with_scale(x_scale).cell().justify(Justify::Right));
+            }
+            row.push(
+                entry
+                    .v
+                    .display_with_scale(&self.scale)
+                    .cell()
+                    .justify(Justify::Right),
+            );
+            row
+        });
+
+        let mut title = vec![
+            "Version".cell().justify(Justify::Center).bold(true),
+            "Status".cell().justify(Justify::Center).bold(true),
+            "Date".cell().justify(Justify::Center).bold(true),
+            "Revision".cell().justify(Justify::Center).bold(true),
+            "Host".cell().justify(Justify::Center).bold(true),
+        ];
+        if let (Some((x_label, _, _)), Some(x_scale)) = (&self.x_axis, &x_scale) {
+            title.push(
+                format!("{} ({})", x_label, x_scale.units())
+                    .cell()
+                    .justify(Justify::Center)
+                    .bold(true),
+            );
+        }
+        title.push(
+            format!("{} ({})", self.v_label, self.scale.units(),)
+                .cell()
+                .justify(Justify::Center)
+                .bold(true),
+        );
+
+        let table = rows.table().title(title).bold(true);
+
+        let table_display = table
+            .display()
+            .map_err(|e| BencherError::io_err(e, "creating table display"))?;
+        writeln!(writer, "{}\n{}", self.title, table_display)
+            .map_err(|e| BencherError::io_err(e, "writing table display"))?;
+        Ok(())
+    }
+
+    fn latex_table<W: Write>(&self, writer: &mut W) -> BencherResult<()> {
+        writeln!(writer, "\\begin{{table}}[t]\n    \\centering\n    \\begin{{tabular}}{{|r|c|r|}}\n        \\hline").map_err(|e| BencherError::io_err(e, "writing latex table"))?;
+        writeln!(
+            writer,
+            "        \\textbf{{ Version }} & \\textbf{{ Date }} & \\textbf{{ {} ({}) }} \\\\ \\hline",
+            self.v_label,
+            self.scale.units(),
+        )
+        .map_err(|e| BencherError::io_err(e, "writing latex table"))?;
+        for entry in &self.entries {
+            writeln!(
+                writer,
+                "        ${:>8}$ & {} & ${:>8}$ \\\\ \\hline",
+                entry.version,
+                entry.run.datetime().unwrap_or_default(),
+                entry.v.display_with_scale(&self.scale)
+            )
+            .map_err(|e| BencherError::io_err(e, "writing latex table"))?
+        }
+        writeln!(writer,
+            "    \\end{{tabular}}\n    \\caption{{History: {0}}}\\label{{table:{0}}}\n\\end{{table}}", self.title
+        ).map_err(|e| BencherError::io_err(e, "writing latex table"))?;
+        Ok(())
+    }
+}
+
+/// Choose the scale of the followed values
+fn choose_scale<'a>(
+    units: &str,
+    entries: impl Iterator<Item = &'a HistoryEntry>,
+    format: &AxisFormat,
+) -> Scale {
+    Scale::choose(units, entries.map(|e| e.v), format)
+}
+
+/// Choose the scale of the x values (of bidimensional datapoints)
+fn choose_x_scale<'a>(
+    units: &str,
+    entries: impl Iterator<Item = &'a HistoryEntry>,
+    format: &AxisFormat,
+) -> Scale {
+    Scale::choose(units, entries.filter_map(|e| e.x), format)
+}
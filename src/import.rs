@@ -0,0 +1,310 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+
+use crate::*;
+
+/// Format of a file with raw samples
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SampleFormat {
+    /// Comma separated values, with a header line naming the columns
+    Csv,
+
+    /// One JSON object per line, keyed by column name
+    JsonLines,
+}
+
+impl SampleFormat {
+    /// Guess the format from the file extension
+    pub fn from_path(path: &std::path::Path) -> BencherResult<Self> {
+        path.extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(|ext| ext.parse().ok())
+            .ok_or_else(|| BencherError::UnknownSampleFormat(path.to_string_lossy().to_string()))
+    }
+}
+
+impl std::str::FromStr for SampleFormat {
+    type Err = BencherError;
+    fn from_str(s: &str) -> BencherResult<Self> {
+        match s.to_lowercase().as_str() {
+            "csv" => Ok(SampleFormat::Csv),
+            "jsonl" | "ndjson" | "json" => Ok(SampleFormat::JsonLines),
+            _ => Err(BencherError::UnknownSampleFormat(s.to_string())),
+        }
+    }
+}
+
+/// Columns to read when importing samples into a linear experiment
+///
+/// Each row is a sample, the rows are grouped by the group column
+#[derive(Clone, Debug)]
+pub struct LinearSampleColumns {
+    pub group: String,
+    pub value: String,
+}
+
+/// Columns to read when importing samples into a bidimensional experiment
+///
+/// Each row is a sample, the rows are grouped by the tag column (if given) or by the x column
+#[derive(Clone, Debug)]
+pub struct XYSampleColumns {
+    pub x: String,
+    pub y: String,
+    pub tag: Option<String>,
+}
+
+/// A row of a sample file: maps column names to the (unparsed) values
+struct Record {
+    /// Line of the file the row is in (starting at 1), for errors
+    line: usize,
+    values: HashMap<String, String>,
+}
+
+/// Read all the records in a sample file
+fn read_records(path: &std::path::Path, format: SampleFormat) -> BencherResult<Vec<Record>> {
+    let file = File::open(path)
+        .map_err(|e| BencherError::io_err(e, format!("opening {}", path.to_string_lossy())))?;
+
+    match format {
+        SampleFormat::Csv => {
+            let mut reader = csv::ReaderBuilder::new()
+                .trim(csv::Trim::All)
+                .from_reader(file);
+            let headers = reader.headers()?.clone();
+            reader
+                .records()
+                .map(|record| {
+                    let record = record?;
+                    Ok(Record {
+                        line: record.position().map(|p| p.line() as usize).unwrap_or(0),
+                        values: headers
+                            .iter()
+                            .zip(record.iter())
+                            .map(|(h, v)| (h.to_string(), v.to_string()))
+                            .collect(),
+                    })
+                })
+                .collect()
+        }
+        SampleFormat::JsonLines => BufReader::new(file)
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !matches!(line, Ok(l) if l.trim().is_empty()))
+            .map(|(idx, line)| {
+                let line = line.map_err(|e| {
+                    BencherError::io_err(e, format!("reading {}", path.to_string_lossy()))
+                })?;
+                let object: serde_json::Map<String, serde_json::Value> =
+                    serde_json::from_str(&line)?;
+                Ok(Record {
+                    line: idx + 1,
+                    values: object
+                        .into_iter()
+                        .map(|(k, v)| match v {
+                            serde_json::Value::String(s) => (k, s),
+                            v => (k, v.to_string()),
+                        })
+                        .collect(),
+                })
+            })
+            .collect(),
+    }
+}
+
+/// Get a column from a record
+fn get_column<'a>(
+    record: &'a Record,
+    column: &str,
+    path: &std::path::Path,
+) -> BencherResult<&'a String> {
+    record.values.get(column).ok_or_else(|| {
+        BencherError::MissingColumn(column.to_string(), path.to_string_lossy().to_string())
+    })
+}
+
+/// Group the records by the value of a column, preserving the order in which the groups appear
+fn group_records<'a>(
+    records: &'a [Record],
+    column: &str,
+    path: &std::path::Path,
+) -> BencherResult<Vec<(String, Vec<&'a Record>)>> {
+    let mut groups: Vec<(String, Vec<&Record>)> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+
+    for record in records {
+        let key = get_column(record, column, path)?;
+        match index.get(key) {
+            Some(&idx) => groups[idx].1.push(record),
+            None => {
+                index.insert(key.clone(), groups.len());
+                groups.push((key.clone(), vec![record]));
+            }
+        }
+    }
+
+    Ok(groups)
+}
+
+/// Parse a column of a group of records into a sample
+///
+/// Every value has to be a finite number
+fn parse_sample(
+    records: &[&Record],
+    column: &str,
+    path: &std::path::Path,
+) -> BencherResult<Sample> {
+    let values = records
+        .iter()
+        .map(|r| {
+            let value = get_column(r, column, path)?;
+            value
+                .parse::<Value>()
+                .map_err(|_| BencherError::InvalidSampleValue {
+                    path: path.to_string_lossy().to_string(),
+                    row: r.line,
+                    column: column.to_string(),
+                    value: value.clone(),
+                })
+        })
+        .collect::<BencherResult<Vec<_>>>()?;
+
+    Ok(sample_from_values(values))
+}
+
+/// Read a sample file for a linear experiment, one raw datapoint per group
+pub fn read_linear_samples(
+    path: &std::path::Path,
+    format: SampleFormat,
+    columns: &LinearSampleColumns,
+) -> BencherResult<Vec<LinearRawDatapoint>> {
+    let records = read_records(path, format)?;
+
+    group_records(&records, &columns.group, path)?
+        .into_iter()
+        .map(|(group, records)| {
+            let v = parse_sample(&records, &columns.value, path)?;
+            Ok(LinearRawDatapoint::new(group, v))
+        })
+        .collect()
+}
+
+/// Read a sample file for a bidimensional experiment, one raw datapoint per tag (or x value)
+pub fn read_xy_samples(
+    path: &std::path::Path,
+    format: SampleFormat,
+    columns: &XYSampleColumns,
+) -> BencherResult<Vec<XYRawDatapoint>> {
+    let records = read_records(path, format)?;
+    let group_column = columns.tag.as_ref().unwrap_or(&columns.x);
+
+    group_records(&records, group_column, path)?
+        .into_iter()
+        .map(|(key, records)| {
+            let x = parse_sample(&records, &columns.x, path)?;
+            let y = parse_sample(&records, &columns.y, path)?;
+            let datapoint = XYRawDatapoint::new(x, y);
+
+            if columns.tag.is_some() {
+                let tag = key
+                    .parse::<isize>()
+                    .map_err(|_| BencherError::InvalidTag(key.clone()))?;
+                Ok(datapoint.tag(tag))
+            } else {
+                Ok(datapoint)
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use either::Either;
+
+    /// Write a sample file to a temporary path (named after the test)
+    fn sample_file(name: &str, contents: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("bencher-{}-{}", std::process::id(), name));
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    fn linear_columns() -> LinearSampleColumns {
+        LinearSampleColumns {
+            group: "group".to_string(),
+            value: "v".to_string(),
+        }
+    }
+
+    #[test]
+    fn import_csv() {
+        let path = sample_file("import.csv", "group, v\nget, 1\nput, 3\nget, 2\n");
+        let datapoints = read_linear_samples(&path, SampleFormat::Csv, &linear_columns()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(datapoints.len(), 2);
+        assert_eq!(datapoints[0].group, "get");
+        assert_eq!(datapoints[0].v, Either::Left(vec![1, 2]));
+        assert_eq!(datapoints[1].group, "put");
+        assert_eq!(datapoints[1].v, Either::Left(vec![3]));
+    }
+
+    #[test]
+    fn import_json_lines() {
+        let path = sample_file(
+            "import.jsonl",
+            "{\"x\": 1, \"y\": 2.5, \"tag\": \"7\"}\n\n{\"x\": 1, \"y\": 3, \"tag\": 7}\n",
+        );
+        let columns = XYSampleColumns {
+            x: "x".to_string(),
+            y: "y".to_string(),
+            tag: Some("tag".to_string()),
+        };
+        let datapoints = read_xy_samples(&path, SampleFormat::JsonLines, &columns).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(datapoints.len(), 1);
+        assert_eq!(datapoints[0].x, Either::Left(vec![1, 1]));
+        assert_eq!(datapoints[0].y, Either::Right(vec![2.5, 3.0]));
+        assert_eq!(datapoints[0].tag, Some(7));
+    }
+
+    #[test]
+    fn import_missing_column() {
+        let path = sample_file("missing.csv", "group,value\nget,1\n");
+        let result = read_linear_samples(&path, SampleFormat::Csv, &linear_columns());
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(matches!(result, Err(BencherError::MissingColumn(column, _)) if column == "v"));
+    }
+
+    #[test]
+    fn import_non_finite_value() {
+        let path = sample_file("nan.csv", "group,v\na,1\na,NaN\n");
+        let result = read_linear_samples(&path, SampleFormat::Csv, &linear_columns());
+        std::fs::remove_file(&path).unwrap();
+
+        match result {
+            Err(BencherError::InvalidSampleValue {
+                path: error_path,
+                row,
+                column,
+                value,
+            }) => {
+                assert_eq!(error_path, path.to_string_lossy());
+                assert_eq!(row, 3);
+                assert_eq!(column, "v");
+                assert_eq!(value, "NaN");
+            }
+            _ => panic!("expected an invalid sample value error"),
+        }
+
+        let path = sample_file("inf.jsonl", "{\"group\": \"a\", \"v\": \"inf\"}\n");
+        let result = read_linear_samples(&path, SampleFormat::JsonLines, &linear_columns());
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(
+            result,
+            Err(BencherError::InvalidSampleValue { row: 1, .. })
+        ));
+    }
+}
@@ -0,0 +1,330 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use evalexpr::{Context, ContextWithMutableVariables, Node, Operator};
+
+use crate::*;
+
+/// Maximum number of variables with intervals for which every corner is evaluated
+/// Above this, intervals are mapped with interval arithmetic
+const MAX_CORNER_VARIABLES: usize = 10;
+
+/// Number of steps along each interval when detecting whether an expression is monotonic
+const DETECTION_STEPS: usize = 8;
+
+type Interval = (f64, f64);
+
+/// Map confidence intervals through an expression
+///
+/// `ctx` has every variable at its value, and `intervals` the bounds of the ones with intervals.
+/// Unless declared, the monotonicity of the expression is detected by evaluating it along each
+/// interval (with the other variables at their values). The bounds of a monotonic expression are
+/// its extremes at the corners of the intervals, while the ones of a non-monotonic expression are
+/// computed with interval arithmetic.
+///
+/// The bounds are always ordered (i.e., lower <= upper)
+pub(crate) fn map_interval(
+    expr: &str,
+    ctx: &evalexpr::HashMapContext,
+    intervals: &BTreeMap<String, (Value, Value)>,
+    monotonicity: Option<Monotonicity>,
+) -> BencherResult<(Value, Value)> {
+    let tree = evalexpr::build_operator_tree(expr)?;
+
+    // only the variables read by the expression matter
+    let read: BTreeSet<&str> = tree.iter_read_variable_identifiers().collect();
+    let expression = IntervalExpression {
+        expr,
+        ctx,
+        intervals: intervals
+            .iter()
+            .filter(|(name, (lower, upper))| read.contains(name.as_str()) && lower != upper)
+            .map(|(name, &(lower, upper))| (name.as_str(), lower, upper))
+            .collect(),
+        tree,
+    };
+
+    let monotonicity = match monotonicity {
+        Some(m) => m,
+        None if expression.is_monotonic()? => Monotonicity::Monotonic,
+        None => Monotonicity::NonMonotonic,
+    };
+
+    let (a, b) = match monotonicity {
+        Monotonicity::Increasing => (expression.eval_at_lower()?, expression.eval_at_upper()?),
+        Monotonicity::Decreasing => (expression.eval_at_upper()?, expression.eval_at_lower()?),
+        Monotonicity::Monotonic if expression.intervals.len() <= MAX_CORNER_VARIABLES => {
+            expression.eval_corners()?
+        }
+        Monotonicity::Monotonic | Monotonicity::NonMonotonic => expression.interval_arithmetic()?,
+    };
+
+    Ok((a.min(b), a.max(b)))
+}
+
+/// An expression, along with the variables with intervals it reads
+struct IntervalExpression<'a> {
+    expr: &'a str,
+    tree: Node,
+    ctx: &'a evalexpr::HashMapContext,
+    intervals: Vec<(&'a str, Value, Value)>,
+}
+
+impl IntervalExpression<'_> {
+    /// Evaluate the expression with some of the variables replaced
+    fn eval_at<'b>(
+        &self,
+        values: impl IntoIterator<Item = (&'b str, Value)>,
+    ) -> BencherResult<Value> {
+        let mut ctx = self.ctx.clone();
+        for (name, v) in values {
+            ctx.set_value(name.to_string(), v.into())?;
+        }
+        match self.tree.eval_with_context(&ctx)?.try_into()? {
+            Value::Float(v) if v.is_nan() => Err(self.unbounded("the expression is not a number")),
+            v => Ok(v),
+        }
+    }
+
+    fn eval_at_lower(&self) -> BencherResult<Value> {
+        self.eval_at(self.intervals.iter().map(|&(name, lower, _)| (name, lower)))
+    }
+
+    fn eval_at_upper(&self) -> BencherResult<Value> {
+        self.eval_at(self.intervals.iter().map(|&(name, _, upper)| (name, upper)))
+    }
+
+    /// Extremes of the expression at the corners of the intervals
+    fn eval_corners(&self) -> BencherResult<(Value, Value)> {
+        let mut bounds: Option<(Value, Value)> = None;
+        for corner in 0..(1usize << self.intervals.len()) {
+            let v = self.eval_at(self.intervals.iter().enumerate().map(
+                |(idx, &(name, lower, upper))| {
+                    (name, if corner >> idx & 1 == 0 { lower } else { upper })
+                },
+            ))?;
+            bounds = Some(bounds.map_or((v, v), |(lower, upper)| (lower.min(v), upper.max(v))));
+        }
+
+        match bounds {
+            Some(bounds) => Ok(bounds),
+            None => self.eval_at([]).map(|v| (v, v)),
+        }
+    }
+
+    /// Whether the expression is monotonic along each interval
+    ///
+    /// This only samples the expression, with the other variables at their values,
+    /// so it may miss non-monotonic behaviour between the samples
+    fn is_monotonic(&self) -> BencherResult<bool> {
+        for &(name, lower, upper) in &self.intervals {
+            let samples = (0..=DETECTION_STEPS)
+                .map(|step| {
+                    let v = interpolate(lower, upper, step as f64 / DETECTION_STEPS as f64);
+                    self.eval_at([(name, v)]).map(|v| v.as_float())
+                })
+                .collect::<BencherResult<Vec<_>>>()?;
+
+            let increasing = samples.windows(2).all(|w| w[0] <= w[1]);
+            let decreasing = samples.windows(2).all(|w| w[0] >= w[1]);
+            if !increasing && !decreasing {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Bounds of the expression computed with interval arithmetic
+    /// The bounds are widened to integers if the expression evaluates to an integer
+    fn interval_arithmetic(&self) -> BencherResult<(Value, Value)> {
+        let intervals = self
+            .intervals
+            .iter()
+            .map(|&(name, lower, upper)| (name, (lower.as_float(), upper.as_float())))
+            .collect::<BTreeMap<_, _>>();
+        let (lower, upper) = self.node_interval(&self.tree, &intervals)?;
+        if !lower.is_finite() || !upper.is_finite() {
+            return Err(self.unbounded("the bounds are not finite"));
+        }
+
+        Ok(match self.eval_at([])? {
+            Value::Int(_) => (
+                Value::Int(lower.floor() as i64),
+                Value::Int(upper.ceil() as i64),
+            ),
+            Value::Float(_) => (Value::Float(lower), Value::Float(upper)),
+        })
+    }
+
+    fn node_interval(
+        &self,
+        node: &Node,
+        intervals: &BTreeMap<&str, Interval>,
+    ) -> BencherResult<Interval> {
+        let children = node.children();
+        let child = |idx: usize| match children.get(idx) {
+            Some(child) => self.node_interval(child, intervals),
+            None => Err(self.unbounded("malformed expression")),
+        };
+
+        match node.operator() {
+            Operator::RootNode if children.len() == 1 => child(0),
+            Operator::Const { value } => Ok(point(value.as_number()?)),
+            Operator::VariableIdentifierRead { identifier } => {
+                match intervals.get(identifier.as_str()) {
+                    Some(&interval) => Ok(interval),
+                    None => match self.ctx.get_value(identifier) {
+                        Some(v) => Ok(point(v.as_number()?)),
+                        None => Err(self.unbounded(format!("`{}` is not defined", identifier))),
+                    },
+                }
+            }
+            Operator::Neg => {
+                let a = child(0)?;
+                Ok((-a.1, -a.0))
+            }
+            Operator::Add => {
+                let (a, b) = (child(0)?, child(1)?);
+                Ok((a.0 + b.0, a.1 + b.1))
+            }
+            Operator::Sub => {
+                let (a, b) = (child(0)?, child(1)?);
+                Ok((a.0 - b.1, a.1 - b.0))
+            }
+            Operator::Mul => {
+                let (a, b) = (child(0)?, child(1)?);
+                Ok(hull([a.0 * b.0, a.0 * b.1, a.1 * b.0, a.1 * b.1]))
+            }
+            Operator::Div => {
+                let (a, b) = (child(0)?, child(1)?);
+                self.divide(a, b)
+            }
+            Operator::Exp => {
+                let (a, b) = (child(0)?, child(1)?);
+                self.power(a, b)
+            }
+            Operator::FunctionIdentifier { identifier } => {
+                let args = match children {
+                    [arg] => function_arguments(arg)
+                        .iter()
+                        .map(|arg| self.node_interval(arg, intervals))
+                        .collect::<BencherResult<Vec<_>>>()?,
+                    _ => return Err(self.unbounded("malformed expression")),
+                };
+                self.function(identifier, &args)
+            }
+            op => Err(self.unbounded(format!("unsupported operator {:?}", op))),
+        }
+    }
+
+    fn divide(&self, a: Interval, b: Interval) -> BencherResult<Interval> {
+        if b.0 <= 0.0 && b.1 >= 0.0 {
+            return Err(self.unbounded("division by an interval containing 0"));
+        }
+        Ok(hull([a.0 / b.0, a.0 / b.1, a.1 / b.0, a.1 / b.1]))
+    }
+
+    fn power(&self, a: Interval, b: Interval) -> BencherResult<Interval> {
+        if b.0 == b.1 && b.0.fract() == 0.0 {
+            let n = b.0 as i32;
+            if n < 0 {
+                return self.divide(point(1.0), self.power(a, point(-b.0))?);
+            }
+
+            let (lower, upper) = (a.0.powi(n), a.1.powi(n));
+            if n % 2 == 0 && a.0 < 0.0 && a.1 > 0.0 {
+                Ok((0.0, lower.max(upper)))
+            } else {
+                Ok(hull([lower, upper]))
+            }
+        } else if a.0 > 0.0 {
+            // positive bases: monotonic in both the base and the exponent
+            Ok(hull([
+                a.0.powf(b.0),
+                a.0.powf(b.1),
+                a.1.powf(b.0),
+                a.1.powf(b.1),
+            ]))
+        } else {
+            Err(self.unbounded("non-integer power of an interval which is not positive"))
+        }
+    }
+
+    fn function(&self, identifier: &str, args: &[Interval]) -> BencherResult<Interval> {
+        let increasing = |f: fn(f64) -> f64| match args {
+            [a] => Ok((f(a.0), f(a.1))),
+            _ => Err(self.unbounded(format!("`{}` takes one argument", identifier))),
+        };
+
+        match identifier {
+            "math::ln" => increasing(f64::ln),
+            "math::log2" => increasing(f64::log2),
+            "math::log10" => increasing(f64::log10),
+            "math::exp" => increasing(f64::exp),
+            "math::exp2" => increasing(f64::exp2),
+            "math::sqrt" => increasing(f64::sqrt),
+            "math::cbrt" => increasing(f64::cbrt),
+            "math::atan" => increasing(f64::atan),
+            "math::sinh" => increasing(f64::sinh),
+            "math::asinh" => increasing(f64::asinh),
+            "math::tanh" => increasing(f64::tanh),
+            "floor" => increasing(f64::floor),
+            "round" => increasing(f64::round),
+            "ceil" => increasing(f64::ceil),
+            "math::abs" => match args {
+                [a] if a.0 < 0.0 && a.1 > 0.0 => Ok((0.0, a.0.abs().max(a.1.abs()))),
+                [a] => Ok(hull([a.0.abs(), a.1.abs()])),
+                _ => Err(self.unbounded("`math::abs` takes one argument")),
+            },
+            "math::pow" => match args {
+                [a, b] => self.power(*a, *b),
+                _ => Err(self.unbounded("`math::pow` takes two arguments")),
+            },
+            "min" if !args.is_empty() => {
+                Ok(args.iter().fold((f64::INFINITY, f64::INFINITY), |acc, a| {
+                    (acc.0.min(a.0), acc.1.min(a.1))
+                }))
+            }
+            "max" if !args.is_empty() => Ok(args
+                .iter()
+                .fold((f64::NEG_INFINITY, f64::NEG_INFINITY), |acc, a| {
+                    (acc.0.max(a.0), acc.1.max(a.1))
+                })),
+            _ => Err(self.unbounded(format!("unsupported function `{}`", identifier))),
+        }
+    }
+
+    fn unbounded(&self, reason: impl ToString) -> BencherError {
+        BencherError::UnboundedExpression(self.expr.to_string(), reason.to_string())
+    }
+}
+
+fn point(v: f64) -> Interval {
+    (v, v)
+}
+
+fn hull<const N: usize>(values: [f64; N]) -> Interval {
+    values
+        .into_iter()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(lower, upper), v| {
+            (lower.min(v), upper.max(v))
+        })
+}
+
+/// Arguments of a function call: either a tuple or a single expression
+fn function_arguments(node: &Node) -> &[Node] {
+    match (node.operator(), node.children()) {
+        (Operator::RootNode, [child]) => function_arguments(child),
+        (Operator::Tuple, children) => children,
+        _ => std::slice::from_ref(node),
+    }
+}
+
+fn interpolate(lower: Value, upper: Value, t: f64) -> Value {
+    match (lower, upper) {
+        (Value::Int(lower), Value::Int(upper)) => {
+            Value::Int(lower + ((upper - lower) as f64 * t).round() as i64)
+        }
+        _ => Value::Float(lower.as_float() + (upper.as_float() - lower.as_float()) * t),
+    }
+}
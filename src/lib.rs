@@ -1,22 +1,37 @@
+mod axis;
 mod bidimensional;
+mod compare;
 mod config;
 mod db;
 mod error;
 mod experiment_view;
 mod handles;
+mod history;
+mod import;
+mod interval;
 mod linear;
 mod model;
 mod parsed;
+mod render;
 pub mod stat;
+mod style;
+mod units;
 mod value_model;
 
+pub use axis::*;
 use bidimensional::*;
+pub use compare::*;
 pub use config::*;
 use db::*;
 pub use error::*;
 pub use experiment_view::*;
 pub use handles::*;
+pub use history::*;
+pub use import::*;
 use linear::*;
 pub use model::*;
-use parsed::*;
+pub use parsed::*;
+pub use render::*;
+pub use style::*;
+pub use units::*;
 pub use value_model::*;
@@ -1,4 +1,4 @@
-use std::collections::BTreeMap;
+use std::collections::BTreeSet;
 use std::fs::File;
 use std::io::Write;
 
@@ -21,10 +21,14 @@ pub(crate) struct LinearExperimentSet {
 
 pub struct LinearExperimentView {
     sets: Vec<LinearExperimentSet>,
-    magnitude: Magnitude,
+    scale: Scale,
     horizontal_label: String,
     v_label: String,
     v_units: String,
+    v_format: AxisFormat,
+    v_axis: AxisStyle,
+    histogram_style: HistogramStyle,
+    style: PlotStyle,
 }
 
 impl LinearExperimentView {
@@ -35,13 +39,18 @@ impl LinearExperimentView {
         if sets.len() == 0 {
             Err(BencherError::NoSets(experiment.exp_type.clone()))
         } else {
-            let magnitude = choose_magnitude(sets.iter());
+            let v_format = experiment.v_format.unwrap_or_default();
+            let scale = choose_scale(&experiment.v_units, sets.iter(), &v_format);
             Ok(Self {
                 sets,
-                magnitude,
+                scale,
                 horizontal_label: experiment.horizontal_label.clone(),
                 v_label: experiment.v_label.clone(),
                 v_units: experiment.v_units.clone(),
+                v_format,
+                v_axis: experiment.v_axis.unwrap_or_default(),
+                histogram_style: experiment.histogram_style.unwrap_or_default(),
+                style: PlotStyle::default(),
             })
         }
     }
@@ -53,20 +62,134 @@ impl LinearExperimentView {
         if sets.len() == 0 {
             Err(BencherError::NoSets(experiment.exp_type.clone()))
         } else {
-            let magnitude = choose_magnitude(sets.iter());
+            let v_format = experiment.v_format.unwrap_or_default();
+            let scale = choose_scale(&experiment.v_units, sets.iter(), &v_format);
             Ok(Self {
                 sets,
-                magnitude,
+                scale,
                 horizontal_label: experiment.horizontal_label.clone(),
                 v_label: experiment.v_label.clone(),
                 v_units: experiment.v_units.clone(),
+                v_format,
+                v_axis: experiment.v_axis.unwrap_or_default(),
+                histogram_style: experiment.histogram_style.unwrap_or_default(),
+                style: PlotStyle::default(),
             })
         }
     }
+
+    /// Override the format of the values (e.g., from the command line)
+    pub(crate) fn with_v_format(mut self, v_format: AxisFormat) -> Self {
+        self.v_format = self.v_format.overridden_by(v_format);
+        self.scale = choose_scale(&self.v_units, self.sets.iter(), &self.v_format);
+        self
+    }
+
+    /// Override the style of the value axis (e.g., from the command line)
+    pub(crate) fn with_v_axis(mut self, v_axis: AxisStyle) -> Self {
+        self.v_axis = self.v_axis.overridden_by(v_axis);
+        self
+    }
+
+    /// Override the histogram style (e.g., from the command line)
+    pub(crate) fn with_histogram_style(mut self, histogram_style: HistogramStyle) -> Self {
+        self.histogram_style = histogram_style;
+        self
+    }
+
+    /// Set the style of the plots (from the config and the command line)
+    pub(crate) fn with_style(mut self, style: PlotStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Error bars are only drawn on clustered histograms
+    fn check_bars(&self, bar: Bars) -> BencherResult<()> {
+        match bar {
+            Bars::Linear(_) if self.histogram_style.is_stacked() => {
+                Err(BencherError::StackedErrorBars(self.histogram_style))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Scale of the value axis, along with its default range
+    ///
+    /// Histograms start at zero, and percent-stacked ones end at 100%
+    fn v_axis_scale(&self) -> (Scale, (Option<f64>, Option<f64>)) {
+        match self.histogram_style {
+            HistogramStyle::PercentStacked => (Scale::unit(), (Some(0.0), Some(100.0))),
+            _ => (self.scale.clone(), (Some(0.0), None)),
+        }
+    }
+
+    /// Value axis drawn by the renderer
+    fn v_plot_axis(&self) -> PlotAxis {
+        let (scale, (default_min, default_max)) = self.v_axis_scale();
+        let mut axis = self.v_axis.plot_axis(&scale);
+        if !axis.log {
+            axis.min = axis.min.or(default_min);
+            axis.max = axis.max.or(default_max);
+        }
+        axis
+    }
+
+    /// Groups of the sets, in the order of the dat file
+    fn groups(&self) -> Vec<String> {
+        self.sets
+            .iter()
+            .flat_map(|set| set.values.iter().map(|d| d.group.clone()))
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect()
+    }
+
+    fn set_labels(&self) -> Vec<String> {
+        self.sets.iter().map(|set| set.set_label.clone()).collect()
+    }
+
+    /// Labels of the series of the histogram, in the order they are drawn:
+    /// the sets, or the groups of column-stacked histograms
+    fn series_labels(&self) -> Vec<String> {
+        match self.histogram_style {
+            HistogramStyle::Clustered | HistogramStyle::RowStacked => self.set_labels(),
+            HistogramStyle::ColumnStacked | HistogramStyle::PercentStacked => self.groups(),
+        }
+    }
+
+    /// Styles of the series with the given labels
+    fn series_styles(&self, labels: &[String]) -> Vec<SeriesStyle> {
+        let labels = labels.iter().map(String::as_str).collect::<Vec<_>>();
+        self.style
+            .series_styles(&labels, |slot| slot as u32 + 1, None)
+    }
+
+    /// Label of the value axis: in percent for percent-stacked histograms
+    fn v_axis_label(&self) -> String {
+        match self.histogram_style {
+            HistogramStyle::PercentStacked => format!("{} (%)", self.v_label),
+            _ => format!("{} ({})", self.v_label, self.scale.units()),
+        }
+    }
+}
+
+/// Share (in %) of a value in a total
+fn percent(v: &Value, total: f64) -> f64 {
+    if total == 0.0 {
+        0.0
+    } else {
+        100.0 * v.as_float() / total
+    }
+}
+
+/// Sum of the values of a set, to compute the share of each group
+fn set_total(set: &LinearExperimentSet) -> f64 {
+    set.values.iter().map(|d| d.v.as_float()).sum()
 }
 
 impl ExperimentView for LinearExperimentView {
     fn gnuplot(&self, prefix: &std::path::Path, bar: Bars) -> BencherResult<()> {
+        self.check_bars(bar)?;
         let mut gnu_path: std::path::PathBuf = prefix.into();
         if !gnu_path.set_extension("gnu") {
             return Err(BencherError::PathCreateError(gnu_path, "gnu".to_string()));
@@ -75,60 +198,82 @@ impl ExperimentView for LinearExperimentView {
         if !dat_path.set_extension("dat") {
             return Err(BencherError::PathCreateError(dat_path, "dat".to_string()));
         }
-        let mut eps_path: std::path::PathBuf = prefix.into();
-        if !eps_path.set_extension("eps") {
-            return Err(BencherError::PathCreateError(eps_path, "eps".to_string()));
-        }
+        let output_path = self.style.output_path(prefix)?;
 
         let mut file = File::create(&gnu_path).map_err(|e| {
             BencherError::io_err(e, format!("creating {}", gnu_path.to_string_lossy()))
         })?;
         write!(
             &mut file,
-            "reset
-
-set terminal postscript eps colour size 12cm,8cm enhanced font 'Helvetica,20'
-set output '{}'
-
-set border linewidth 0.75
-set key outside above
-set style data histogram
-",
-            eps_path.to_string_lossy()
+            "{}set style data histogram
+set datafile missing '?'
+{}",
+            self.style.gnuplot_preamble(&output_path),
+            PlotStyle::gnuplot_line_types(&self.series_styles(&self.series_labels())),
         )
         .map_err(|e| BencherError::io_err(e, "writing gnu to file"))?;
 
-        match bar {
-            Bars::Linear(_) => writeln!(file, "set style histogram cluster gap 1 errorbars lw 2")
-                .map_err(|e| BencherError::io_err(e, "writing gnu to file"))?,
-            _ => writeln!(file, "set style histogram cluster gap 1")
+        match (self.histogram_style, bar) {
+            (HistogramStyle::Clustered, Bars::Linear(_)) => {
+                writeln!(file, "set style histogram cluster gap 1 errorbars lw 2")
+                    .map_err(|e| BencherError::io_err(e, "writing gnu to file"))?
+            }
+            (HistogramStyle::Clustered, _) => {
+                writeln!(file, "set style histogram cluster gap 1")
+                    .map_err(|e| BencherError::io_err(e, "writing gnu to file"))?
+            }
+            (HistogramStyle::RowStacked, _) => writeln!(file, "set style histogram rowstacked")
                 .map_err(|e| BencherError::io_err(e, "writing gnu to file"))?,
+            (HistogramStyle::ColumnStacked | HistogramStyle::PercentStacked, _) => {
+                writeln!(file, "set style histogram columnstacked")
+                    .map_err(|e| BencherError::io_err(e, "writing gnu to file"))?
+            }
         }
 
+        let (scale, default_range) = self.v_axis_scale();
         write!(
             &mut file,
             "
 # set axis
 set style fill pattern 4 border rgb \"black\"
 set auto x
-set yrange [0:*]
-set ylabel '{} ({}{})'
+{}set ylabel '{}'
 ",
-            self.v_label,
-            self.magnitude.prefix(),
-            self.v_units,
+            self.v_axis.gnuplot("y", &scale, default_range),
+            self.v_axis_label(),
         )
         .map_err(|e| BencherError::io_err(e, "writing gnu to file"))?;
 
-        match bar {
-            Bars::Linear(_) => writeln!(
+        // column-stacked histograms take the legend from the groups (`key(1)`, given once)
+        // and the ticks from the titles of the columns
+        let last_column = 1 + self.sets.len();
+        match (self.histogram_style, bar) {
+            (HistogramStyle::ColumnStacked | HistogramStyle::PercentStacked, _)
+                if last_column == 2 =>
+            {
+                writeln!(
+                    &mut file,
+                    "plot '{}' using 2:key(1) title col(2)",
+                    dat_path.to_string_lossy()
+                )
+                .map_err(|e| BencherError::io_err(e, "writing gnu to file"))?
+            }
+            (HistogramStyle::ColumnStacked | HistogramStyle::PercentStacked, _) => writeln!(
+                &mut file,
+                "plot for [i=2:{}] '{1}' using i title col(i), '{1}' using {2}:key(1) title col({2})",
+                last_column - 1,
+                dat_path.to_string_lossy(),
+                last_column,
+            )
+            .map_err(|e| BencherError::io_err(e, "writing gnu to file"))?,
+            (_, Bars::Linear(_)) => writeln!(
                 &mut file,
                 "plot for [i=2:{}:3] '{}' using i:i+1:i+2:xtic(1) title col(i)",
                 2 + 3 * (self.sets.len() - 1),
                 dat_path.to_string_lossy()
             )
             .map_err(|e| BencherError::io_err(e, "writing gnu to file"))?,
-            _ => writeln!(
+            (_, _) => writeln!(
                 &mut file,
                 "plot for [i=2:{}:1] '{}' using i:xtic(1) title col(i)",
                 2 + self.sets.len() - 1,
@@ -141,6 +286,7 @@ set ylabel '{} ({}{})'
     }
 
     fn dat(&self, prefix: &std::path::Path, bar: Bars) -> BencherResult<()> {
+        self.check_bars(bar)?;
         let mut dat_path: std::path::PathBuf = prefix.into();
         if !dat_path.set_extension("dat") {
             return Err(BencherError::PathCreateError(dat_path, "dat".to_string()));
@@ -149,12 +295,11 @@ set ylabel '{} ({}{})'
             BencherError::io_err(e, format!("creating {}", dat_path.to_string_lossy()))
         })?;
 
-        let confidence_str = match bar {
-            Bars::Linear(c) => format!(
-                "confidence interval: {}% - {}%",
-                std::cmp::min(c, 100 - c),
-                std::cmp::max(c, 100 - c)
-            ),
+        let confidence_str = match (bar, self.histogram_style) {
+            (Bars::Linear(c), _) => {
+                format!("confidence interval: {}% - {}%", c.lower(), c.upper())
+            }
+            (_, HistogramStyle::PercentStacked) => "share of the total of each set (%)".to_string(),
             _ => "".to_string(),
         };
 
@@ -166,9 +311,6 @@ set ylabel '{} ({}{})'
         )
         .map_err(|e| BencherError::io_err(e, "writing dat to file"))?;
 
-        // table: mapping from group to values, ordered by what it matters
-        let mut group_values = BTreeMap::new();
-
         // header
         write!(
             &mut file,
@@ -190,31 +332,42 @@ set ylabel '{} ({}{})'
                 }
                 _ => {}
             }
-
-            for datapoint in &set.values {
-                let guard = group_values
-                    .entry(datapoint.group.clone())
-                    .or_insert(vec![]);
-                guard.push(datapoint.v.display_with_magnitude(self.magnitude));
-                match bar {
-                    Bars::Linear(confidence) => {
-                        let (min, max) = datapoint
-                            .get_confidence(confidence.try_into()?)
-                            .unwrap_or((datapoint.v.clone(), datapoint.v.clone()));
-                        guard.push(min.display_with_magnitude(self.magnitude));
-                        guard.push(max.display_with_magnitude(self.magnitude));
-                    }
-                    _ => {}
-                }
-            }
         }
 
-        for (group, values) in group_values {
+        // one row per group, with the cells of the sets without it marked as missing
+        let totals = self.sets.iter().map(set_total).collect::<Vec<_>>();
+        for group in self.groups() {
             write!(&mut file, "\n{:>34} ", format!("\"{}\"", group))
                 .map_err(|e| BencherError::io_err(e, "writing dat to file"))?;
-            for v in values {
-                write!(&mut file, "{:>34} ", v)
-                    .map_err(|e| BencherError::io_err(e, "writing dat to file"))?;
+
+            for (set, total) in self.sets.iter().zip(&totals) {
+                let datapoint = set.values.iter().find(|d| d.group == group);
+                let mut cells = vec![match datapoint {
+                    Some(datapoint) => match self.histogram_style {
+                        HistogramStyle::PercentStacked => {
+                            format!("{:.2}", percent(&datapoint.v, *total))
+                        }
+                        _ => datapoint.v.display_with_scale(&self.scale),
+                    },
+                    None => "?".to_string(),
+                }];
+                if let Bars::Linear(confidence) = bar {
+                    match datapoint {
+                        Some(datapoint) => {
+                            let (min, max) = datapoint
+                                .get_confidence(confidence)
+                                .unwrap_or((datapoint.v, datapoint.v));
+                            cells.push(min.display_with_scale(&self.scale));
+                            cells.push(max.display_with_scale(&self.scale));
+                        }
+                        None => cells.extend(["?".to_string(), "?".to_string()]),
+                    }
+                }
+
+                for cell in cells {
+                    write!(&mut file, "{:>34} ", cell)
+                        .map_err(|e| BencherError::io_err(e, "writing dat to file"))?;
+                }
             }
         }
 
@@ -223,6 +376,171 @@ set ylabel '{} ({}{})'
         Ok(())
     }
 
+    fn image(&self, prefix: &std::path::Path, format: ImageFormat, bar: Bars) -> BencherResult<()> {
+        self.check_bars(bar)?;
+
+        let groups = self.groups();
+
+        let sets = self
+            .sets
+            .iter()
+            .zip(self.series_styles(&self.set_labels()))
+            .map(|(set, style)| HistogramSet {
+                label: set.set_label.clone(),
+                style,
+                values: groups
+                    .iter()
+                    .map(|group| {
+                        let datapoint = set.values.iter().find(|d| &d.group == group)?;
+                        let bar = match bar {
+                            Bars::Linear(confidence) => {
+                                datapoint.get_confidence(confidence).map(|(min, max)| {
+                                    (
+                                        self.scale.scale(min.as_float()),
+                                        self.scale.scale(max.as_float()),
+                                    )
+                                })
+                            }
+                            _ => None,
+                        };
+                        Some(HistogramBar {
+                            v: self.scale.scale(datapoint.v.as_float()),
+                            bar,
+                        })
+                    })
+                    .collect(),
+            })
+            .collect::<Vec<_>>();
+
+        let axis_labels = (self.horizontal_label.as_str(), self.v_axis_label());
+        let y_axis = self.v_plot_axis();
+        match self.histogram_style {
+            HistogramStyle::Clustered => render_histogram(
+                prefix,
+                format,
+                (axis_labels.0, &axis_labels.1),
+                &groups,
+                &sets,
+                &y_axis,
+                &self.style,
+            ),
+            HistogramStyle::RowStacked => render_stacked_histogram(
+                prefix,
+                format,
+                (axis_labels.0, &axis_labels.1),
+                &groups,
+                &sets,
+                &y_axis,
+                &self.style,
+            ),
+            HistogramStyle::ColumnStacked | HistogramStyle::PercentStacked => {
+                // one bar per set, stacking its groups
+                let labels = self.set_labels();
+                let group_sets = groups
+                    .iter()
+                    .zip(self.series_styles(&groups))
+                    .map(|(group, style)| HistogramSet {
+                        label: group.clone(),
+                        style,
+                        values: self
+                            .sets
+                            .iter()
+                            .map(|set| {
+                                let datapoint = set.values.iter().find(|d| &d.group == group)?;
+                                let v = match self.histogram_style {
+                                    HistogramStyle::PercentStacked => {
+                                        percent(&datapoint.v, set_total(set))
+                                    }
+                                    _ => self.scale.scale(datapoint.v.as_float()),
+                                };
+                                Some(HistogramBar { v, bar: None })
+                            })
+                            .collect(),
+                    })
+                    .collect::<Vec<_>>();
+                // the ticks are the sets, while the groups are in the legend
+                render_stacked_histogram(
+                    prefix,
+                    format,
+                    ("", &axis_labels.1),
+                    &labels,
+                    &group_sets,
+                    &y_axis,
+                    &self.style,
+                )
+            }
+        }
+    }
+
+    fn json<W: Write>(&self, writer: &mut W) -> BencherResult<()> {
+        let sets = self
+            .sets
+            .iter()
+            .map(|set| {
+                serde_json::json!({
+                    "label": set.set_label,
+                    "values": set.values.iter().map(|d| serde_json::json!({
+                        "group": d.group,
+                        "v": json_value(&d.v),
+                        "confidences": json_confidences(&d.v_confidence),
+                    })).collect::<Vec<_>>(),
+                })
+            })
+            .collect::<Vec<_>>();
+
+        write_json(
+            writer,
+            &serde_json::json!({
+                "horizontal_label": self.horizontal_label,
+                "v_label": self.v_label,
+                "v_units": self.v_units,
+                "display_magnitude": self.scale.prefix(),
+                "display_units": self.scale.units(),
+                "sets": sets,
+            }),
+        )
+    }
+
+    fn csv<W: Write>(&self, writer: &mut W) -> BencherResult<()> {
+        let confidences = self
+            .sets
+            .iter()
+            .flat_map(|set| set.values.iter())
+            .flat_map(|d| d.v_confidence.keys().copied())
+            .collect::<BTreeSet<_>>();
+
+        let mut csv_writer = csv::Writer::from_writer(writer);
+        let mut header = vec![
+            "set".to_string(),
+            "group".to_string(),
+            "v".to_string(),
+            "units".to_string(),
+            "display_magnitude".to_string(),
+            "display_units".to_string(),
+        ];
+        header.extend(csv_confidence_header(&confidences, ""));
+        csv_writer.write_record(&header)?;
+
+        for set in &self.sets {
+            for d in &set.values {
+                let mut record = vec![
+                    set.set_label.clone(),
+                    d.group.clone(),
+                    d.v.to_string(),
+                    self.v_units.clone(),
+                    self.scale.prefix().to_string(),
+                    self.scale.units(),
+                ];
+                record.extend(csv_confidence_cells(&d.v_confidence, &confidences));
+                csv_writer.write_record(&record)?;
+            }
+        }
+
+        csv_writer
+            .flush()
+            .map_err(|e| BencherError::io_err(e, "writing csv"))
+    }
+
     fn table<W: Write>(&self, writer: &mut W) -> BencherResult<()> {
         let mut rows = Vec::new();
         for set in &self.sets {
@@ -232,7 +550,7 @@ set ylabel '{} ({}{})'
                     datapoint.group.clone().cell().justify(Justify::Right),
                     datapoint
                         .v
-                        .display_with_magnitude(self.magnitude)
+                        .display_with_scale(&self.scale)
                         .cell()
                         .justify(Justify::Right),
                 ]
@@ -244,15 +562,10 @@ set ylabel '{} ({}{})'
             .title(vec![
                 "Set".cell().justify(Justify::Center).bold(true),
                 "Group".cell().justify(Justify::Center).bold(true),
-                format!(
-                    "{} ({}{})",
-                    self.v_label,
-                    self.magnitude.prefix(),
-                    self.v_units
-                )
-                .cell()
-                .justify(Justify::Center)
-                .bold(true),
+                format!("{} ({})", self.v_label, self.scale.units(),)
+                    .cell()
+                    .justify(Justify::Center)
+                    .bold(true),
             ])
             .bold(true);
 
@@ -269,11 +582,10 @@ set ylabel '{} ({}{})'
             writeln!(writer, "\\begin{{table}}[t]\n    \\centering\n    \\begin{{tabular}}{{|r|r|}}\n        \\hline").map_err(|e| BencherError::io_err(e, "writing latex table"))?;
             writeln!(
                 writer,
-                "        \\textbf{{ {} }} & \\textbf{{ {} ({}{}) }} \\\\ \\hline",
+                "        \\textbf{{ {} }} & \\textbf{{ {} ({}) }} \\\\ \\hline",
                 set.set_label,
                 self.v_label,
-                self.magnitude.prefix(),
-                self.v_units,
+                self.scale.units(),
             )
             .map_err(|e| BencherError::io_err(e, "writing latex table"))?;
             for datapoint in &set.values {
@@ -281,7 +593,7 @@ set ylabel '{} ({}{})'
                     writer,
                     "        ${:>8}$ & ${:>8}$ \\\\ \\hline",
                     datapoint.group,
-                    datapoint.v.display_with_magnitude(self.magnitude)
+                    datapoint.v.display_with_scale(&self.scale)
                 )
                 .map_err(|e| BencherError::io_err(e, "writing latex table"))?
             }
@@ -293,36 +605,167 @@ set ylabel '{} ({}{})'
     }
 }
 
-/// Choose a magnitude based on a given iterator of LinearExperimentSets
-fn choose_magnitude<'a>(sets: impl Iterator<Item = &'a LinearExperimentSet>) -> Magnitude {
-    let mut magnitude_counts = [0; 7];
-
-    sets.for_each(|set| {
-        set.values.iter().for_each(|d| match d.magnitude() {
-            Magnitude::Nano => magnitude_counts[0] += 1,
-            Magnitude::Micro => magnitude_counts[1] += 1,
-            Magnitude::Mili => magnitude_counts[2] += 1,
-            Magnitude::Normal => magnitude_counts[3] += 1,
-            Magnitude::Kilo => magnitude_counts[4] += 1,
-            Magnitude::Mega => magnitude_counts[5] += 1,
-            Magnitude::Giga => magnitude_counts[6] += 1,
-        })
-    });
-
-    let idx = magnitude_counts
-        .iter()
-        .enumerate()
-        .max_by_key(|v| v.1)
-        .map(|(idx, c)| if *c > 0 { idx } else { 3 })
+/// Choose the scale of the values of a given iterator of LinearExperimentSets, in a given format
+fn choose_scale<'a>(
+    units: &str,
+    sets: impl Iterator<Item = &'a LinearExperimentSet>,
+    format: &AxisFormat,
+) -> Scale {
+    Scale::choose(
+        units,
+        sets.flat_map(|set| set.values.iter().map(|d| d.v)),
+        format,
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// View of a `lat` experiment with sets A (get and put) and B (get, put and scan)
+    fn view(histogram_style: HistogramStyle) -> LinearExperimentView {
+        let experiment: LinearExperiment = serde_json::from_value(serde_json::json!({
+            "exp_type": "lat",
+            "horizontal_label": "op",
+            "v_label": "latency",
+            "v_units": "",
+        }))
+        .unwrap();
+        let set = |set_label: &str, values: &[(&str, i64)]| LinearExperimentSet {
+            values: values
+                .iter()
+                .map(|&(group, v)| LinearDatapoint::new(group, Value::Int(v)))
+                .collect(),
+            set_label: set_label.to_string(),
+        };
+        LinearExperimentView::from_linear(
+            &experiment,
+            vec![
+                set("A", &[("put", 3), ("get", 1)]),
+                set("B", &[("get", 2), ("scan", 4), ("put", 2)]),
+            ],
+        )
+        .unwrap()
+        .with_histogram_style(histogram_style)
+    }
+
+    /// Rows of the dat file of a view, split in cells (without the comments)
+    fn dat_rows(view: &LinearExperimentView, name: &str, bar: Bars) -> Vec<Vec<String>> {
+        let prefix = std::env::temp_dir().join(format!("bencher-{}-{}", std::process::id(), name));
+        view.dat(&prefix, bar).unwrap();
+        let dat_path = prefix.with_extension("dat");
+        let contents = std::fs::read_to_string(&dat_path).unwrap();
+        std::fs::remove_file(&dat_path).unwrap();
+
+        contents
+            .lines()
+            .filter(|line| !line.starts_with('#'))
+            .map(|line| line.split_whitespace().map(str::to_string).collect())
+            .collect()
+    }
+
+    fn row(cells: &[&str]) -> Vec<String> {
+        cells.iter().map(|c| c.to_string()).collect()
+    }
+
+    #[test]
+    fn stacked_dat_columns() {
+        for style in [HistogramStyle::RowStacked, HistogramStyle::ColumnStacked] {
+            // a column per set (in order) and a row per group (sorted)
+            assert_eq!(
+                dat_rows(&view(style), "stacked-columns.dat", Bars::None),
+                vec![
+                    row(&["\"op\"", "\"A\"", "\"B\""]),
+                    row(&["\"get\"", "1", "2"]),
+                    row(&["\"put\"", "3", "2"]),
+                    row(&["\"scan\"", "?", "4"]),
+                ],
+                "{}",
+                style
+            );
+        }
+    }
+
+    #[test]
+    fn percent_stacked_dat() {
+        // each set is normalized to its own total
+        assert_eq!(
+            dat_rows(
+                &view(HistogramStyle::PercentStacked),
+                "percent-stacked.dat",
+                Bars::None
+            ),
+            vec![
+                row(&["\"op\"", "\"A\"", "\"B\""]),
+                row(&["\"get\"", "25.00", "25.00"]),
+                row(&["\"put\"", "75.00", "25.00"]),
+                row(&["\"scan\"", "?", "50.00"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn clustered_dat_missing_cells() {
+        // the confidence columns of missing datapoints are missing as well
+        assert_eq!(
+            dat_rows(
+                &view(HistogramStyle::Clustered),
+                "clustered-missing.dat",
+                Bars::Linear(Confidence::FIVE)
+            ),
+            vec![
+                row(&["\"op\"", "\"A\"", "\"min\"", "\"max\"", "\"B\"", "\"min\"", "\"max\""]),
+                row(&["\"get\"", "1", "1", "1", "2", "2", "2"]),
+                row(&["\"put\"", "3", "3", "3", "2", "2", "2"]),
+                row(&["\"scan\"", "?", "?", "?", "4", "4", "4"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn stacked_error_bars() {
+        assert!(matches!(
+            view(HistogramStyle::ColumnStacked).dat(
+                &std::env::temp_dir().join("bencher-stacked-bars"),
+                Bars::Linear(Confidence::FIVE)
+            ),
+            Err(BencherError::StackedErrorBars(
+                HistogramStyle::ColumnStacked
+            ))
+        ));
+    }
+
+    #[test]
+    fn exports_keep_values_unscaled() {
+        let experiment: LinearExperiment = serde_json::from_value(serde_json::json!({
+            "exp_type": "lat",
+            "horizontal_label": "op",
+            "v_label": "latency",
+            "v_units": "ns",
+        }))
+        .unwrap();
+        let view = LinearExperimentView::from_linear(
+            &experiment,
+            vec![LinearExperimentSet {
+                values: vec![LinearDatapoint::new("get", Value::Int(3_000_000))],
+                set_label: "A".to_string(),
+            }],
+        )
         .unwrap();
 
-    match idx {
-        0 => Magnitude::Nano,
-        1 => Magnitude::Micro,
-        2 => Magnitude::Mili,
-        3 => Magnitude::Normal,
-        4 => Magnitude::Kilo,
-        5 => Magnitude::Mega,
-        _ => Magnitude::Giga,
+        let mut json = Vec::new();
+        view.json(&mut json).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&json).unwrap();
+        assert_eq!(json["v_units"], "ns");
+        assert_eq!(json["display_magnitude"], "m");
+        assert_eq!(json["display_units"], "ms");
+        assert_eq!(json["sets"][0]["values"][0]["v"], 3_000_000);
+
+        let mut csv = Vec::new();
+        view.csv(&mut csv).unwrap();
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "set,group,v,units,display_magnitude,display_units\nA,get,3000000,ns,m,ms\n"
+        );
     }
 }
@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 pub enum Axis {
     X,
     Y,
@@ -11,6 +13,152 @@ pub struct ExperimentStatus {
     pub exp_code: String,
     pub n_datapoints: usize,
     pub n_active_datapoints: usize,
+
+    /// Metadata of the most recently added datapoint version
+    pub last_run: Option<RunMetadata>,
+}
+
+/// Metadata about the run which produced a datapoint version
+#[derive(Debug, Clone, Default, Hash, PartialEq, Eq)]
+pub struct RunMetadata {
+    /// Seconds since the Unix epoch; set to the time of insertion if missing
+    pub timestamp: Option<i64>,
+    pub git_revision: Option<String>,
+    pub hostname: Option<String>,
+
+    /// Free-form key/value metadata
+    pub values: BTreeMap<String, String>,
+}
+
+impl RunMetadata {
+    /// Metadata of the current run: the current time, the hostname
+    /// and the git revision of the current directory (if any)
+    pub fn current() -> Self {
+        RunMetadata {
+            timestamp: Some(now_timestamp()),
+            git_revision: command_output("git", &["rev-parse", "HEAD"]),
+            hostname: std::env::var("HOSTNAME")
+                .ok()
+                .filter(|h| !h.is_empty())
+                .or_else(|| command_output("hostname", &[])),
+            values: BTreeMap::new(),
+        }
+    }
+
+    pub fn timestamp(mut self, timestamp: i64) -> Self {
+        self.timestamp = Some(timestamp);
+        self
+    }
+
+    pub fn git_revision(mut self, git_revision: impl ToString) -> Self {
+        self.git_revision = Some(git_revision.to_string());
+        self
+    }
+
+    pub fn hostname(mut self, hostname: impl ToString) -> Self {
+        self.hostname = Some(hostname.to_string());
+        self
+    }
+
+    pub fn value(mut self, key: impl ToString, value: impl ToString) -> Self {
+        self.values.insert(key.to_string(), value.to_string());
+        self
+    }
+
+    /// Timestamp as a UTC date and time (YYYY-MM-DD HH:MM:SS)
+    pub fn datetime(&self) -> Option<String> {
+        let timestamp = self.timestamp?;
+        let (days, secs) = (timestamp.div_euclid(86400), timestamp.rem_euclid(86400));
+
+        // civil date from days since the epoch (proleptic gregorian calendar)
+        let z = days + 719468;
+        let era = z.div_euclid(146097);
+        let doe = z.rem_euclid(146097);
+        let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = doy - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+        Some(format!(
+            "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+            year,
+            month,
+            day,
+            secs / 3600,
+            secs % 3600 / 60,
+            secs % 60
+        ))
+    }
+}
+
+/// A version of a datapoint, along with the run that produced it
+#[derive(Debug, Clone)]
+pub struct DatapointVersion<D> {
+    /// Versions are numbered from 1, in the order they were added
+    pub version: usize,
+
+    pub status: VersionStatus,
+    pub datapoint: D,
+    pub run: RunMetadata,
+}
+
+/// Status of a version of a datapoint
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VersionStatus {
+    /// The version shown by views
+    Active,
+
+    /// A version replaced by a later one
+    Superseded,
+
+    /// A version which was reverted
+    Reverted,
+}
+
+impl VersionStatus {
+    /// Status of a version, as stored in the database (reverted versions are negative),
+    /// given the greatest stored version of its datapoint
+    pub(crate) fn new(version: isize, active_version: Option<isize>) -> Self {
+        if Some(version) == active_version {
+            VersionStatus::Active
+        } else if version > 0 {
+            VersionStatus::Superseded
+        } else {
+            VersionStatus::Reverted
+        }
+    }
+}
+
+impl std::fmt::Display for VersionStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            VersionStatus::Active => write!(f, "active"),
+            VersionStatus::Superseded => write!(f, "superseded"),
+            VersionStatus::Reverted => write!(f, "reverted"),
+        }
+    }
+}
+
+/// Seconds since the Unix epoch
+pub(crate) fn now_timestamp() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+/// Trimmed stdout of a command, if it succeeds
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = std::process::Command::new(program)
+        .args(args)
+        .stderr(std::process::Stdio::null())
+        .output()
+        .ok()?;
+    let stdout = String::from_utf8(output.stdout).ok()?;
+    let stdout = stdout.trim();
+    (output.status.success() && !stdout.is_empty()).then(|| stdout.to_string())
 }
 
 #[derive(Debug, Hash, PartialEq, Eq)]
@@ -43,30 +191,61 @@ pub struct Selector {
     exp_code_include: Vec<regex::Regex>,
     exp_type_exclude: Vec<regex::Regex>,
     exp_type_include: Vec<regex::Regex>,
+    git_revision_include: Vec<regex::Regex>,
+    hostname_include: Vec<regex::Regex>,
+    metadata_include: Vec<(String, regex::Regex)>,
 }
 
 impl Selector {
     pub(crate) fn filter_code(&self, exp_code: &str) -> bool {
-        let res = (self.exp_code_exclude.len() == 0
+        (self.exp_code_exclude.is_empty()
             || !self.exp_code_exclude.iter().any(|re| re.is_match(exp_code)))
-            && (self.exp_code_include.len() == 0
-                || self.exp_code_include.iter().any(|re| re.is_match(exp_code)));
-
-        res
+            && (self.exp_code_include.is_empty()
+                || self.exp_code_include.iter().any(|re| re.is_match(exp_code)))
     }
 
     pub(crate) fn filter_type(&self, exp_type: &str) -> bool {
-        (self.exp_type_exclude.len() == 0
+        (self.exp_type_exclude.is_empty()
             || !self.exp_type_exclude.iter().any(|re| re.is_match(exp_type)))
-            && (self.exp_type_include.len() == 0
+            && (self.exp_type_include.is_empty()
                 || self.exp_type_include.iter().any(|re| re.is_match(exp_type)))
     }
+
+    /// Whether any filter applies to the run metadata
+    pub(crate) fn filters_metadata(&self) -> bool {
+        !self.git_revision_include.is_empty()
+            || !self.hostname_include.is_empty()
+            || !self.metadata_include.is_empty()
+    }
+
+    /// An experiment is selected if the metadata of one of its runs matches every filter
+    pub(crate) fn filter_metadata(&self, metadata: &RunMetadata) -> bool {
+        fn matches(res: &[regex::Regex], value: &Option<String>) -> bool {
+            res.is_empty()
+                || value
+                    .as_ref()
+                    .is_some_and(|v| res.iter().any(|re| re.is_match(v)))
+        }
+
+        matches(&self.git_revision_include, &metadata.git_revision)
+            && matches(&self.hostname_include, &metadata.hostname)
+            && self
+                .metadata_include
+                .iter()
+                .all(|(key, re)| metadata.values.get(key).is_some_and(|v| re.is_match(v)))
+    }
 }
 
 pub struct SelectorBuilder {
     selector: Selector,
 }
 
+impl Default for SelectorBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl SelectorBuilder {
     pub fn new() -> Self {
         SelectorBuilder {
@@ -94,6 +273,22 @@ impl SelectorBuilder {
         self
     }
 
+    pub fn git_revision_include(mut self, re: regex::Regex) -> Self {
+        self.selector.git_revision_include.push(re);
+        self
+    }
+
+    pub fn hostname_include(mut self, re: regex::Regex) -> Self {
+        self.selector.hostname_include.push(re);
+        self
+    }
+
+    /// Select runs where the metadata value of `key` matches
+    pub fn metadata_include(mut self, key: impl ToString, re: regex::Regex) -> Self {
+        self.selector.metadata_include.push((key.to_string(), re));
+        self
+    }
+
     pub fn build(self) -> Selector {
         self.selector
     }
@@ -120,3 +315,25 @@ impl Sorter {
         self.regex.len()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn version_status() {
+        // versions 1 and 2 were added, 3 was added and reverted
+        let active_version = [1, 2, -3].into_iter().max();
+        assert_eq!(active_version, Some(2));
+        assert_eq!(
+            VersionStatus::new(1, active_version),
+            VersionStatus::Superseded
+        );
+        assert_eq!(VersionStatus::new(2, active_version), VersionStatus::Active);
+        assert_eq!(
+            VersionStatus::new(-3, active_version),
+            VersionStatus::Reverted
+        );
+        assert_eq!(VersionStatus::Superseded.to_string(), "superseded");
+    }
+}
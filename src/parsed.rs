@@ -1,6 +1,5 @@
 use crate::*;
-use std::fs::File;
-use std::io::BufReader;
+use std::collections::{BTreeMap, HashMap};
 
 /// A linear experiment represents a histogram
 ///
@@ -8,83 +7,1322 @@ use std::io::BufReader;
 /// Example: if the histogram is latency per operation,
 /// and there are two labels (A and B) and two operations (get and put),
 /// the groups are put/get
-#[derive(serde::Deserialize, Clone, PartialEq, Eq, Debug, Hash)]
+#[derive(serde::Deserialize, serde::Serialize, Clone, PartialEq, Eq, Debug, Hash)]
 pub struct LinearExperiment {
     pub(crate) exp_type: String,
     pub(crate) horizontal_label: String,
     pub(crate) v_label: String,
     pub(crate) v_units: String,
+
+    /// Whether larger values are better (e.g., throughput), for comparisons
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) higher_is_better: bool,
+
+    /// How the sets are laid out in plots (clustered by default)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) histogram_style: Option<HistogramStyle>,
+
+    /// How the values are formatted (magnitude, precision or significant figures)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) v_format: Option<AxisFormat>,
+
+    /// How the value axis is drawn in plots (logarithmic, range, ticks, reversal)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) v_axis: Option<AxisStyle>,
 }
 
 /// A bidimensional (xy) experiment represents a line graph
 ///
 /// The tag is an id for a point in a line
 /// Additionally, a line has a label
-#[derive(serde::Deserialize, Clone, PartialEq, Eq, Debug, Hash)]
+#[derive(serde::Deserialize, serde::Serialize, Clone, PartialEq, Eq, Debug, Hash)]
 pub struct XYExperiment {
     pub(crate) exp_type: String,
     pub(crate) x_label: String,
     pub(crate) x_units: String,
     pub(crate) y_label: String,
     pub(crate) y_units: String,
+
+    /// Whether larger y values are better (e.g., throughput), for comparisons
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) higher_is_better: bool,
+
+    /// How the x values are formatted (magnitude, precision or significant figures)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) x_format: Option<AxisFormat>,
+
+    /// How the y values are formatted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) y_format: Option<AxisFormat>,
+
+    /// How the x axis is drawn in plots (logarithmic, range, ticks, reversal)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) x_axis: Option<AxisStyle>,
+
+    /// How the y axis is drawn in plots
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) y_axis: Option<AxisStyle>,
 }
 
 /// A virtual linear experiment
 ///
-/// This takes an existing linear experiment and performs an operation on the each value
-#[derive(serde::Deserialize, Clone, PartialEq, Eq, Debug, Hash)]
+/// This takes existing linear experiments and performs an operation on the each value
+///
+/// The first source is the primary one (`v` in the expressions): its datapoints are joined with
+/// the ones of the other sources with the same set label and group, which are available in the
+/// expressions by the source experiment type
+#[derive(serde::Deserialize, serde::Serialize, Clone, PartialEq, Eq, Debug, Hash)]
 pub struct VirtualLinearExperiment {
     pub(crate) exp_type: String,
-    pub(crate) source_exp_type: String, // TODO: make this a vector
+    #[serde(
+        rename = "source_exp_type",
+        alias = "source_exp_types",
+        deserialize_with = "one_or_many",
+        serialize_with = "serialize_one_or_many"
+    )]
+    pub(crate) source_exp_types: Vec<String>,
     pub(crate) horizontal_label: String,
     pub(crate) v_label: String,
     pub(crate) v_units: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub(crate) v_operation: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub(crate) tag_operation: Option<String>,
+
+    /// Monotonicity of the v operation, to map the confidence intervals (detected by default)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) v_monotonicity: Option<Monotonicity>,
+
+    /// Label of a set of the primary source whose datapoints are available in the expressions
+    /// as `baseline` (joined by group), e.g., to compute speedups
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) baseline_set: Option<String>,
+
+    /// How the sets are laid out in plots (clustered by default)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) histogram_style: Option<HistogramStyle>,
+
+    /// How the values are formatted (magnitude, precision or significant figures)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) v_format: Option<AxisFormat>,
+
+    /// How the value axis is drawn in plots (logarithmic, range, ticks, reversal)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) v_axis: Option<AxisStyle>,
 }
 
 /// A virtual bidimensional (xy) experiment
 ///
-/// This takes an existing xy experiment and performs an operation
+/// This takes existing xy experiments and performs an operation
 /// on the xy values
-#[derive(serde::Deserialize, Clone, PartialEq, Eq, Debug, Hash)]
+///
+/// The first source is the primary one (`x` and `y` in the expressions): its datapoints are joined
+/// with the ones of the other sources with the same line label and tag, which are available
+/// in the expressions as `<exp_type>_x` and `<exp_type>_y`.
+/// If the primary source is linear, the other sources must be linear as well: they are joined
+/// by set label and group, and are available by the source experiment type
+#[derive(serde::Deserialize, serde::Serialize, Clone, PartialEq, Eq, Debug, Hash)]
 pub struct VirtualXYExperiment {
     pub(crate) exp_type: String,
-    pub(crate) source_exp_type: String, // TODO: make this a vector
+    #[serde(
+        rename = "source_exp_type",
+        alias = "source_exp_types",
+        deserialize_with = "one_or_many",
+        serialize_with = "serialize_one_or_many"
+    )]
+    pub(crate) source_exp_types: Vec<String>,
     pub(crate) x_label: String,
     pub(crate) x_units: String,
     pub(crate) y_label: String,
     pub(crate) y_units: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub(crate) x_operation: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub(crate) y_operation: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub(crate) tag_operation: Option<String>,
+
+    /// Monotonicity of the x operation, to map the confidence intervals (detected by default)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) x_monotonicity: Option<Monotonicity>,
+
+    /// Monotonicity of the y operation, to map the confidence intervals (detected by default)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) y_monotonicity: Option<Monotonicity>,
+
+    /// Label of a set (i.e., line) of the primary source whose datapoints are available
+    /// in the expressions as `baseline_x` and `baseline_y` (joined by tag), e.g., to compute speedups
+    /// If the primary source is linear, its datapoints are available as `baseline` (joined by group)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) baseline_set: Option<String>,
+
+    /// How the x values are formatted (magnitude, precision or significant figures)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) x_format: Option<AxisFormat>,
+
+    /// How the y values are formatted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) y_format: Option<AxisFormat>,
+
+    /// How the x axis is drawn in plots (logarithmic, range, ticks, reversal)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) x_axis: Option<AxisStyle>,
+
+    /// How the y axis is drawn in plots
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) y_axis: Option<AxisStyle>,
+}
+
+/// Monotonicity of an operation of a virtual experiment
+///
+/// The confidence intervals of monotonic operations are mapped through their bounds,
+/// while the ones of non-monotonic operations are mapped with interval arithmetic
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, PartialEq, Eq, Debug, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Monotonicity {
+    /// Non-decreasing in every variable (e.g., `v * 2`)
+    Increasing,
+
+    /// Non-increasing in every variable (e.g., `1.0 / v`)
+    Decreasing,
+
+    /// Either non-decreasing or non-increasing in each variable (e.g., `v / baseline`)
+    Monotonic,
+
+    /// None of the above (e.g., `(v - avg)^2`)
+    NonMonotonic,
+}
+
+impl std::str::FromStr for Monotonicity {
+    type Err = BencherError;
+    fn from_str(s: &str) -> BencherResult<Monotonicity> {
+        match s.to_lowercase().replace('-', "_").as_str() {
+            "increasing" => Ok(Monotonicity::Increasing),
+            "decreasing" => Ok(Monotonicity::Decreasing),
+            "monotonic" => Ok(Monotonicity::Monotonic),
+            "non_monotonic" => Ok(Monotonicity::NonMonotonic),
+            _ => Err(BencherError::UnknownMonotonicity(s.to_string())),
+        }
+    }
+}
+
+/// How the sets of a linear experiment are laid out in a histogram
+///
+/// In every style, the groups are the rows of the dat file and the sets are its columns
+#[derive(
+    serde::Deserialize, serde::Serialize, Clone, Copy, Default, PartialEq, Eq, Debug, Hash,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum HistogramStyle {
+    /// One cluster per group, with a bar per set
+    #[default]
+    #[serde(alias = "cluster")]
+    Clustered,
+
+    /// One bar per group, stacking the values of the sets
+    #[serde(alias = "rowstacked")]
+    RowStacked,
+
+    /// One bar per set, stacking the values of its groups (e.g., a latency breakdown)
+    #[serde(alias = "columnstacked")]
+    ColumnStacked,
+
+    /// One bar per set, stacking the share (in %) of the total of the set of each of its groups
+    #[serde(alias = "percentstacked", alias = "percent")]
+    PercentStacked,
+}
+
+impl HistogramStyle {
+    pub(crate) fn is_stacked(&self) -> bool {
+        *self != HistogramStyle::Clustered
+    }
+}
+
+impl std::fmt::Display for HistogramStyle {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            HistogramStyle::Clustered => write!(f, "clustered"),
+            HistogramStyle::RowStacked => write!(f, "row_stacked"),
+            HistogramStyle::ColumnStacked => write!(f, "column_stacked"),
+            HistogramStyle::PercentStacked => write!(f, "percent_stacked"),
+        }
+    }
+}
+
+impl std::str::FromStr for HistogramStyle {
+    type Err = BencherError;
+    fn from_str(s: &str) -> BencherResult<HistogramStyle> {
+        match s.to_lowercase().replace('-', "_").as_str() {
+            "clustered" | "cluster" => Ok(HistogramStyle::Clustered),
+            "row_stacked" | "rowstacked" => Ok(HistogramStyle::RowStacked),
+            "column_stacked" | "columnstacked" => Ok(HistogramStyle::ColumnStacked),
+            "percent_stacked" | "percentstacked" | "percent" => Ok(HistogramStyle::PercentStacked),
+            _ => Err(BencherError::UnknownHistogramStyle(s.to_string())),
+        }
+    }
+}
+
+impl LinearExperiment {
+    pub fn new(
+        exp_type: impl Into<String>,
+        horizontal_label: impl Into<String>,
+        v_label: impl Into<String>,
+        v_units: impl Into<String>,
+    ) -> Self {
+        LinearExperiment {
+            exp_type: exp_type.into(),
+            horizontal_label: horizontal_label.into(),
+            v_label: v_label.into(),
+            v_units: v_units.into(),
+            higher_is_better: false,
+            histogram_style: None,
+            v_format: None,
+            v_axis: None,
+        }
+    }
+
+    pub fn higher_is_better(mut self, higher_is_better: bool) -> Self {
+        self.higher_is_better = higher_is_better;
+        self
+    }
+
+    pub fn v_format(mut self, v_format: Option<AxisFormat>) -> Self {
+        self.v_format = v_format;
+        self
+    }
+
+    pub fn histogram_style(mut self, histogram_style: Option<HistogramStyle>) -> Self {
+        self.histogram_style = histogram_style;
+        self
+    }
+
+    pub fn v_axis(mut self, v_axis: Option<AxisStyle>) -> Self {
+        self.v_axis = v_axis;
+        self
+    }
+}
+
+impl XYExperiment {
+    pub fn new(
+        exp_type: impl Into<String>,
+        x_label: impl Into<String>,
+        x_units: impl Into<String>,
+        y_label: impl Into<String>,
+        y_units: impl Into<String>,
+    ) -> Self {
+        XYExperiment {
+            exp_type: exp_type.into(),
+            x_label: x_label.into(),
+            x_units: x_units.into(),
+            y_label: y_label.into(),
+            y_units: y_units.into(),
+            higher_is_better: false,
+            x_format: None,
+            y_format: None,
+            x_axis: None,
+            y_axis: None,
+        }
+    }
+
+    pub fn higher_is_better(mut self, higher_is_better: bool) -> Self {
+        self.higher_is_better = higher_is_better;
+        self
+    }
+
+    pub fn x_format(mut self, x_format: Option<AxisFormat>) -> Self {
+        self.x_format = x_format;
+        self
+    }
+
+    pub fn y_format(mut self, y_format: Option<AxisFormat>) -> Self {
+        self.y_format = y_format;
+        self
+    }
+
+    pub fn x_axis(mut self, x_axis: Option<AxisStyle>) -> Self {
+        self.x_axis = x_axis;
+        self
+    }
+
+    pub fn y_axis(mut self, y_axis: Option<AxisStyle>) -> Self {
+        self.y_axis = y_axis;
+        self
+    }
+}
+
+impl VirtualLinearExperiment {
+    pub fn new(
+        exp_type: impl Into<String>,
+        source_exp_types: Vec<String>,
+        horizontal_label: impl Into<String>,
+        v_label: impl Into<String>,
+        v_units: impl Into<String>,
+    ) -> Self {
+        VirtualLinearExperiment {
+            exp_type: exp_type.into(),
+            source_exp_types,
+            horizontal_label: horizontal_label.into(),
+            v_label: v_label.into(),
+            v_units: v_units.into(),
+            v_operation: None,
+            tag_operation: None,
+            v_monotonicity: None,
+            baseline_set: None,
+            histogram_style: None,
+            v_format: None,
+            v_axis: None,
+        }
+    }
+
+    pub fn v_operation(mut self, v_operation: Option<String>) -> Self {
+        self.v_operation = v_operation;
+        self
+    }
+
+    pub fn tag_operation(mut self, tag_operation: Option<String>) -> Self {
+        self.tag_operation = tag_operation;
+        self
+    }
+
+    pub fn v_monotonicity(mut self, v_monotonicity: Option<Monotonicity>) -> Self {
+        self.v_monotonicity = v_monotonicity;
+        self
+    }
+
+    pub fn baseline_set(mut self, baseline_set: Option<String>) -> Self {
+        self.baseline_set = baseline_set;
+        self
+    }
+
+    pub fn v_format(mut self, v_format: Option<AxisFormat>) -> Self {
+        self.v_format = v_format;
+        self
+    }
+
+    pub fn histogram_style(mut self, histogram_style: Option<HistogramStyle>) -> Self {
+        self.histogram_style = histogram_style;
+        self
+    }
+
+    pub fn v_axis(mut self, v_axis: Option<AxisStyle>) -> Self {
+        self.v_axis = v_axis;
+        self
+    }
+
+    /// The v operation, as evaluated (see `expression`)
+    pub(crate) fn v_expression(&self) -> Option<String> {
+        expression(&self.v_operation)
+    }
+
+    /// The tag operation, as evaluated (see `expression`)
+    pub(crate) fn tag_expression(&self) -> Option<String> {
+        expression(&self.tag_operation)
+    }
+}
+
+impl VirtualXYExperiment {
+    pub fn new(
+        exp_type: impl Into<String>,
+        source_exp_types: Vec<String>,
+        x_label: impl Into<String>,
+        x_units: impl Into<String>,
+        y_label: impl Into<String>,
+        y_units: impl Into<String>,
+    ) -> Self {
+        VirtualXYExperiment {
+            exp_type: exp_type.into(),
+            source_exp_types,
+            x_label: x_label.into(),
+            x_units: x_units.into(),
+            y_label: y_label.into(),
+            y_units: y_units.into(),
+            x_operation: None,
+            y_operation: None,
+            tag_operation: None,
+            x_monotonicity: None,
+            y_monotonicity: None,
+            baseline_set: None,
+            x_format: None,
+            y_format: None,
+            x_axis: None,
+            y_axis: None,
+        }
+    }
+
+    pub fn x_operation(mut self, x_operation: Option<String>) -> Self {
+        self.x_operation = x_operation;
+        self
+    }
+
+    pub fn y_operation(mut self, y_operation: Option<String>) -> Self {
+        self.y_operation = y_operation;
+        self
+    }
+
+    pub fn tag_operation(mut self, tag_operation: Option<String>) -> Self {
+        self.tag_operation = tag_operation;
+        self
+    }
+
+    pub fn x_monotonicity(mut self, x_monotonicity: Option<Monotonicity>) -> Self {
+        self.x_monotonicity = x_monotonicity;
+        self
+    }
+
+    pub fn y_monotonicity(mut self, y_monotonicity: Option<Monotonicity>) -> Self {
+        self.y_monotonicity = y_monotonicity;
+        self
+    }
+
+    pub fn baseline_set(mut self, baseline_set: Option<String>) -> Self {
+        self.baseline_set = baseline_set;
+        self
+    }
+
+    pub fn x_format(mut self, x_format: Option<AxisFormat>) -> Self {
+        self.x_format = x_format;
+        self
+    }
+
+    pub fn y_format(mut self, y_format: Option<AxisFormat>) -> Self {
+        self.y_format = y_format;
+        self
+    }
+
+    pub fn x_axis(mut self, x_axis: Option<AxisStyle>) -> Self {
+        self.x_axis = x_axis;
+        self
+    }
+
+    pub fn y_axis(mut self, y_axis: Option<AxisStyle>) -> Self {
+        self.y_axis = y_axis;
+        self
+    }
+
+    /// The x operation, as evaluated (see `expression`)
+    pub(crate) fn x_expression(&self) -> Option<String> {
+        expression(&self.x_operation)
+    }
+
+    /// The y operation, as evaluated (see `expression`)
+    pub(crate) fn y_expression(&self) -> Option<String> {
+        expression(&self.y_operation)
+    }
+
+    /// The tag operation, as evaluated (see `expression`)
+    pub(crate) fn tag_expression(&self) -> Option<String> {
+        expression(&self.tag_operation)
+    }
 }
 
-#[derive(Debug, serde::Deserialize)]
+/// Serialize a single string as such, and a list of strings otherwise
+fn serialize_one_or_many<S: serde::Serializer>(
+    strings: &[String],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match strings {
+        [s] => serializer.serialize_str(s),
+        _ => serde::Serialize::serialize(strings, serializer),
+    }
+}
+
+/// Deserialize either a single string or a list of strings
+fn one_or_many<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    #[derive(serde::Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(String),
+        Many(Vec<String>),
+    }
+
+    match <OneOrMany as serde::Deserialize>::deserialize(deserializer)? {
+        OneOrMany::One(s) => Ok(vec![s]),
+        OneOrMany::Many(v) => Ok(v),
+    }
+}
+
+/// Expression of an (optional) operation, which may span multiple lines, as given to evalexpr
+///
+/// Comments (from a `#` to the end of the line) are stripped, as evalexpr does not support them.
+/// The operations keep them in the config, so that editing it does not drop them
+fn expression(operation: &Option<String>) -> Option<String> {
+    operation.as_deref().map(strip_expression_comments)
+}
+
+/// Strip the comments from an expression (outside of string literals), along with blank lines
+pub(crate) fn strip_expression_comments(expression: &str) -> String {
+    expression
+        .lines()
+        .map(|line| {
+            let mut in_string = false;
+            let mut escaped = false;
+            for (idx, c) in line.char_indices() {
+                match c {
+                    _ if escaped => escaped = false,
+                    '\\' if in_string => escaped = true,
+                    '"' => in_string = !in_string,
+                    '#' if !in_string => return line[..idx].trim_end(),
+                    _ => {}
+                }
+            }
+            line.trim_end()
+        })
+        .filter(|line| !line.trim().is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Format of a config file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Json,
+    Toml,
+    Yaml,
+}
+
+impl ConfigFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ConfigFormat::Json => "json",
+            ConfigFormat::Toml => "toml",
+            ConfigFormat::Yaml => "yaml",
+        }
+    }
+
+    /// Detect the format of a config from the extension of its path
+    /// or, if it has none (e.g., `.bencher-config`), from its contents
+    pub(crate) fn detect(path: &std::path::Path, contents: &str) -> BencherResult<Self> {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(extension) => extension.parse(),
+            None => Ok(Self::from_contents(contents)),
+        }
+    }
+
+    /// Guess the format of a config from its first meaningful line:
+    /// JSON configs open an object, TOML configs start with a key assignment or a table header
+    /// and everything else is taken to be YAML
+    fn from_contents(contents: &str) -> Self {
+        let first_line = contents
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty() && !line.starts_with('#'));
+        match first_line {
+            None => ConfigFormat::Json,
+            Some(line) if line.starts_with('{') => ConfigFormat::Json,
+            Some(line) if line.starts_with('[') => ConfigFormat::Toml,
+            Some(line) => match (line.find('='), line.find(':')) {
+                (Some(eq), Some(colon)) if eq < colon => ConfigFormat::Toml,
+                (Some(_), None) => ConfigFormat::Toml,
+                _ => ConfigFormat::Yaml,
+            },
+        }
+    }
+}
+
+impl std::str::FromStr for ConfigFormat {
+    type Err = BencherError;
+    fn from_str(s: &str) -> BencherResult<ConfigFormat> {
+        match s.to_lowercase().as_str() {
+            "json" => Ok(ConfigFormat::Json),
+            "toml" => Ok(ConfigFormat::Toml),
+            "yaml" | "yml" => Ok(ConfigFormat::Yaml),
+            _ => Err(BencherError::UnknownConfigFormat(s.to_string())),
+        }
+    }
+}
+
+/// A config, as read from a file
+///
+/// Configs are merged with the configs they include and with the configs of the parent
+/// directories (up to a root config), in this order of precedence:
+///   1. the config itself;
+///   2. the configs it includes (in order, each followed by the configs it includes);
+///   3. the config of the closest parent directory which has one, along with its includes, and so on.
+///
+/// The default database is taken from the first config (in that order) which sets it.
+/// Experiments are merged from every config: an experiment type can be defined by more than one
+/// config only if the definitions are the same, and the plot style is merged option by option
+#[derive(Debug, Clone, Default, serde::Deserialize, serde::Serialize)]
 pub struct ParsedConfig {
     /// database filepath relative to the config filepath
-    pub default_database_filepath: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_database_filepath: Option<String>,
+
+    /// configs to merge into this one, relative to the config filepath
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include: Option<Vec<String>>,
+
+    /// whether to stop merging the configs of the parent directories into this one
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub root: bool,
 
     /// bidimensional experiment descriptions
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub xy_experiments: Option<Vec<XYExperiment>>,
 
     /// linear experiment descriptions
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub linear_experiments: Option<Vec<LinearExperiment>>,
 
     /// virtual bidimensional experiment descriptions
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub virtual_xy_experiments: Option<Vec<VirtualXYExperiment>>,
 
     /// virtual linear experiment descriptions
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub virtual_linear_experiments: Option<Vec<VirtualLinearExperiment>>,
+
+    /// how the plots are styled (terminal, size, font, palette, lines and points, legend)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub style: Option<PlotStyle>,
+}
+
+/// Merge experiment definitions (of one kind) from the config at `path` into a merged list
+fn merge_experiments<E: Clone + PartialEq>(
+    merged: &mut Option<Vec<E>>,
+    definitions: &Option<Vec<E>>,
+    exp_type: impl Fn(&E) -> &String,
+    path: &std::path::Path,
+    origins: &mut HashMap<String, std::path::PathBuf>,
+) -> BencherResult<()> {
+    for definition in definitions.iter().flatten() {
+        match origins.get(exp_type(definition)) {
+            Some(origin) if origin == path => {}
+            Some(origin) => {
+                if merged.iter().flatten().any(|e| e == definition) {
+                    continue;
+                }
+                return Err(BencherError::ConflictingExperiment(
+                    exp_type(definition).clone(),
+                    origin.clone(),
+                    path.into(),
+                ));
+            }
+            None => {
+                origins.insert(exp_type(definition).clone(), path.into());
+            }
+        }
+        merged.get_or_insert_with(Vec::new).push(definition.clone());
+    }
+
+    Ok(())
+}
+
+/// Kinds of experiments, to validate the sources of virtual experiments
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum ExperimentKind {
+    Linear,
+    XY,
+    VirtualLinear,
+    VirtualXY,
+}
+
+impl ExperimentKind {
+    fn is_linear(&self) -> bool {
+        matches!(self, ExperimentKind::Linear | ExperimentKind::VirtualLinear)
+    }
+
+    fn is_virtual(&self) -> bool {
+        matches!(
+            self,
+            ExperimentKind::VirtualLinear | ExperimentKind::VirtualXY
+        )
+    }
+
+    fn family(&self) -> &'static str {
+        if self.is_linear() {
+            "linear"
+        } else {
+            "bidimensional"
+        }
+    }
+}
+
+/// Find the cycles in the dependencies between virtual experiments
+/// Each cycle is reported once, as a path which starts and ends in the same experiment
+fn find_cycles<'a>(dependencies: &BTreeMap<&'a str, Vec<&'a str>>) -> Vec<Vec<String>> {
+    fn visit<'a>(
+        exp_type: &'a str,
+        dependencies: &BTreeMap<&'a str, Vec<&'a str>>,
+        on_stack: &mut HashMap<&'a str, bool>,
+        stack: &mut Vec<&'a str>,
+        cycles: &mut Vec<Vec<String>>,
+    ) {
+        on_stack.insert(exp_type, true);
+        stack.push(exp_type);
+        for &source in dependencies.get(exp_type).into_iter().flatten() {
+            match on_stack.get(source) {
+                Some(true) => {
+                    let start = stack.iter().position(|&e| e == source).unwrap_or(0);
+                    cycles.push(
+                        stack[start..]
+                            .iter()
+                            .chain(std::iter::once(&source))
+                            .map(|e| e.to_string())
+                            .collect(),
+                    );
+                }
+                Some(false) => {}
+                None => visit(source, dependencies, on_stack, stack, cycles),
+            }
+        }
+        stack.pop();
+        on_stack.insert(exp_type, false);
+    }
+
+    let mut on_stack = HashMap::new();
+    let mut cycles = vec![];
+    for &exp_type in dependencies.keys() {
+        if !on_stack.contains_key(exp_type) {
+            visit(
+                exp_type,
+                dependencies,
+                &mut on_stack,
+                &mut vec![],
+                &mut cycles,
+            );
+        }
+    }
+    cycles
 }
 
 impl ParsedConfig {
+    /// Experiment types defined by the config, along with their kinds
+    fn experiment_kinds(&self) -> impl Iterator<Item = (&str, ExperimentKind)> {
+        self.linear_experiments
+            .iter()
+            .flatten()
+            .map(|e| (e.exp_type.as_str(), ExperimentKind::Linear))
+            .chain(
+                self.xy_experiments
+                    .iter()
+                    .flatten()
+                    .map(|e| (e.exp_type.as_str(), ExperimentKind::XY)),
+            )
+            .chain(
+                self.virtual_linear_experiments
+                    .iter()
+                    .flatten()
+                    .map(|e| (e.exp_type.as_str(), ExperimentKind::VirtualLinear)),
+            )
+            .chain(
+                self.virtual_xy_experiments
+                    .iter()
+                    .flatten()
+                    .map(|e| (e.exp_type.as_str(), ExperimentKind::VirtualXY)),
+            )
+    }
+
+    /// Every problem with the experiment definitions: duplicate experiment types,
+    /// undefined (or mismatched) sources, cycles between virtual experiments,
+    /// operations which do not parse, conflicting formats, invalid axes and an invalid plot style
+    pub(crate) fn problems(&self) -> Vec<ConfigProblem> {
+        let mut kinds: BTreeMap<&str, Vec<ExperimentKind>> = BTreeMap::new();
+        for (exp_type, kind) in self.experiment_kinds() {
+            kinds.entry(exp_type).or_default().push(kind);
+        }
+
+        let mut problems = kinds
+            .iter()
+            .filter(|(_, kinds)| kinds.len() > 1)
+            .map(|(exp_type, kinds)| {
+                ConfigProblem::DuplicateExperiment(exp_type.to_string(), kinds.len())
+            })
+            .collect::<Vec<_>>();
+
+        let mut check_expression = |exp_type: &str, field: &str, expr: Option<String>| {
+            if let Some(Err(e)) = expr.as_deref().map(evalexpr::build_operator_tree) {
+                problems.push(ConfigProblem::InvalidExpression(
+                    exp_type.to_string(),
+                    field.to_string(),
+                    e.to_string(),
+                ));
+            }
+        };
+        for e in self.virtual_linear_experiments.iter().flatten() {
+            check_expression(&e.exp_type, "v_operation", e.v_expression());
+            check_expression(&e.exp_type, "tag_operation", e.tag_expression());
+        }
+        for e in self.virtual_xy_experiments.iter().flatten() {
+            check_expression(&e.exp_type, "x_operation", e.x_expression());
+            check_expression(&e.exp_type, "y_operation", e.y_expression());
+            check_expression(&e.exp_type, "tag_operation", e.tag_expression());
+        }
+
+        let mut check_format = |exp_type: &str, axis: &str, format: &Option<AxisFormat>| {
+            if format.is_some_and(|f| f.is_conflicting()) {
+                problems.push(ConfigProblem::ConflictingFormat(
+                    exp_type.to_string(),
+                    axis.to_string(),
+                ));
+            }
+        };
+        for e in self.linear_experiments.iter().flatten() {
+            check_format(&e.exp_type, "v_format", &e.v_format);
+        }
+        for e in self.xy_experiments.iter().flatten() {
+            check_format(&e.exp_type, "x_format", &e.x_format);
+            check_format(&e.exp_type, "y_format", &e.y_format);
+        }
+        for e in self.virtual_linear_experiments.iter().flatten() {
+            check_format(&e.exp_type, "v_format", &e.v_format);
+        }
+        for e in self.virtual_xy_experiments.iter().flatten() {
+            check_format(&e.exp_type, "x_format", &e.x_format);
+            check_format(&e.exp_type, "y_format", &e.y_format);
+        }
+
+        let mut check_axis = |exp_type: &str, axis: &str, style: &Option<AxisStyle>| {
+            if let Some(problem) = style.as_ref().and_then(AxisStyle::problem) {
+                problems.push(ConfigProblem::InvalidAxis(
+                    exp_type.to_string(),
+                    axis.to_string(),
+                    problem,
+                ));
+            }
+        };
+        for e in self.linear_experiments.iter().flatten() {
+            check_axis(&e.exp_type, "v_axis", &e.v_axis);
+        }
+        for e in self.xy_experiments.iter().flatten() {
+            check_axis(&e.exp_type, "x_axis", &e.x_axis);
+            check_axis(&e.exp_type, "y_axis", &e.y_axis);
+        }
+        for e in self.virtual_linear_experiments.iter().flatten() {
+            check_axis(&e.exp_type, "v_axis", &e.v_axis);
+        }
+        for e in self.virtual_xy_experiments.iter().flatten() {
+            check_axis(&e.exp_type, "x_axis", &e.x_axis);
+            check_axis(&e.exp_type, "y_axis", &e.y_axis);
+        }
+
+        if let Some(problem) = self.style.as_ref().and_then(PlotStyle::problem) {
+            problems.push(ConfigProblem::InvalidStyle(problem));
+        }
+
+        // linear virtual experiments need linear sources, while the sources of xy virtual
+        // experiments need to be of the same family as the primary one
+        let kind = |exp_type: &str| kinds.get(exp_type).map(|kinds| kinds[0]);
+        let virtual_sources = self
+            .virtual_linear_experiments
+            .iter()
+            .flatten()
+            .map(|e| {
+                (
+                    &e.exp_type,
+                    &e.source_exp_types,
+                    Some(ExperimentKind::Linear),
+                )
+            })
+            .chain(
+                self.virtual_xy_experiments
+                    .iter()
+                    .flatten()
+                    .map(|e| (&e.exp_type, &e.source_exp_types, None)),
+            );
+        let mut dependencies: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+        for (exp_type, sources, family) in virtual_sources {
+            let Some(primary) = sources.first() else {
+                problems.push(ConfigProblem::NoSources(exp_type.clone()));
+                continue;
+            };
+            let family = family.or_else(|| kind(primary));
+
+            for source in sources {
+                match (kind(source), family) {
+                    (None, _) => problems.push(ConfigProblem::DanglingSource(
+                        exp_type.clone(),
+                        source.clone(),
+                    )),
+                    (Some(source_kind), Some(family))
+                        if source_kind.is_linear() != family.is_linear() =>
+                    {
+                        problems.push(ConfigProblem::MismatchedSource(
+                            exp_type.clone(),
+                            source.clone(),
+                            family.family().to_string(),
+                        ))
+                    }
+                    (Some(source_kind), _) => {
+                        if source_kind.is_virtual() {
+                            dependencies.entry(exp_type).or_default().push(source);
+                        }
+                    }
+                }
+            }
+        }
+
+        problems.extend(
+            find_cycles(&dependencies)
+                .into_iter()
+                .map(ConfigProblem::Cycle),
+        );
+        problems
+    }
+
+    /// Check the experiment definitions, failing with every problem found
+    pub(crate) fn validate(&self) -> BencherResult<()> {
+        let problems = self.problems();
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(BencherError::InvalidConfig(problems))
+        }
+    }
+
+    /// Empty config, with the default database at a given path (relative to the config)
+    pub(crate) fn new(default_database_filepath: impl Into<String>) -> Self {
+        ParsedConfig {
+            default_database_filepath: Some(default_database_filepath.into()),
+            ..Default::default()
+        }
+    }
+
+    /// Merge the experiments (and the plot style) of a config with lower precedence
+    /// (read from `path`) into this one
+    ///
+    /// `origins` maps the experiment types merged so far to the config which defines them:
+    /// an experiment defined by another config is kept once if both definitions are the same,
+    /// and is a conflict otherwise (duplicates within a config are left for the validation)
+    pub(crate) fn merge(
+        &mut self,
+        other: &ParsedConfig,
+        path: &std::path::Path,
+        origins: &mut HashMap<String, std::path::PathBuf>,
+    ) -> BencherResult<()> {
+        self.style = match (self.style.take(), &other.style) {
+            (Some(style), Some(other_style)) => Some(other_style.clone().overridden_by(style)),
+            (style, other_style) => style.or_else(|| other_style.clone()),
+        };
+
+        merge_experiments(
+            &mut self.linear_experiments,
+            &other.linear_experiments,
+            |e| &e.exp_type,
+            path,
+            origins,
+        )?;
+        merge_experiments(
+            &mut self.xy_experiments,
+            &other.xy_experiments,
+            |e| &e.exp_type,
+            path,
+            origins,
+        )?;
+        merge_experiments(
+            &mut self.virtual_linear_experiments,
+            &other.virtual_linear_experiments,
+            |e| &e.exp_type,
+            path,
+            origins,
+        )?;
+        merge_experiments(
+            &mut self.virtual_xy_experiments,
+            &other.virtual_xy_experiments,
+            |e| &e.exp_type,
+            path,
+            origins,
+        )
+    }
+
+    /// Write the config to a given path, in a given format
+    ///
+    /// The config is first written to a temporary file, which then replaces the file at the path,
+    /// so that the config is never left half-written
+    pub(crate) fn to_path(
+        &self,
+        path: &std::path::Path,
+        format: ConfigFormat,
+    ) -> BencherResult<()> {
+        let mut tmp_path = path.as_os_str().to_owned();
+        tmp_path.push(".tmp");
+        let tmp_path = std::path::PathBuf::from(tmp_path);
+
+        let mut contents = match format {
+            ConfigFormat::Json => serde_json::to_string_pretty(self)?,
+            ConfigFormat::Toml => toml::to_string_pretty(self)?,
+            ConfigFormat::Yaml => serde_yaml::to_string(self)?,
+        };
+        if !contents.ends_with('\n') {
+            contents.push('\n');
+        }
+        std::fs::write(&tmp_path, contents)
+            .map_err(|e| BencherError::io_err(e, format!("writing {:?}", &tmp_path)))?;
+        std::fs::rename(&tmp_path, path)
+            .map_err(|e| BencherError::io_err(e, format!("replacing {:?}", &path)))
+    }
+
+    /// Read the config at a given path, in any of the supported formats
     pub(crate) fn from_path(path: &std::path::Path) -> BencherResult<Self> {
-        let config_file = File::open(&path)
-            .map_err(|e| BencherError::io_err(e, format!("opening {:?}", &path)))?;
-        let reader = BufReader::new(config_file);
-        let config = serde_json::from_reader(reader)?;
+        Self::from_path_with_format(path).map(|(config, _)| config)
+    }
+
+    /// Read the config at a given path, along with the format it is written in
+    pub(crate) fn from_path_with_format(
+        path: &std::path::Path,
+    ) -> BencherResult<(Self, ConfigFormat)> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| BencherError::io_err(e, format!("reading {:?}", &path)))?;
+
+        let format = ConfigFormat::detect(path, &contents)?;
+        let config = match format {
+            ConfigFormat::Json => serde_json::from_str(&contents)?,
+            ConfigFormat::Toml => toml::from_str(&contents)?,
+            ConfigFormat::Yaml => serde_yaml::from_str(&contents)?,
+        };
+
+        Ok((config, format))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn dependencies<'a>(edges: &[(&'a str, &'a str)]) -> BTreeMap<&'a str, Vec<&'a str>> {
+        let mut dependencies: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+        for &(exp_type, source) in edges {
+            dependencies.entry(exp_type).or_default().push(source);
+        }
+        dependencies
+    }
+
+    /// Config with a linear experiment `lat` and virtual linear experiments given by
+    /// their type and sources
+    fn virtual_config(experiments: &[(&str, &[&str])]) -> ParsedConfig {
+        let virtual_experiments = experiments
+            .iter()
+            .map(|(exp_type, sources)| {
+                serde_json::json!({
+                    "exp_type": exp_type,
+                    "source_exp_types": sources,
+                    "horizontal_label": "op",
+                    "v_label": exp_type,
+                    "v_units": "",
+                })
+            })
+            .collect::<Vec<_>>();
+        serde_json::from_value(serde_json::json!({
+            "linear_experiments": [
+                { "exp_type": "lat", "horizontal_label": "op", "v_label": "latency", "v_units": "ms" }
+            ],
+            "virtual_linear_experiments": virtual_experiments,
+        }))
+        .unwrap()
+    }
+
+    fn path(cycle: &[&str]) -> Vec<String> {
+        cycle.iter().map(|e| e.to_string()).collect()
+    }
+
+    #[test]
+    fn config_format_from_contents() {
+        for (contents, format) in [
+            ("", ConfigFormat::Json),
+            ("{\n  \"root\": true\n}", ConfigFormat::Json),
+            ("# a comment first\n\n{}", ConfigFormat::Json),
+            ("root = true", ConfigFormat::Toml),
+            (
+                "[[linear_experiments]]\nexp_type = \"lat\"",
+                ConfigFormat::Toml,
+            ),
+            ("root: true", ConfigFormat::Yaml),
+            ("- lat", ConfigFormat::Yaml),
+            ("---\nroot: true", ConfigFormat::Yaml),
+        ] {
+            assert_eq!(
+                ConfigFormat::from_contents(contents),
+                format,
+                "{:?}",
+                contents
+            );
+        }
+    }
+
+    #[test]
+    fn config_format_from_ambiguous_contents() {
+        // lines with both separators are decided by the first one
+        assert_eq!(
+            ConfigFormat::from_contents("default_database_filepath = \"dbs/a:b.db\""),
+            ConfigFormat::Toml
+        );
+        assert_eq!(
+            ConfigFormat::from_contents("default_database_filepath: dbs/a=b.db"),
+            ConfigFormat::Yaml
+        );
+        // only the first meaningful line counts
+        assert_eq!(
+            ConfigFormat::from_contents("   \n# root: true\nroot = true\nother: 1"),
+            ConfigFormat::Toml
+        );
+        // YAML flow mappings look like JSON objects
+        assert_eq!(
+            ConfigFormat::from_contents("{root: true}"),
+            ConfigFormat::Json
+        );
+    }
+
+    #[test]
+    fn config_format_detect() {
+        let detect =
+            |path: &str, contents: &str| ConfigFormat::detect(std::path::Path::new(path), contents);
+        // the extension takes precedence over the contents
+        assert_eq!(
+            detect(".bencher-config.yml", "{}").unwrap(),
+            ConfigFormat::Yaml
+        );
+        assert_eq!(
+            detect(".bencher-config.toml", "root: true").unwrap(),
+            ConfigFormat::Toml
+        );
+        assert_eq!(
+            detect(".bencher-config", "root = true").unwrap(),
+            ConfigFormat::Toml
+        );
+        assert!(matches!(
+            detect(".bencher-config.ini", "root = true"),
+            Err(BencherError::UnknownConfigFormat(e)) if e == "ini"
+        ));
+    }
+
+    #[test]
+    fn strip_comments() {
+        assert_eq!(strip_expression_comments("v * 2 # double"), "v * 2");
+        assert_eq!(
+            strip_expression_comments("# speedup\n\nbaseline / v  # over the baseline\n   \n"),
+            "baseline / v"
+        );
+        assert_eq!(
+            strip_expression_comments("a = 1; # first\nb = 2; # second\na + b"),
+            "a = 1;\nb = 2;\na + b"
+        );
+    }
+
+    #[test]
+    fn strip_comments_in_strings() {
+        assert_eq!(
+            strip_expression_comments(r##"str::from(v) + "#1" # label"##),
+            r##"str::from(v) + "#1""##
+        );
+        assert_eq!(
+            strip_expression_comments(r#""a \" # b" # c"#),
+            r#""a \" # b""#
+        );
+    }
+
+    #[test]
+    fn expressions_in_every_format() {
+        let expected = "v * 2\n+ 1";
+        let raw = "v * 2 # double\n+ 1 # and one\n";
+        let json = r##"{ "virtual_linear_experiments": [{
+            "exp_type": "double", "source_exp_type": "lat", "horizontal_label": "op",
+            "v_label": "double", "v_units": "ms", "v_operation": "v * 2 # double\n+ 1 # and one\n"
+        }] }"##;
+        let toml = r#"
+[[virtual_linear_experiments]]
+exp_type = "double"
+source_exp_type = "lat"
+horizontal_label = "op"
+v_label = "double"
+v_units = "ms"
+v_operation = """
+v * 2 # double
++ 1 # and one
+"""
+"#;
+        let yaml = r#"
+virtual_linear_experiments:
+  - exp_type: double
+    source_exp_type: lat
+    horizontal_label: op
+    v_label: double
+    v_units: ms
+    v_operation: |
+      v * 2 # double
+      + 1 # and one
+"#;
+
+        let configs: [ParsedConfig; 3] = [
+            serde_json::from_str(json).unwrap(),
+            toml::from_str(toml).unwrap(),
+            serde_yaml::from_str(yaml).unwrap(),
+        ];
+        for config in configs {
+            let experiments = config.virtual_linear_experiments.unwrap();
+            // the comments are kept in the config, and only stripped to evaluate the expression
+            assert_eq!(experiments[0].v_operation.as_deref(), Some(raw));
+            assert_eq!(experiments[0].v_expression().as_deref(), Some(expected));
+            assert_eq!(experiments[0].source_exp_types, vec!["lat".to_string()]);
+        }
+    }
+
+    #[test]
+    fn find_cycles_self_reference() {
+        assert_eq!(
+            find_cycles(&dependencies(&[("a", "a")])),
+            vec![path(&["a", "a"])]
+        );
+    }
+
+    #[test]
+    fn find_cycles_two_experiments() {
+        // the cycle is reported once, from the first experiment reached
+        assert_eq!(
+            find_cycles(&dependencies(&[("a", "b"), ("b", "a")])),
+            vec![path(&["a", "b", "a"])]
+        );
+        assert_eq!(
+            find_cycles(&dependencies(&[("x", "a"), ("a", "b"), ("b", "a")])),
+            vec![path(&["a", "b", "a"])]
+        );
+    }
+
+    #[test]
+    fn find_cycles_diamond() {
+        // two paths to the same experiment are not a cycle
+        assert!(find_cycles(&dependencies(&[
+            ("top", "left"),
+            ("top", "right"),
+            ("left", "bottom"),
+            ("right", "bottom"),
+            ("bottom", "lat"),
+        ]))
+        .is_empty());
+    }
+
+    #[test]
+    fn cycle_problems() {
+        let config = virtual_config(&[
+            ("self", &["self"]),
+            ("a", &["b"]),
+            ("b", &["lat", "a"]),
+            ("ok", &["a", "lat"]),
+        ]);
+        assert_eq!(
+            config.problems(),
+            vec![
+                ConfigProblem::Cycle(path(&["a", "b", "a"])),
+                ConfigProblem::Cycle(path(&["self", "self"])),
+            ]
+        );
+
+        let error = config.validate().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Invalid config:\n  \
+             - virtual experiments depend on each other: a -> b -> a\n  \
+             - virtual experiments depend on each other: self -> self"
+        );
+    }
 
-        Ok(config)
+    #[test]
+    fn acyclic_config() {
+        let config = virtual_config(&[
+            ("top", &["left", "right"]),
+            ("left", &["bottom"]),
+            ("right", &["bottom"]),
+            ("bottom", &["lat"]),
+        ]);
+        assert!(config.validate().is_ok());
     }
 }
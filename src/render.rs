@@ -0,0 +1,776 @@
+use plotters::coord::ranged1d::{KeyPointHint, NoDefaultFormatting, ValueFormatter};
+use plotters::coord::types::RangedCoordf64;
+use plotters::coord::Shift;
+use plotters::prelude::*;
+
+use crate::*;
+
+/// Size (in pixels) of rendered images
+const IMAGE_SIZE: (u32, u32) = (1024, 768);
+
+/// Image formats which can be rendered without gnuplot
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    Svg,
+    Png,
+}
+
+impl ImageFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ImageFormat::Svg => "svg",
+            ImageFormat::Png => "png",
+        }
+    }
+}
+
+impl std::str::FromStr for ImageFormat {
+    type Err = BencherError;
+    fn from_str(s: &str) -> BencherResult<ImageFormat> {
+        match s.to_lowercase().as_str() {
+            "svg" => Ok(ImageFormat::Svg),
+            "png" => Ok(ImageFormat::Png),
+            _ => Err(BencherError::UnknownImageFormat(s.to_string())),
+        }
+    }
+}
+
+/// A bar of a histogram, with its error bar
+pub(crate) struct HistogramBar {
+    pub(crate) v: f64,
+    pub(crate) bar: Option<(f64, f64)>,
+}
+
+/// A set of bars of a histogram, with one (optional) bar per group
+pub(crate) struct HistogramSet {
+    pub(crate) label: String,
+    pub(crate) style: SeriesStyle,
+
+    /// None if the set does not have the group
+    pub(crate) values: Vec<Option<HistogramBar>>,
+}
+
+/// A point of a line chart, with its error bars
+pub(crate) struct LinePoint {
+    pub(crate) x: f64,
+    pub(crate) y: f64,
+    pub(crate) x_bar: Option<(f64, f64)>,
+    pub(crate) y_bar: Option<(f64, f64)>,
+}
+
+/// A line of a line chart
+pub(crate) struct Line {
+    pub(crate) label: String,
+    pub(crate) style: SeriesStyle,
+    pub(crate) points: Vec<LinePoint>,
+
+    /// Whether the points are joined (otherwise only the points are drawn)
+    pub(crate) joined: bool,
+}
+
+fn render_err(e: impl std::fmt::Display) -> BencherError {
+    BencherError::RenderError(e.to_string())
+}
+
+fn color(style: &SeriesStyle) -> RGBColor {
+    let (r, g, b) = parse_color(&style.color).unwrap_or((0, 0, 0));
+    RGBColor(r, g, b)
+}
+
+/// Size of the rendered images: the size of the style if given in pixels
+fn image_size(style: &PlotStyle) -> (u32, u32) {
+    style.pixel_size().unwrap_or(IMAGE_SIZE)
+}
+
+/// Where the legend is drawn (None if it is hidden)
+///
+/// The legend is always drawn inside the chart, so outside positions take the closest border
+fn legend_position(style: &PlotStyle) -> Option<SeriesLabelPosition> {
+    match style.legend.unwrap_or_default() {
+        LegendPosition::OutsideAbove => Some(SeriesLabelPosition::UpperMiddle),
+        LegendPosition::OutsideBelow => Some(SeriesLabelPosition::LowerMiddle),
+        LegendPosition::OutsideRight => Some(SeriesLabelPosition::MiddleRight),
+        LegendPosition::InsideTopLeft => Some(SeriesLabelPosition::UpperLeft),
+        LegendPosition::InsideTopRight => Some(SeriesLabelPosition::UpperRight),
+        LegendPosition::InsideBottomLeft => Some(SeriesLabelPosition::LowerLeft),
+        LegendPosition::InsideBottomRight => Some(SeriesLabelPosition::LowerRight),
+        LegendPosition::Hidden => None,
+    }
+}
+
+/// Shape of the points of a line, following its gnuplot point type
+/// (the filled and the empty shapes are both drawn filled)
+enum Marker {
+    Cross,
+    Square,
+    Circle,
+    Triangle,
+}
+
+impl Marker {
+    fn from_point_type(point_type: u32) -> Self {
+        match point_type {
+            1..=3 => Marker::Cross,
+            4 | 5 => Marker::Square,
+            8..=11 => Marker::Triangle,
+            _ => Marker::Circle,
+        }
+    }
+}
+
+fn image_path(prefix: &std::path::Path, format: ImageFormat) -> BencherResult<std::path::PathBuf> {
+    let mut path: std::path::PathBuf = prefix.into();
+    if !path.set_extension(format.extension()) {
+        return Err(BencherError::PathCreateError(
+            path,
+            format.extension().to_string(),
+        ));
+    }
+    Ok(path)
+}
+
+/// Range covering every value, with some padding
+fn padded_range(values: impl Iterator<Item = f64>) -> std::ops::Range<f64> {
+    let (min, max) = values.fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), v| {
+        (min.min(v), max.max(v))
+    });
+    if !min.is_finite() || !max.is_finite() {
+        0.0..1.0
+    } else if min == max {
+        (min - 1.0)..(max + 1.0)
+    } else {
+        let padding = (max - min) * 0.05;
+        (min - padding)..(max + padding)
+    }
+}
+
+/// An axis drawn by the renderer (see `AxisStyle`), with the bounds in the displayed units
+///
+/// Values are drawn at their plot coordinates: their logarithm on logarithmic axes,
+/// negated on reversed axes
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct PlotAxis {
+    pub(crate) log: bool,
+    pub(crate) reverse: bool,
+    pub(crate) min: Option<f64>,
+    pub(crate) max: Option<f64>,
+    pub(crate) tick_spacing: Option<f64>,
+}
+
+/// Most ticks drawn on an axis
+const MAX_TICKS: usize = 100;
+
+impl PlotAxis {
+    /// Plot coordinate of a value (not finite for non-positive values on logarithmic axes)
+    fn plot_coord(&self, v: f64) -> f64 {
+        let c = if self.log { v.log10() } else { v };
+        if self.reverse {
+            -c
+        } else {
+            c
+        }
+    }
+
+    /// Value at a plot coordinate
+    fn value_at(&self, c: f64) -> f64 {
+        let c = if self.reverse { -c } else { c };
+        if self.log {
+            10_f64.powf(c)
+        } else {
+            c
+        }
+    }
+
+    /// Range of the axis in plot coordinates: covering every value (with some padding)
+    /// unless its bounds are set, in which case they default to `default_range`
+    ///
+    /// The default bounds are ignored on logarithmic axes
+    fn plot_range(
+        &self,
+        values: impl Iterator<Item = f64>,
+        default_range: (Option<f64>, Option<f64>),
+    ) -> std::ops::Range<f64> {
+        let std::ops::Range { mut start, mut end } =
+            padded_range(values.map(|v| self.plot_coord(v)).filter(|c| c.is_finite()));
+
+        let bound = |v: Option<f64>| v.map(|v| self.plot_coord(v)).filter(|c| c.is_finite());
+        let min = bound(self.min.or(default_range.0));
+        let max = bound(self.max.or(default_range.1));
+        let (lower, upper) = if self.reverse { (max, min) } else { (min, max) };
+        if let Some(lower) = lower {
+            start = lower;
+        }
+        if let Some(upper) = upper {
+            end = upper;
+        }
+
+        if start < end {
+            start..end
+        } else {
+            start..(start + 1.0)
+        }
+    }
+
+    /// Ticks (in plot coordinates) within a range (in plot coordinates)
+    ///
+    /// On linear axes, they are multiples of the tick spacing (or of a round step),
+    /// while on logarithmic axes they are powers of the tick spacing (10 by default)
+    fn ticks(&self, range: &std::ops::Range<f64>) -> Vec<f64> {
+        let (a, b) = (self.value_at(range.start), self.value_at(range.end));
+        let (lower, upper) = (a.min(b), a.max(b));
+
+        let powers = |factor: f64| {
+            let first = (lower.log(factor) - 1e-9).ceil() as i64;
+            let last = (upper.log(factor) + 1e-9).floor() as i64;
+            (first..=last)
+                .take(MAX_TICKS)
+                .map(|k| factor.powi(k as i32))
+                .collect::<Vec<_>>()
+        };
+        let multiples = |step: f64| {
+            let first = (lower / step - 1e-9).ceil() as i64;
+            let last = (upper / step + 1e-9).floor() as i64;
+            (first..=last)
+                .take(MAX_TICKS)
+                .map(|k| k as f64 * step)
+                .collect::<Vec<_>>()
+        };
+
+        let ticks = match (self.log, self.tick_spacing) {
+            (true, spacing) => {
+                let ticks = powers(spacing.unwrap_or(10.0));
+                if ticks.len() < 2 && spacing.is_none() {
+                    multiples(round_step((upper - lower) / 10.0))
+                } else {
+                    ticks
+                }
+            }
+            (false, Some(spacing)) => multiples(spacing),
+            (false, None) => multiples(round_step((upper - lower) / 10.0)),
+        };
+        ticks.into_iter().map(|v| self.plot_coord(v)).collect()
+    }
+
+    /// Label of the tick at a plot coordinate
+    fn tick_label(&self, c: &f64) -> String {
+        let v = self.value_at(*c);
+        if v != 0.0 && (v.abs() < 1e-3 || v.abs() >= 1e7) {
+            format!("{:e}", v)
+        } else {
+            let label = format!("{:.6}", v);
+            let label = label.trim_end_matches('0').trim_end_matches('.');
+            if label == "-0" {
+                "0".to_string()
+            } else {
+                label.to_string()
+            }
+        }
+    }
+
+    /// Coordinate of the axis with its ticks, covering a range (in plot coordinates)
+    fn coord(&self, range: std::ops::Range<f64>) -> PlotCoord {
+        PlotCoord {
+            axis: *self,
+            ticks: self.ticks(&range),
+            inner: range.into(),
+        }
+    }
+
+    /// Plot coordinate bars start from: zero, or the start of the range on logarithmic axes
+    fn base(&self, range: &std::ops::Range<f64>) -> f64 {
+        match self.plot_coord(0.0) {
+            c if c.is_finite() => c,
+            _ if self.reverse => range.end,
+            _ => range.start,
+        }
+    }
+}
+
+/// Coordinate of a `PlotAxis` (in plot coordinates), with its ticks and their labels
+pub(crate) struct PlotCoord {
+    axis: PlotAxis,
+    ticks: Vec<f64>,
+    inner: RangedCoordf64,
+}
+
+impl Ranged for PlotCoord {
+    type FormatOption = NoDefaultFormatting;
+    type ValueType = f64;
+
+    fn map(&self, value: &f64, limit: (i32, i32)) -> i32 {
+        self.inner.map(value, limit)
+    }
+
+    fn key_points<Hint: KeyPointHint>(&self, hint: Hint) -> Vec<f64> {
+        if hint.weight().allow_light_points() {
+            Vec::new()
+        } else {
+            self.ticks.clone()
+        }
+    }
+
+    fn range(&self) -> std::ops::Range<f64> {
+        self.inner.range()
+    }
+}
+
+impl ValueFormatter<f64> for PlotCoord {
+    fn format_ext(&self, value: &f64) -> String {
+        self.axis.tick_label(value)
+    }
+}
+
+/// Round (1, 2 or 5 times a power of 10) step of at least a given size
+fn round_step(min_step: f64) -> f64 {
+    if !min_step.is_finite() || min_step <= 0.0 {
+        return 1.0;
+    }
+    let power = 10_f64.powf(min_step.log10().floor());
+    match min_step / power {
+        x if x <= 1.0 => power,
+        x if x <= 2.0 => 2.0 * power,
+        x if x <= 5.0 => 5.0 * power,
+        _ => 10.0 * power,
+    }
+}
+
+/// Render a clustered histogram (one cluster per group, one bar per set) to `prefix`.{svg,png}
+pub(crate) fn render_histogram(
+    prefix: &std::path::Path,
+    format: ImageFormat,
+    axis_labels: (&str, &str),
+    groups: &[String],
+    sets: &[HistogramSet],
+    y_axis: &PlotAxis,
+    style: &PlotStyle,
+) -> BencherResult<()> {
+    let path = image_path(prefix, format)?;
+    match format {
+        ImageFormat::Svg => draw_histogram(
+            SVGBackend::new(&path, image_size(style)).into_drawing_area(),
+            axis_labels,
+            groups,
+            sets,
+            y_axis,
+            style,
+        ),
+        ImageFormat::Png => draw_histogram(
+            BitMapBackend::new(&path, image_size(style)).into_drawing_area(),
+            axis_labels,
+            groups,
+            sets,
+            y_axis,
+            style,
+        ),
+    }
+}
+
+fn draw_histogram<DB: DrawingBackend>(
+    root: DrawingArea<DB, Shift>,
+    (x_label, y_label): (&str, &str),
+    groups: &[String],
+    sets: &[HistogramSet],
+    y_axis: &PlotAxis,
+    style: &PlotStyle,
+) -> BencherResult<()> {
+    root.fill(&WHITE).map_err(render_err)?;
+
+    let y_range = y_axis.plot_range(
+        sets.iter()
+            .flat_map(|set| set.values.iter().flatten())
+            .flat_map(|b| {
+                let (min, max) = b.bar.unwrap_or((b.v, b.v));
+                [b.v, min, max]
+            }),
+        (Some(0.0), None),
+    );
+    let base = y_axis.base(&y_range);
+
+    let n_groups = groups.len();
+    let mut chart = ChartBuilder::on(&root)
+        .margin(20)
+        .x_label_area_size(50)
+        .y_label_area_size(80)
+        .build_cartesian_2d(-0.5..(n_groups as f64 - 0.5), y_axis.coord(y_range))
+        .map_err(render_err)?;
+
+    let group_label = |x: &f64| {
+        let idx = x.round();
+        if (x - idx).abs() < 1e-6 && idx >= 0.0 && (idx as usize) < n_groups {
+            groups[idx as usize].clone()
+        } else {
+            String::new()
+        }
+    };
+    chart
+        .configure_mesh()
+        .disable_x_mesh()
+        .x_labels(n_groups)
+        .x_label_formatter(&group_label)
+        .y_label_formatter(&|c| y_axis.tick_label(c))
+        .x_desc(x_label)
+        .y_desc(y_label)
+        .label_style(("sans-serif", 16))
+        .axis_desc_style(("sans-serif", 20))
+        .draw()
+        .map_err(render_err)?;
+
+    let width = 0.8 / sets.len().max(1) as f64;
+    for (idx, set) in sets.iter().enumerate() {
+        let color = color(&set.style);
+        let left = |group: usize| group as f64 - 0.4 + width * idx as f64;
+        let bars = || {
+            set.values
+                .iter()
+                .enumerate()
+                .filter_map(|(group, b)| b.as_ref().map(|b| (left(group), b.v, b.bar)))
+                .map(|(x, v, bar)| {
+                    let bar =
+                        bar.map(|(min, max)| (y_axis.plot_coord(min), y_axis.plot_coord(max)));
+                    (x, y_axis.plot_coord(v), bar)
+                })
+                .filter(|(_, v, _)| v.is_finite())
+        };
+
+        chart
+            .draw_series(
+                bars().map(|(x, v, _)| Rectangle::new([(x, base), (x + width, v)], color.filled())),
+            )
+            .map_err(render_err)?
+            .label(set.label.as_str())
+            .legend(move |(x, y)| Rectangle::new([(x, y - 6), (x + 12, y + 6)], color.filled()));
+        chart
+            .draw_series(bars().map(|(x, v, _)| {
+                Rectangle::new([(x, base), (x + width, v)], BLACK.stroke_width(1))
+            }))
+            .map_err(render_err)?;
+        chart
+            .draw_series(bars().filter_map(|(x, v, bar)| {
+                let (min, max) = bar.filter(|(min, max)| min.is_finite() && max.is_finite())?;
+                Some(ErrorBar::new_vertical(
+                    x + width / 2.0,
+                    min,
+                    v,
+                    max,
+                    BLACK.stroke_width(2),
+                    10,
+                ))
+            }))
+            .map_err(render_err)?;
+    }
+
+    if let Some(position) = legend_position(style) {
+        chart
+            .configure_series_labels()
+            .position(position)
+            .label_font(("sans-serif", 16))
+            .background_style(WHITE.mix(0.8))
+            .border_style(BLACK)
+            .draw()
+            .map_err(render_err)?;
+    }
+
+    root.present().map_err(render_err)
+}
+
+/// Render a stacked histogram (one bar per tick, stacking one value per set) to `prefix`.{svg,png}
+pub(crate) fn render_stacked_histogram(
+    prefix: &std::path::Path,
+    format: ImageFormat,
+    axis_labels: (&str, &str),
+    ticks: &[String],
+    sets: &[HistogramSet],
+    y_axis: &PlotAxis,
+    style: &PlotStyle,
+) -> BencherResult<()> {
+    let path = image_path(prefix, format)?;
+    match format {
+        ImageFormat::Svg => draw_stacked_histogram(
+            SVGBackend::new(&path, image_size(style)).into_drawing_area(),
+            axis_labels,
+            ticks,
+            sets,
+            y_axis,
+            style,
+        ),
+        ImageFormat::Png => draw_stacked_histogram(
+            BitMapBackend::new(&path, image_size(style)).into_drawing_area(),
+            axis_labels,
+            ticks,
+            sets,
+            y_axis,
+            style,
+        ),
+    }
+}
+
+fn draw_stacked_histogram<DB: DrawingBackend>(
+    root: DrawingArea<DB, Shift>,
+    (x_label, y_label): (&str, &str),
+    ticks: &[String],
+    sets: &[HistogramSet],
+    y_axis: &PlotAxis,
+    style: &PlotStyle,
+) -> BencherResult<()> {
+    root.fill(&WHITE).map_err(render_err)?;
+
+    let n_ticks = ticks.len();
+    let mut tops = vec![0.0; n_ticks];
+    for set in sets {
+        for (tick, b) in set.values.iter().enumerate() {
+            if let Some(b) = b {
+                tops[tick] += b.v;
+            }
+        }
+    }
+    let y_range = y_axis.plot_range(tops.iter().copied(), (Some(0.0), None));
+    let base = y_axis.base(&y_range);
+
+    let mut chart = ChartBuilder::on(&root)
+        .margin(20)
+        .x_label_area_size(50)
+        .y_label_area_size(80)
+        .build_cartesian_2d(-0.5..(n_ticks as f64 - 0.5), y_axis.coord(y_range))
+        .map_err(render_err)?;
+
+    let tick_label = |x: &f64| {
+        let idx = x.round();
+        if (x - idx).abs() < 1e-6 && idx >= 0.0 && (idx as usize) < n_ticks {
+            ticks[idx as usize].clone()
+        } else {
+            String::new()
+        }
+    };
+    chart
+        .configure_mesh()
+        .disable_x_mesh()
+        .x_labels(n_ticks)
+        .x_label_formatter(&tick_label)
+        .y_label_formatter(&|c| y_axis.tick_label(c))
+        .x_desc(x_label)
+        .y_desc(y_label)
+        .label_style(("sans-serif", 16))
+        .axis_desc_style(("sans-serif", 20))
+        .draw()
+        .map_err(render_err)?;
+
+    let mut bases = vec![0.0; n_ticks];
+    for set in sets {
+        let color = color(&set.style);
+        let segments = set
+            .values
+            .iter()
+            .enumerate()
+            .filter_map(|(tick, b)| b.as_ref().map(|b| (tick, b.v)))
+            .map(|(tick, v)| {
+                let bottom = bases[tick];
+                bases[tick] += v;
+                let bottom = match y_axis.plot_coord(bottom) {
+                    c if c.is_finite() => c,
+                    _ => base,
+                };
+                let top = y_axis.plot_coord(bases[tick]);
+                (tick as f64 - 0.3, bottom, tick as f64 + 0.3, top)
+            })
+            .filter(|(_, _, _, top)| top.is_finite())
+            .collect::<Vec<_>>();
+
+        chart
+            .draw_series(
+                segments
+                    .iter()
+                    .map(|&(x0, y0, x1, y1)| Rectangle::new([(x0, y0), (x1, y1)], color.filled())),
+            )
+            .map_err(render_err)?
+            .label(set.label.as_str())
+            .legend(move |(x, y)| Rectangle::new([(x, y - 6), (x + 12, y + 6)], color.filled()));
+        chart
+            .draw_series(segments.iter().map(|&(x0, y0, x1, y1)| {
+                Rectangle::new([(x0, y0), (x1, y1)], BLACK.stroke_width(1))
+            }))
+            .map_err(render_err)?;
+    }
+
+    if let Some(position) = legend_position(style) {
+        chart
+            .configure_series_labels()
+            .position(position)
+            .label_font(("sans-serif", 16))
+            .background_style(WHITE.mix(0.8))
+            .border_style(BLACK)
+            .draw()
+            .map_err(render_err)?;
+    }
+
+    root.present().map_err(render_err)
+}
+
+/// Render a line chart to `prefix`.{svg,png}
+///
+/// The x tick labels are formatted with `x_formatter` (given the x value), if given
+pub(crate) fn render_lines(
+    prefix: &std::path::Path,
+    format: ImageFormat,
+    axis_labels: (&str, &str),
+    lines: &[Line],
+    x_formatter: Option<&dyn Fn(&f64) -> String>,
+    axes: (&PlotAxis, &PlotAxis),
+    style: &PlotStyle,
+) -> BencherResult<()> {
+    let path = image_path(prefix, format)?;
+    match format {
+        ImageFormat::Svg => draw_lines(
+            SVGBackend::new(&path, image_size(style)).into_drawing_area(),
+            axis_labels,
+            lines,
+            x_formatter,
+            axes,
+            style,
+        ),
+        ImageFormat::Png => draw_lines(
+            BitMapBackend::new(&path, image_size(style)).into_drawing_area(),
+            axis_labels,
+            lines,
+            x_formatter,
+            axes,
+            style,
+        ),
+    }
+}
+
+fn draw_lines<DB: DrawingBackend>(
+    root: DrawingArea<DB, Shift>,
+    (x_label, y_label): (&str, &str),
+    lines: &[Line],
+    x_formatter: Option<&dyn Fn(&f64) -> String>,
+    (x_axis, y_axis): (&PlotAxis, &PlotAxis),
+    style: &PlotStyle,
+) -> BencherResult<()> {
+    root.fill(&WHITE).map_err(render_err)?;
+
+    // points and error bars in plot coordinates, leaving out the ones which cannot be drawn
+    // (e.g., non-positive values on logarithmic axes)
+    let bar = |axis: &PlotAxis, bar: Option<(f64, f64)>| {
+        bar.map(|(min, max)| (axis.plot_coord(min), axis.plot_coord(max)))
+            .filter(|(min, max)| min.is_finite() && max.is_finite())
+    };
+    let lines = lines
+        .iter()
+        .map(|line| Line {
+            label: line.label.clone(),
+            style: line.style.clone(),
+            points: line
+                .points
+                .iter()
+                .map(|p| LinePoint {
+                    x: x_axis.plot_coord(p.x),
+                    y: y_axis.plot_coord(p.y),
+                    x_bar: bar(x_axis, p.x_bar),
+                    y_bar: bar(y_axis, p.y_bar),
+                })
+                .filter(|p| p.x.is_finite() && p.y.is_finite())
+                .collect(),
+            joined: line.joined,
+        })
+        .collect::<Vec<_>>();
+
+    let points = || lines.iter().flat_map(|line| line.points.iter());
+    let x_range = x_axis.plot_range(
+        points().flat_map(|p| {
+            let (min, max) = p.x_bar.unwrap_or((p.x, p.x));
+            [p.x, min, max].map(|c| x_axis.value_at(c))
+        }),
+        (None, None),
+    );
+    let y_range = y_axis.plot_range(
+        points().flat_map(|p| {
+            let (min, max) = p.y_bar.unwrap_or((p.y, p.y));
+            [p.y, min, max].map(|c| y_axis.value_at(c))
+        }),
+        (None, None),
+    );
+
+    let mut chart = ChartBuilder::on(&root)
+        .margin(20)
+        .x_label_area_size(50)
+        .y_label_area_size(80)
+        .build_cartesian_2d(x_axis.coord(x_range), y_axis.coord(y_range))
+        .map_err(render_err)?;
+
+    let x_tick_label = |c: &f64| match x_formatter {
+        Some(x_formatter) => x_formatter(&x_axis.value_at(*c)),
+        None => x_axis.tick_label(c),
+    };
+    chart
+        .configure_mesh()
+        .x_desc(x_label)
+        .y_desc(y_label)
+        .x_label_formatter(&x_tick_label)
+        .y_label_formatter(&|c| y_axis.tick_label(c))
+        .label_style(("sans-serif", 16))
+        .axis_desc_style(("sans-serif", 20))
+        .draw()
+        .map_err(render_err)?;
+
+    for line in &lines {
+        let color = color(&line.style);
+        let line_style = color.stroke_width(style.line_width_or(2.0).round().max(1.0) as u32);
+        let radius = (2.0 * style.point_size_or(2.0)).round().max(1.0) as u32;
+
+        let series = if line.joined {
+            chart.draw_series(LineSeries::new(
+                line.points.iter().map(|p| (p.x, p.y)),
+                line_style,
+            ))
+        } else {
+            chart.draw_series(std::iter::empty::<Circle<(f64, f64), u32>>())
+        }
+        .map_err(render_err)?;
+        series
+            .label(line.label.as_str())
+            .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], line_style));
+
+        let points = line.points.iter().map(|p| (p.x, p.y));
+        match Marker::from_point_type(line.style.point_type) {
+            Marker::Cross => {
+                chart.draw_series(points.map(|p| Cross::new(p, radius, color.stroke_width(2))))
+            }
+            Marker::Square => chart.draw_series(points.map(|p| {
+                let r = radius as i32;
+                EmptyElement::at(p) + Rectangle::new([(-r, -r), (r, r)], color.filled())
+            })),
+            Marker::Circle => {
+                chart.draw_series(points.map(|p| Circle::new(p, radius, color.filled())))
+            }
+            Marker::Triangle => {
+                chart.draw_series(points.map(|p| TriangleMarker::new(p, radius, color.filled())))
+            }
+        }
+        .map_err(render_err)?;
+        chart
+            .draw_series(line.points.iter().filter_map(|p| {
+                let (min, max) = p.y_bar?;
+                Some(ErrorBar::new_vertical(p.x, min, p.y, max, line_style, 10))
+            }))
+            .map_err(render_err)?;
+        chart
+            .draw_series(line.points.iter().filter_map(|p| {
+                let (min, max) = p.x_bar?;
+                Some(ErrorBar::new_horizontal(p.y, min, p.x, max, line_style, 10))
+            }))
+            .map_err(render_err)?;
+    }
+
+    if let Some(position) = legend_position(style) {
+        chart
+            .configure_series_labels()
+            .position(position)
+            .label_font(("sans-serif", 16))
+            .background_style(WHITE.mix(0.8))
+            .border_style(BLACK)
+            .draw()
+            .map_err(render_err)?;
+    }
+
+    root.present().map_err(render_err)
+}
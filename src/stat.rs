@@ -14,7 +14,14 @@ pub fn integer_median(sorted_sample: &Vec<impl Into<i64> + Clone>) -> i64 {
 }
 
 pub fn integer_percentile(sorted_sample: &Vec<impl Into<i64> + Clone>, percentile: usize) -> i64 {
-    let n = (sorted_sample.len() as f64 * (percentile as f64 / 100.0)).ceil() as usize;
+    integer_fractional_percentile(sorted_sample, percentile as f64)
+}
+
+pub fn integer_fractional_percentile(
+    sorted_sample: &Vec<impl Into<i64> + Clone>,
+    percentile: f64,
+) -> i64 {
+    let n = (sorted_sample.len() as f64 * (percentile / 100.0)).ceil() as usize;
     if n < sorted_sample.len() {
         sorted_sample[n].clone().into()
     } else {
@@ -38,10 +45,476 @@ pub fn float_median(sorted_sample: &Vec<impl Into<f64> + Clone>) -> f64 {
 }
 
 pub fn float_percentile(sorted_sample: &Vec<impl Into<f64> + Clone>, percentile: usize) -> f64 {
-    let n = (sorted_sample.len() as f64 * (percentile as f64 / 100.0)).ceil() as usize;
+    float_fractional_percentile(sorted_sample, percentile as f64)
+}
+
+pub fn float_fractional_percentile(
+    sorted_sample: &Vec<impl Into<f64> + Clone>,
+    percentile: f64,
+) -> f64 {
+    let n = (sorted_sample.len() as f64 * (percentile / 100.0)).ceil() as usize;
     if n < sorted_sample.len() {
         sorted_sample[n].clone().into()
     } else {
         sorted_sample[sorted_sample.len() - 1].clone().into()
     }
 }
+
+/// Mean and (unbiased) variance of a sample
+fn mean_variance(sample: &[f64]) -> (f64, f64) {
+    let n = sample.len() as f64;
+    let mean = sample.iter().sum::<f64>() / n;
+    let variance = sample.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1.0);
+    (mean, variance)
+}
+
+/// Result of a Mann–Whitney U test
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MannWhitneyU {
+    /// U statistic of the second sample
+    pub u: f64,
+
+    /// Two-sided p-value (normal approximation, with tie and continuity corrections)
+    pub p_value: f64,
+
+    /// Rank-biserial correlation, in [-1, 1]: positive when the second sample tends to be larger
+    pub rank_biserial: f64,
+}
+
+/// Mann–Whitney U test of whether two samples come from the same distribution
+///
+/// Returns None if either sample is empty
+pub fn mann_whitney_u(x: &[f64], y: &[f64]) -> Option<MannWhitneyU> {
+    if x.is_empty() || y.is_empty() {
+        return None;
+    }
+
+    let (nx, ny) = (x.len() as f64, y.len() as f64);
+    let n = nx + ny;
+
+    let mut all: Vec<(f64, bool)> = x
+        .iter()
+        .map(|&v| (v, false))
+        .chain(y.iter().map(|&v| (v, true)))
+        .collect();
+    all.sort_unstable_by(|a, b| a.0.total_cmp(&b.0));
+
+    // rank with ties averaged
+    let mut rank_sum_y = 0.0;
+    let mut tie_correction = 0.0;
+    let mut i = 0;
+    while i < all.len() {
+        let mut j = i;
+        while j + 1 < all.len() && all[j + 1].0 == all[i].0 {
+            j += 1;
+        }
+        let rank = (i + j) as f64 / 2.0 + 1.0;
+        let ties = (j - i + 1) as f64;
+        tie_correction += ties.powi(3) - ties;
+        rank_sum_y += rank * all[i..=j].iter().filter(|(_, is_y)| *is_y).count() as f64;
+        i = j + 1;
+    }
+
+    let u_y = rank_sum_y - ny * (ny + 1.0) / 2.0;
+    let u_x = nx * ny - u_y;
+    let mean = nx * ny / 2.0;
+    let sigma = (nx * ny / 12.0 * ((n + 1.0) - tie_correction / (n * (n - 1.0)))).sqrt();
+
+    let p_value = if sigma > 0.0 {
+        let z = ((u_y - mean).abs() - 0.5).max(0.0) / sigma;
+        erfc(z / std::f64::consts::SQRT_2).min(1.0)
+    } else {
+        1.0
+    };
+
+    Some(MannWhitneyU {
+        u: u_y,
+        p_value,
+        rank_biserial: (u_y - u_x) / (nx * ny),
+    })
+}
+
+/// Result of a Welch's t-test
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WelchT {
+    /// t statistic of the difference of the means (second minus first)
+    pub t: f64,
+
+    /// Degrees of freedom (Welch–Satterthwaite)
+    pub df: f64,
+
+    /// Two-sided p-value
+    pub p_value: f64,
+
+    /// Cohen's d: difference of the means over the average standard deviation
+    pub cohen_d: f64,
+}
+
+/// Welch's t-test of whether two samples have the same mean (without assuming equal variances)
+///
+/// Returns None if either sample has less than two values
+pub fn welch_t_test(x: &[f64], y: &[f64]) -> Option<WelchT> {
+    if x.len() < 2 || y.len() < 2 {
+        return None;
+    }
+
+    let (nx, ny) = (x.len() as f64, y.len() as f64);
+    let (mean_x, var_x) = mean_variance(x);
+    let (mean_y, var_y) = mean_variance(y);
+    let diff = mean_y - mean_x;
+
+    let (se_x, se_y) = (var_x / nx, var_y / ny);
+    let se = (se_x + se_y).sqrt();
+    let pooled_sd = ((var_x + var_y) / 2.0).sqrt();
+
+    if se == 0.0 {
+        let (t, p_value, cohen_d) = if diff == 0.0 {
+            (0.0, 1.0, 0.0)
+        } else {
+            (
+                diff.signum() * f64::INFINITY,
+                0.0,
+                diff.signum() * f64::INFINITY,
+            )
+        };
+        return Some(WelchT {
+            t,
+            df: nx + ny - 2.0,
+            p_value,
+            cohen_d,
+        });
+    }
+
+    let t = diff / se;
+    let df = (se_x + se_y).powi(2) / (se_x.powi(2) / (nx - 1.0) + se_y.powi(2) / (ny - 1.0));
+    let p_value = regularized_incomplete_beta(df / (df + t * t), df / 2.0, 0.5).min(1.0);
+
+    Some(WelchT {
+        t,
+        df,
+        p_value,
+        cohen_d: diff / pooled_sd,
+    })
+}
+
+/// Bootstrap confidence interval of the ratio of the median of the second sample
+/// over the median of the first one
+///
+/// `confidence` is the coverage of the interval (e.g., 0.95), estimated with `resamples` resamples
+/// of each sample (drawn with a fixed seed, so the interval is reproducible).
+/// Returns None if either sample is empty or the resampled medians of the first sample are always 0
+pub fn bootstrap_median_ratio(
+    x: &[f64],
+    y: &[f64],
+    confidence: f64,
+    resamples: usize,
+) -> Option<(f64, f64)> {
+    if x.is_empty() || y.is_empty() || resamples == 0 {
+        return None;
+    }
+
+    let mut rng = SplitMix64(0x0062_656e_6368_6572);
+    let mut resample_median = |sample: &[f64]| {
+        let mut resample = (0..sample.len())
+            .map(|_| sample[(rng.next() % sample.len() as u64) as usize])
+            .collect::<Vec<_>>();
+        resample.sort_unstable_by(f64::total_cmp);
+        float_median(&resample)
+    };
+
+    let mut ratios = Vec::with_capacity(resamples);
+    for _ in 0..resamples {
+        let median_x = resample_median(x);
+        let median_y = resample_median(y);
+        if median_x != 0.0 {
+            ratios.push(median_y / median_x);
+        }
+    }
+    if ratios.is_empty() {
+        return None;
+    }
+
+    ratios.sort_unstable_by(f64::total_cmp);
+    let tail = (1.0 - confidence) / 2.0 * 100.0;
+    Some((
+        float_fractional_percentile(&ratios, tail),
+        float_fractional_percentile(&ratios, 100.0 - tail),
+    ))
+}
+
+/// Cumulative distribution function of the standard normal distribution
+pub fn normal_cdf(z: f64) -> f64 {
+    0.5 * erfc(-z / std::f64::consts::SQRT_2)
+}
+
+/// Quantile function of the standard normal distribution (by bisection of the CDF)
+pub fn normal_quantile(p: f64) -> f64 {
+    let (mut lo, mut hi) = (-40.0, 40.0);
+    for _ in 0..200 {
+        let mid = (lo + hi) / 2.0;
+        if normal_cdf(mid) < p {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    (lo + hi) / 2.0
+}
+
+/// Complementary error function (fractional error below 1.2e-7)
+fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let r = t
+        * (-z * z - 1.26551223
+            + t * (1.00002368
+                + t * (0.37409196
+                    + t * (0.09678418
+                        + t * (-0.18628806
+                            + t * (0.27886807
+                                + t * (-1.13520398
+                                    + t * (1.48851587 + t * (-0.82215223 + t * 0.17087277)))))))))
+            .exp();
+    if x >= 0.0 {
+        r
+    } else {
+        2.0 - r
+    }
+}
+
+/// Natural logarithm of the gamma function (Lanczos approximation)
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 6] = [
+        76.18009172947146,
+        -86.50532032941677,
+        24.01409824083091,
+        -1.231739572450155,
+        0.1208650973866179e-2,
+        -0.5395239384953e-5,
+    ];
+
+    let tmp = x + 5.5;
+    let tmp = tmp - (x + 0.5) * tmp.ln();
+    let series = COEFFICIENTS
+        .iter()
+        .enumerate()
+        .fold(1.000000000190015, |acc, (i, c)| {
+            acc + c / (x + 1.0 + i as f64)
+        });
+    -tmp + (2.5066282746310005 * series / x).ln()
+}
+
+/// Regularized incomplete beta function I_x(a, b)
+fn regularized_incomplete_beta(x: f64, a: f64, b: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    if x >= 1.0 {
+        return 1.0;
+    }
+
+    let front =
+        (ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b) + a * x.ln() + b * (1.0 - x).ln()).exp();
+    if x < (a + 1.0) / (a + b + 2.0) {
+        front * beta_continued_fraction(x, a, b) / a
+    } else {
+        1.0 - front * beta_continued_fraction(1.0 - x, b, a) / b
+    }
+}
+
+/// Continued fraction for the incomplete beta function (modified Lentz's method)
+fn beta_continued_fraction(x: f64, a: f64, b: f64) -> f64 {
+    const TINY: f64 = 1e-300;
+
+    let mut c = 1.0;
+    let mut d = 1.0 - (a + b) * x / (a + 1.0);
+    if d.abs() < TINY {
+        d = TINY;
+    }
+    d = 1.0 / d;
+    let mut h = d;
+
+    for m in 1..300 {
+        let m = m as f64;
+
+        let even = m * (b - m) * x / ((a + 2.0 * m - 1.0) * (a + 2.0 * m));
+        d = 1.0 + even * d;
+        d = if d.abs() < TINY { 1.0 / TINY } else { 1.0 / d };
+        c = 1.0 + even / c;
+        if c.abs() < TINY {
+            c = TINY;
+        }
+        h *= d * c;
+
+        let odd = -(a + m) * (a + b + m) * x / ((a + 2.0 * m) * (a + 2.0 * m + 1.0));
+        d = 1.0 + odd * d;
+        d = if d.abs() < TINY { 1.0 / TINY } else { 1.0 / d };
+        c = 1.0 + odd / c;
+        if c.abs() < TINY {
+            c = TINY;
+        }
+        let delta = d * c;
+        h *= delta;
+
+        if (delta - 1.0).abs() < 1e-12 {
+            break;
+        }
+    }
+
+    h
+}
+
+/// Small deterministic random number generator, for resampling
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64, tolerance: f64) {
+        assert!(
+            (actual - expected).abs() <= tolerance,
+            "{} is not within {} of {}",
+            actual,
+            tolerance,
+            expected
+        );
+    }
+
+    #[test]
+    fn mann_whitney_u_separated() {
+        // every value of the second sample is larger: U = nx * ny
+        let test = mann_whitney_u(&[1.0, 2.0, 3.0, 4.0, 5.0], &[6.0, 7.0, 8.0, 9.0, 10.0]).unwrap();
+        assert_eq!(test.u, 25.0);
+        assert_eq!(test.rank_biserial, 1.0);
+        // R: wilcox.test(1:5, 6:10, exact = FALSE)
+        assert_close(test.p_value, 0.01219, 1e-5);
+
+        let test = mann_whitney_u(&[6.0, 7.0, 8.0, 9.0, 10.0], &[1.0, 2.0, 3.0, 4.0, 5.0]).unwrap();
+        assert_eq!(test.u, 0.0);
+        assert_eq!(test.rank_biserial, -1.0);
+        assert_close(test.p_value, 0.01219, 1e-5);
+    }
+
+    #[test]
+    fn mann_whitney_u_ties() {
+        // ties between the samples count as half
+        let test = mann_whitney_u(&[1.1, 2.2, 2.2, 3.3, 4.4], &[2.2, 3.3, 5.5, 6.6, 7.7]).unwrap();
+        assert_eq!(test.u, 20.5);
+        assert_close(test.rank_biserial, 0.64, 1e-12);
+        // R: wilcox.test(c(1.1, 2.2, 2.2, 3.3, 4.4), c(2.2, 3.3, 5.5, 6.6, 7.7), exact = FALSE)
+        assert_close(test.p_value, 0.111612, 1e-6);
+    }
+
+    #[test]
+    fn mann_whitney_u_degenerate() {
+        assert!(mann_whitney_u(&[], &[1.0]).is_none());
+        assert!(mann_whitney_u(&[1.0], &[]).is_none());
+
+        // all values tied: no variance, no evidence of a difference
+        let test = mann_whitney_u(&[1.0, 1.0], &[1.0, 1.0]).unwrap();
+        assert_eq!(test.u, 2.0);
+        assert_eq!(test.p_value, 1.0);
+
+        // NaN does not panic
+        assert!(mann_whitney_u(&[1.0, f64::NAN], &[2.0, 3.0]).is_some());
+    }
+
+    #[test]
+    fn welch_t_test_reference() {
+        // R: t.test(c(2, 4, 6, 8, 10), 1:5)
+        let test = welch_t_test(&[1.0, 2.0, 3.0, 4.0, 5.0], &[2.0, 4.0, 6.0, 8.0, 10.0]).unwrap();
+        assert_close(test.t, 1.897367, 1e-6);
+        assert_close(test.df, 5.882353, 1e-6);
+        assert_close(test.p_value, 0.107531, 1e-6);
+        assert_close(test.cohen_d, 1.2, 1e-12);
+
+        assert!(welch_t_test(&[1.0], &[1.0, 2.0]).is_none());
+    }
+
+    #[test]
+    fn welch_t_test_zero_variance() {
+        let test = welch_t_test(&[3.0, 3.0, 3.0], &[3.0, 3.0]).unwrap();
+        assert_eq!(test.t, 0.0);
+        assert_eq!(test.df, 3.0);
+        assert_eq!(test.p_value, 1.0);
+        assert_eq!(test.cohen_d, 0.0);
+
+        let test = welch_t_test(&[3.0, 3.0, 3.0], &[2.0, 2.0]).unwrap();
+        assert_eq!(test.t, f64::NEG_INFINITY);
+        assert_eq!(test.p_value, 0.0);
+        assert_eq!(test.cohen_d, f64::NEG_INFINITY);
+    }
+
+    #[test]
+    fn bootstrap_median_ratio_known() {
+        // constant samples have a single possible ratio
+        assert_eq!(
+            bootstrap_median_ratio(&[2.0, 2.0, 2.0], &[3.0, 3.0], 0.95, 100),
+            Some((1.5, 1.5))
+        );
+
+        let x = (1..=20).map(|v| v as f64).collect::<Vec<_>>();
+        let y = x.iter().map(|v| v * 2.0).collect::<Vec<_>>();
+        let (lo, hi) = bootstrap_median_ratio(&x, &y, 0.95, 1000).unwrap();
+        assert!(
+            lo <= 2.0 && 2.0 <= hi,
+            "[{}, {}] does not contain 2",
+            lo,
+            hi
+        );
+        assert!(lo > 1.0 && hi < 4.0, "[{}, {}] is too wide", lo, hi);
+
+        // resampled with a fixed seed
+        assert_eq!(bootstrap_median_ratio(&x, &y, 0.95, 1000), Some((lo, hi)));
+    }
+
+    #[test]
+    fn bootstrap_median_ratio_degenerate() {
+        assert_eq!(bootstrap_median_ratio(&[], &[1.0], 0.95, 100), None);
+        assert_eq!(bootstrap_median_ratio(&[1.0], &[1.0], 0.95, 0), None);
+        assert_eq!(bootstrap_median_ratio(&[0.0, 0.0], &[1.0], 0.95, 100), None);
+    }
+
+    #[test]
+    fn erfc_known() {
+        assert_close(erfc(0.0), 1.0, 1e-7);
+        assert_close(erfc(0.5), 0.4795001221869535, 1e-7);
+        assert_close(erfc(1.0), 0.15729920705028513, 1e-7);
+        assert_close(erfc(-1.0), 1.842700792949715, 1e-7);
+        assert_close(erfc(2.0), 0.004677734981047265, 1e-8);
+    }
+
+    #[test]
+    fn normal_quantile_known() {
+        assert_close(normal_quantile(0.975), 1.959964, 1e-5);
+        assert_close(normal_quantile(0.5), 0.0, 1e-6);
+        assert_close(normal_quantile(0.05), -1.644854, 1e-5);
+        assert_close(normal_cdf(1.959964), 0.975, 1e-6);
+    }
+
+    #[test]
+    fn regularized_incomplete_beta_known() {
+        assert_eq!(regularized_incomplete_beta(0.0, 2.0, 3.0), 0.0);
+        assert_eq!(regularized_incomplete_beta(1.0, 2.0, 3.0), 1.0);
+
+        // I_x(1, 1) = x and I_x(a, 1) = x^a
+        assert_close(regularized_incomplete_beta(0.3, 1.0, 1.0), 0.3, 1e-10);
+        assert_close(regularized_incomplete_beta(0.6, 3.0, 1.0), 0.216, 1e-10);
+        // symmetric about 0.5 when a = b
+        assert_close(regularized_incomplete_beta(0.5, 4.5, 4.5), 0.5, 1e-10);
+        // I_x(2, 3) = 6x^2(1-x)^2 + 4x^3(1-x) + x^4
+        assert_close(regularized_incomplete_beta(0.4, 2.0, 3.0), 0.5248, 1e-10);
+        assert_close(regularized_incomplete_beta(0.9, 2.0, 3.0), 0.9963, 1e-10);
+    }
+}
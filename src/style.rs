@@ -0,0 +1,631 @@
+use crate::*;
+use std::collections::BTreeMap;
+
+/// Colors of the lines (and bars) of the plots, by default
+pub(crate) const COLORS: [&str; 5] = ["f6511d", "ffb400", "00a6ed", "7fb800", "0d2c54"];
+
+/// Number of point types the lines of a plot cycle through, by default
+const N_POINT_TYPES: usize = 6;
+
+/// gnuplot terminal the scripts draw to
+#[derive(
+    serde::Deserialize, serde::Serialize, Clone, Copy, Default, PartialEq, Eq, Debug, Hash,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum Terminal {
+    /// Encapsulated postscript
+    #[default]
+    #[serde(alias = "postscript")]
+    Eps,
+
+    /// Encapsulated postscript, with the text in a LaTeX file (which includes the figure)
+    Epslatex,
+
+    #[serde(alias = "pdf")]
+    Pdfcairo,
+
+    #[serde(alias = "png")]
+    Pngcairo,
+
+    Svg,
+}
+
+impl Terminal {
+    /// Extension of the file gnuplot writes to
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Terminal::Eps => "eps",
+            Terminal::Epslatex => "tex",
+            Terminal::Pdfcairo => "pdf",
+            Terminal::Pngcairo => "png",
+            Terminal::Svg => "svg",
+        }
+    }
+
+    /// Size of the plots, by default: in centimeters or in pixels, depending on the terminal
+    fn default_size(&self) -> &'static str {
+        match self {
+            Terminal::Eps | Terminal::Epslatex | Terminal::Pdfcairo => "12cm,8cm",
+            Terminal::Pngcairo | Terminal::Svg => "1024,768",
+        }
+    }
+
+    fn gnuplot(&self, size: &str, font: &str) -> String {
+        match self {
+            Terminal::Eps => format!(
+                "set terminal postscript eps colour size {} enhanced font '{}'",
+                size, font
+            ),
+            Terminal::Epslatex => {
+                format!("set terminal epslatex colour size {} font '{}'", size, font)
+            }
+            Terminal::Pdfcairo => format!(
+                "set terminal pdfcairo enhanced color size {} font '{}'",
+                size, font
+            ),
+            Terminal::Pngcairo => format!(
+                "set terminal pngcairo enhanced color size {} font '{}'",
+                size, font
+            ),
+            Terminal::Svg => format!("set terminal svg enhanced size {} font '{}'", size, font),
+        }
+    }
+}
+
+impl std::fmt::Display for Terminal {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Terminal::Eps => write!(f, "eps"),
+            Terminal::Epslatex => write!(f, "epslatex"),
+            Terminal::Pdfcairo => write!(f, "pdfcairo"),
+            Terminal::Pngcairo => write!(f, "pngcairo"),
+            Terminal::Svg => write!(f, "svg"),
+        }
+    }
+}
+
+impl std::str::FromStr for Terminal {
+    type Err = BencherError;
+    fn from_str(s: &str) -> BencherResult<Terminal> {
+        match s.to_lowercase().as_str() {
+            "eps" | "postscript" => Ok(Terminal::Eps),
+            "epslatex" => Ok(Terminal::Epslatex),
+            "pdfcairo" | "pdf" => Ok(Terminal::Pdfcairo),
+            "pngcairo" | "png" => Ok(Terminal::Pngcairo),
+            "svg" => Ok(Terminal::Svg),
+            _ => Err(BencherError::UnknownTerminal(s.to_string())),
+        }
+    }
+}
+
+/// Where the legend of the plots is placed
+#[derive(
+    serde::Deserialize, serde::Serialize, Clone, Copy, Default, PartialEq, Eq, Debug, Hash,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum LegendPosition {
+    #[default]
+    OutsideAbove,
+    OutsideBelow,
+    OutsideRight,
+    InsideTopLeft,
+    InsideTopRight,
+    InsideBottomLeft,
+    InsideBottomRight,
+
+    /// No legend
+    #[serde(alias = "none")]
+    Hidden,
+}
+
+impl LegendPosition {
+    fn gnuplot(&self) -> &'static str {
+        match self {
+            LegendPosition::OutsideAbove => "set key outside above",
+            LegendPosition::OutsideBelow => "set key outside below",
+            LegendPosition::OutsideRight => "set key outside right",
+            LegendPosition::InsideTopLeft => "set key inside top left",
+            LegendPosition::InsideTopRight => "set key inside top right",
+            LegendPosition::InsideBottomLeft => "set key inside bottom left",
+            LegendPosition::InsideBottomRight => "set key inside bottom right",
+            LegendPosition::Hidden => "unset key",
+        }
+    }
+}
+
+impl std::fmt::Display for LegendPosition {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            LegendPosition::OutsideAbove => write!(f, "outside_above"),
+            LegendPosition::OutsideBelow => write!(f, "outside_below"),
+            LegendPosition::OutsideRight => write!(f, "outside_right"),
+            LegendPosition::InsideTopLeft => write!(f, "inside_top_left"),
+            LegendPosition::InsideTopRight => write!(f, "inside_top_right"),
+            LegendPosition::InsideBottomLeft => write!(f, "inside_bottom_left"),
+            LegendPosition::InsideBottomRight => write!(f, "inside_bottom_right"),
+            LegendPosition::Hidden => write!(f, "hidden"),
+        }
+    }
+}
+
+impl std::str::FromStr for LegendPosition {
+    type Err = BencherError;
+    fn from_str(s: &str) -> BencherResult<LegendPosition> {
+        match s.to_lowercase().replace('-', "_").as_str() {
+            "outside_above" => Ok(LegendPosition::OutsideAbove),
+            "outside_below" => Ok(LegendPosition::OutsideBelow),
+            "outside_right" => Ok(LegendPosition::OutsideRight),
+            "inside_top_left" => Ok(LegendPosition::InsideTopLeft),
+            "inside_top_right" => Ok(LegendPosition::InsideTopRight),
+            "inside_bottom_left" => Ok(LegendPosition::InsideBottomLeft),
+            "inside_bottom_right" => Ok(LegendPosition::InsideBottomRight),
+            "hidden" | "none" => Ok(LegendPosition::Hidden),
+            _ => Err(BencherError::UnknownLegendPosition(s.to_string())),
+        }
+    }
+}
+
+/// Style of the lines (or bars) of a label, as set in the `labels` of the plot style
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub struct LabelStyle {
+    /// Hex RGB code (e.g., `f6511d` or `#f6511d`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) color: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) point_type: Option<u32>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) dash_type: Option<u32>,
+}
+
+impl LabelStyle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn color(mut self, color: Option<String>) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn point_type(mut self, point_type: Option<u32>) -> Self {
+        self.point_type = point_type;
+        self
+    }
+
+    pub fn dash_type(mut self, dash_type: Option<u32>) -> Self {
+        self.dash_type = dash_type;
+        self
+    }
+}
+
+/// Style of a line (or of the bars of a set) in a plot, as drawn by every plot writer
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct SeriesStyle {
+    /// Hex RGB code, without `#`
+    pub(crate) color: String,
+    pub(crate) point_type: u32,
+    pub(crate) dash_type: Option<u32>,
+}
+
+impl SeriesStyle {
+    /// gnuplot option setting the dash type (empty if not set)
+    pub(crate) fn gnuplot_dash_type(&self) -> String {
+        match self.dash_type {
+            Some(dash_type) => format!(" dashtype {}", dash_type),
+            None => String::new(),
+        }
+    }
+}
+
+/// Hash of a label (64-bit FNV-1a), which does not change between runs or versions
+fn label_hash(label: &str) -> u64 {
+    label.bytes().fold(0xcbf29ce484222325, |hash, b| {
+        (hash ^ b as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// How the plots are styled, as set in the `style` section of the config
+/// (or on the command line)
+///
+/// Options which are not set keep the previous look of the plots:
+/// eps figures of 12cm by 8cm in Helvetica 20, with the legend outside (above the plot)
+#[derive(Clone, Debug, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct PlotStyle {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) terminal: Option<Terminal>,
+
+    /// Size of the plots, as given to gnuplot (e.g., `12cm,8cm` or `800,600`)
+    ///
+    /// Sizes in pixels are also used by the renderer
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) size: Option<String>,
+
+    /// Font of the plots, as given to gnuplot (e.g., `Helvetica,20`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) font: Option<String>,
+
+    /// Colors of the lines (and bars), as hex RGB codes (e.g., `f6511d` or `#f6511d`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) palette: Option<Vec<String>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) line_width: Option<f64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) point_size: Option<f64>,
+
+    /// gnuplot point types of the lines, in order
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) point_types: Option<Vec<u32>>,
+
+    /// gnuplot dash type of the lines
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) dash_type: Option<u32>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) legend: Option<LegendPosition>,
+
+    /// Styles of the lines (or sets) with a given label, in every plot
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) labels: Option<BTreeMap<String, LabelStyle>>,
+}
+
+impl PlotStyle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn terminal(mut self, terminal: Option<Terminal>) -> Self {
+        self.terminal = terminal;
+        self
+    }
+
+    pub fn size(mut self, size: Option<String>) -> Self {
+        self.size = size;
+        self
+    }
+
+    pub fn font(mut self, font: Option<String>) -> Self {
+        self.font = font;
+        self
+    }
+
+    pub fn palette(mut self, palette: Option<Vec<String>>) -> Self {
+        self.palette = palette;
+        self
+    }
+
+    pub fn line_width(mut self, line_width: Option<f64>) -> Self {
+        self.line_width = line_width;
+        self
+    }
+
+    pub fn point_size(mut self, point_size: Option<f64>) -> Self {
+        self.point_size = point_size;
+        self
+    }
+
+    pub fn point_types(mut self, point_types: Option<Vec<u32>>) -> Self {
+        self.point_types = point_types;
+        self
+    }
+
+    pub fn dash_type(mut self, dash_type: Option<u32>) -> Self {
+        self.dash_type = dash_type;
+        self
+    }
+
+    pub fn legend(mut self, legend: Option<LegendPosition>) -> Self {
+        self.legend = legend;
+        self
+    }
+
+    /// Set the style of the lines (or sets) with a given label
+    pub fn label(mut self, label: impl Into<String>, style: LabelStyle) -> Self {
+        self.labels
+            .get_or_insert_with(BTreeMap::new)
+            .insert(label.into(), style);
+        self
+    }
+
+    /// Override this style with another one (e.g., from the command line)
+    ///
+    /// Only the options set by the other style are overridden,
+    /// while the label styles are overridden label by label
+    pub fn overridden_by(self, other: PlotStyle) -> Self {
+        let labels = match (self.labels, other.labels) {
+            (Some(mut labels), Some(other_labels)) => {
+                labels.extend(other_labels);
+                Some(labels)
+            }
+            (labels, other_labels) => other_labels.or(labels),
+        };
+        PlotStyle {
+            terminal: other.terminal.or(self.terminal),
+            size: other.size.or(self.size),
+            font: other.font.or(self.font),
+            palette: other.palette.or(self.palette),
+            line_width: other.line_width.or(self.line_width),
+            point_size: other.point_size.or(self.point_size),
+            point_types: other.point_types.or(self.point_types),
+            dash_type: other.dash_type.or(self.dash_type),
+            legend: other.legend.or(self.legend),
+            labels,
+        }
+    }
+
+    /// What is wrong with the style, if anything
+    pub(crate) fn problem(&self) -> Option<String> {
+        if let Some(color) = self
+            .palette
+            .iter()
+            .flatten()
+            .chain(
+                self.labels
+                    .iter()
+                    .flatten()
+                    .filter_map(|(_, style)| style.color.as_ref()),
+            )
+            .find(|color| parse_color(color).is_none())
+        {
+            return Some(format!("`{}` is not a hex RGB color", color));
+        }
+        match (
+            &self.palette,
+            &self.point_types,
+            self.line_width,
+            self.point_size,
+        ) {
+            (Some(palette), _, _, _) if palette.is_empty() => Some("the palette is empty".into()),
+            (_, Some(point_types), _, _) if point_types.is_empty() => {
+                Some("the list of point types is empty".into())
+            }
+            (_, _, Some(width), _) if width <= 0.0 => {
+                Some(format!("the line width ({}) is not positive", width))
+            }
+            (_, _, _, Some(size)) if size <= 0.0 => {
+                Some(format!("the point size ({}) is not positive", size))
+            }
+            _ => None,
+        }
+    }
+
+    pub fn validate(&self) -> BencherResult<()> {
+        match self.problem() {
+            Some(problem) => Err(BencherError::InvalidStyle(problem)),
+            None => Ok(()),
+        }
+    }
+
+    /// Path gnuplot writes the plot to: `prefix`, with the extension of the terminal
+    pub(crate) fn output_path(
+        &self,
+        prefix: &std::path::Path,
+    ) -> BencherResult<std::path::PathBuf> {
+        let extension = self.terminal.unwrap_or_default().extension();
+        let mut path: std::path::PathBuf = prefix.into();
+        if !path.set_extension(extension) {
+            return Err(BencherError::PathCreateError(path, extension.to_string()));
+        }
+        Ok(path)
+    }
+
+    /// Start of the gnuplot scripts: the terminal, the output and the legend
+    pub(crate) fn gnuplot_preamble(&self, output_path: &std::path::Path) -> String {
+        let terminal = self.terminal.unwrap_or_default();
+        format!(
+            "reset
+
+{}
+set output '{}'
+
+set border linewidth 0.75
+{}
+",
+            terminal.gnuplot(
+                self.size.as_deref().unwrap_or(terminal.default_size()),
+                self.font.as_deref().unwrap_or("Helvetica,20"),
+            ),
+            output_path.to_string_lossy(),
+            self.legend.unwrap_or_default().gnuplot(),
+        )
+    }
+
+    fn palette_len(&self) -> usize {
+        match &self.palette {
+            Some(palette) if !palette.is_empty() => palette.len(),
+            _ => COLORS.len(),
+        }
+    }
+
+    /// Color (as a hex RGB code, without `#`) of the line (or bar) at a slot of the palette
+    pub(crate) fn color(&self, slot: usize) -> String {
+        match &self.palette {
+            Some(palette) if !palette.is_empty() => palette[slot % palette.len()]
+                .trim_start_matches('#')
+                .to_lowercase(),
+            _ => COLORS[slot % COLORS.len()].to_string(),
+        }
+    }
+
+    pub(crate) fn line_width_or(&self, default: f64) -> f64 {
+        self.line_width.unwrap_or(default)
+    }
+
+    pub(crate) fn point_size_or(&self, default: f64) -> f64 {
+        self.point_size.unwrap_or(default)
+    }
+
+    fn point_types_len(&self) -> usize {
+        match &self.point_types {
+            Some(point_types) if !point_types.is_empty() => point_types.len(),
+            _ => N_POINT_TYPES,
+        }
+    }
+
+    /// Point type of the line at a slot (cycling through the configured ones)
+    fn point_type_or(&self, slot: usize, default: u32) -> u32 {
+        match &self.point_types {
+            Some(point_types) if !point_types.is_empty() => point_types[slot % point_types.len()],
+            _ => default,
+        }
+    }
+
+    /// Style of the series at a given position of a plot whose series are not labelled
+    /// (e.g., the versions of a history)
+    pub(crate) fn indexed_series_style(&self, idx: usize, default_point_type: u32) -> SeriesStyle {
+        SeriesStyle {
+            color: self.color(idx),
+            point_type: self.point_type_or(idx, default_point_type),
+            dash_type: self.dash_type,
+        }
+    }
+
+    /// Styles of the series (lines or sets) of a plot, given their labels
+    ///
+    /// Each label takes the color and the point type given by its hash alone, so that it keeps
+    /// them across plots, whatever the other labels of the plot.
+    /// The point type comes from the high bits of the hash (and the color from the low ones), so
+    /// that labels sharing a color are still told apart by their points.
+    /// The styles set for a label in the config take precedence
+    pub(crate) fn series_styles(
+        &self,
+        labels: &[&str],
+        default_point_type: impl Fn(usize) -> u32,
+        default_dash_type: Option<u32>,
+    ) -> Vec<SeriesStyle> {
+        let n_colors = self.palette_len() as u64;
+        let n_point_types = self.point_types_len() as u64;
+
+        labels
+            .iter()
+            .map(|label| {
+                let hash = label_hash(label);
+                let color_slot = (hash % n_colors) as usize;
+                let point_slot = ((hash >> 32) % n_point_types) as usize;
+                let explicit = self
+                    .labels
+                    .as_ref()
+                    .and_then(|labels| labels.get(*label))
+                    .cloned()
+                    .unwrap_or_default();
+                SeriesStyle {
+                    color: explicit
+                        .color
+                        .map(|color| color.trim_start_matches('#').to_lowercase())
+                        .unwrap_or_else(|| self.color(color_slot)),
+                    point_type: explicit.point_type.unwrap_or_else(|| {
+                        self.point_type_or(point_slot, default_point_type(point_slot))
+                    }),
+                    dash_type: explicit.dash_type.or(self.dash_type).or(default_dash_type),
+                }
+            })
+            .collect()
+    }
+
+    /// gnuplot commands coloring the line types (which histograms use) with the styles of the series
+    pub(crate) fn gnuplot_line_types(series: &[SeriesStyle]) -> String {
+        series
+            .iter()
+            .enumerate()
+            .map(|(idx, style)| {
+                format!(
+                    "set linetype {} linecolor rgb '#{}'\n",
+                    idx + 1,
+                    style.color
+                )
+            })
+            .collect()
+    }
+
+    /// Size (in pixels) of the plots, if given in pixels
+    pub(crate) fn pixel_size(&self) -> Option<(u32, u32)> {
+        let (width, height) = self.size.as_deref()?.split_once(',')?;
+        Some((width.trim().parse().ok()?, height.trim().parse().ok()?))
+    }
+}
+
+/// RGB channels of a hex color (e.g., `f6511d` or `#f6511d`)
+pub(crate) fn parse_color(color: &str) -> Option<(u8, u8, u8)> {
+    let hex = color.strip_prefix('#').unwrap_or(color);
+    if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some((channel(0)?, channel(2)?, channel(4)?))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn series_styles_follow_labels() {
+        let style = PlotStyle::new();
+        let styles = style.series_styles(&["ours", "baseline", "other"], |slot| slot as u32, None);
+        let reordered =
+            style.series_styles(&["other", "ours", "baseline"], |slot| slot as u32, None);
+        assert_eq!(styles[0], reordered[1]);
+        assert_eq!(styles[1], reordered[2]);
+        assert_eq!(styles[2], reordered[0]);
+
+        // other labels do not change the style of a label
+        let alone = style.series_styles(&["ours"], |slot| slot as u32, None);
+        let with_other = style.series_styles(&["zeta", "ours"], |slot| slot as u32, None);
+        assert_eq!(alone[0], with_other[1]);
+    }
+
+    #[test]
+    fn series_styles_across_label_sets() {
+        let style = PlotStyle::new().palette(Some(vec!["000000".into(), "ffffff".into()]));
+        let labels = ["a", "b", "c", "d", "e"];
+        let alone = labels
+            .iter()
+            .map(|label| style.series_styles(&[label], |slot| slot as u32, None)[0].clone())
+            .collect::<Vec<_>>();
+
+        // the same label gets the same style in every set of labels, even when they collide
+        let all = style.series_styles(&labels, |slot| slot as u32, None);
+        let some = style.series_styles(&["e", "c", "a"], |slot| slot as u32, None);
+        assert_eq!(all, alone);
+        assert_eq!(
+            some,
+            vec![alone[4].clone(), alone[2].clone(), alone[0].clone()]
+        );
+        assert!(all
+            .iter()
+            .all(|s| s.color == "000000" || s.color == "ffffff"));
+    }
+
+    #[test]
+    fn series_styles_are_distinct() {
+        // both labels take the same color of the default palette
+        let style = PlotStyle::new();
+        let styles = style.series_styles(&["base", "ours"], |slot| slot as u32, None);
+        assert_eq!(styles[0].color, styles[1].color);
+        assert_ne!(styles[0], styles[1]);
+        assert_ne!(styles[0].point_type, styles[1].point_type);
+    }
+
+    #[test]
+    fn series_styles_explicit() {
+        let style = PlotStyle::new().dash_type(Some(3)).label(
+            "ours",
+            LabelStyle::new()
+                .color(Some("#ABCDEF".into()))
+                .point_type(Some(7)),
+        );
+        let styles = style.series_styles(&["ours", "baseline"], |slot| slot as u32, Some(2));
+        assert_eq!(
+            styles[0],
+            SeriesStyle {
+                color: "abcdef".into(),
+                point_type: 7,
+                dash_type: Some(3),
+            }
+        );
+        assert_eq!(styles[1].dash_type, Some(3));
+    }
+}
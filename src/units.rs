@@ -0,0 +1,455 @@
+use crate::*;
+
+/// Family of a recognized unit, which determines how its values are scaled
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum UnitFamily {
+    /// Seconds, scaled down with SI prefixes (ps, ns, μs, ms, s)
+    Time,
+
+    /// Bytes, scaled up with IEC prefixes (B, KiB, MiB, GiB, TiB, PiB)
+    Bytes,
+
+    /// Operations per second, scaled up with SI prefixes (ops/s, Kops/s, ..., Pops/s)
+    OpsPerSecond,
+}
+
+impl UnitFamily {
+    /// Base unit of the family
+    fn base(&self) -> &'static str {
+        match self {
+            UnitFamily::Time => "s",
+            UnitFamily::Bytes => "B",
+            UnitFamily::OpsPerSecond => "ops/s",
+        }
+    }
+
+    /// Range of magnitudes values of the family are displayed in
+    fn magnitudes(&self) -> (Magnitude, Magnitude) {
+        match self {
+            UnitFamily::Time => (Magnitude::Pico, Magnitude::Normal),
+            UnitFamily::Bytes | UnitFamily::OpsPerSecond => (Magnitude::Normal, Magnitude::Peta),
+        }
+    }
+
+    /// Factor between consecutive magnitudes
+    fn step(&self) -> f64 {
+        match self {
+            UnitFamily::Bytes => 1024.0,
+            _ => 1000.0,
+        }
+    }
+
+    fn prefix(&self, magnitude: Magnitude) -> &'static str {
+        match (self, magnitude) {
+            (UnitFamily::Bytes, Magnitude::Kilo) => "Ki",
+            (UnitFamily::Bytes, Magnitude::Mega) => "Mi",
+            (UnitFamily::Bytes, Magnitude::Giga) => "Gi",
+            (UnitFamily::Bytes, Magnitude::Tera) => "Ti",
+            (UnitFamily::Bytes, Magnitude::Peta) => "Pi",
+            _ => magnitude.prefix(),
+        }
+    }
+}
+
+/// Units of the values of an axis, as written in the config
+#[derive(Clone, Debug, PartialEq)]
+enum Units {
+    /// A recognized unit, along with the size of the unit in the base unit of its family
+    /// (e.g., ms is 1e-3 seconds)
+    Known(UnitFamily, f64),
+
+    /// Any other unit, which is prefixed with the SI magnitude of the values
+    Other(String),
+}
+
+impl Units {
+    fn parse(units: &str) -> Self {
+        let (family, size) = match units.trim() {
+            "ps" => (UnitFamily::Time, 1e-12),
+            "ns" => (UnitFamily::Time, 1e-9),
+            "us" | "μs" | "µs" => (UnitFamily::Time, 1e-6),
+            "ms" => (UnitFamily::Time, 1e-3),
+            "s" | "sec" | "secs" | "second" | "seconds" => (UnitFamily::Time, 1.0),
+            "B" | "byte" | "bytes" => (UnitFamily::Bytes, 1.0),
+            "KiB" => (UnitFamily::Bytes, 1024.0),
+            "MiB" => (UnitFamily::Bytes, 1024.0 * 1024.0),
+            "GiB" => (UnitFamily::Bytes, 1024_f64.powi(3)),
+            "TiB" => (UnitFamily::Bytes, 1024_f64.powi(4)),
+            "PiB" => (UnitFamily::Bytes, 1024_f64.powi(5)),
+            "kB" | "KB" => (UnitFamily::Bytes, 1e3),
+            "MB" => (UnitFamily::Bytes, 1e6),
+            "GB" => (UnitFamily::Bytes, 1e9),
+            "TB" => (UnitFamily::Bytes, 1e12),
+            "PB" => (UnitFamily::Bytes, 1e15),
+            "ops/s" | "op/s" | "ops/sec" => (UnitFamily::OpsPerSecond, 1.0),
+            "Kops/s" | "kops/s" => (UnitFamily::OpsPerSecond, 1e3),
+            "Mops/s" => (UnitFamily::OpsPerSecond, 1e6),
+            "Gops/s" => (UnitFamily::OpsPerSecond, 1e9),
+            "Tops/s" => (UnitFamily::OpsPerSecond, 1e12),
+            "Pops/s" => (UnitFamily::OpsPerSecond, 1e15),
+            other => return Units::Other(other.to_string()),
+        };
+        Units::Known(family, size)
+    }
+
+    /// Magnitude a single value is best displayed in
+    fn magnitude(&self, v: &Value) -> Magnitude {
+        match self {
+            Units::Other(_) => v.magnitude(),
+            Units::Known(family, size) => {
+                let (min, max) = family.magnitudes();
+                let base = (v.as_float() * size).abs();
+                if base == 0.0 || !base.is_finite() {
+                    return Magnitude::Normal.clamp(min, max);
+                }
+
+                let exponent = (base.log(family.step()) + 1e-9).floor() as i32;
+                Magnitude::from_exponent(exponent).clamp(min, max)
+            }
+        }
+    }
+}
+
+/// How the values of an axis are formatted, as set for an experiment in the config
+/// (or on the command line)
+///
+/// By default, the magnitude is the most common one among the values, and the number of decimal
+/// places depends on it. A fixed number of decimal places (`precision`) and a number of
+/// significant figures are mutually exclusive
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, Hash, serde::Deserialize, serde::Serialize,
+)]
+pub struct AxisFormat {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) magnitude: Option<Magnitude>,
+
+    /// Number of decimal places
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) precision: Option<usize>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) significant_figures: Option<usize>,
+}
+
+impl AxisFormat {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn magnitude(mut self, magnitude: Option<Magnitude>) -> Self {
+        self.magnitude = magnitude;
+        self
+    }
+
+    pub fn precision(mut self, precision: Option<usize>) -> Self {
+        self.precision = precision;
+        self
+    }
+
+    pub fn significant_figures(mut self, significant_figures: Option<usize>) -> Self {
+        self.significant_figures = significant_figures;
+        self
+    }
+
+    pub(crate) fn is_conflicting(&self) -> bool {
+        self.precision.is_some() && self.significant_figures.is_some()
+    }
+
+    /// Override this format with another one (e.g., from the command line)
+    ///
+    /// Setting either the precision or the significant figures overrides both
+    pub fn overridden_by(self, other: AxisFormat) -> Self {
+        let digits = if other.precision.is_some() || other.significant_figures.is_some() {
+            other
+        } else {
+            self
+        };
+        AxisFormat {
+            magnitude: other.magnitude.or(self.magnitude),
+            precision: digits.precision,
+            significant_figures: digits.significant_figures,
+        }
+    }
+}
+
+/// Format a number with a given number of significant figures (at least one)
+///
+/// Digits left of the decimal point are never dropped, but are rounded (e.g., 12345 is 12300
+/// with three significant figures)
+fn format_significant(v: f64, figures: usize) -> String {
+    let figures = figures.max(1) as i32;
+    if v == 0.0 || !v.is_finite() {
+        return format!("{:.*}", (figures - 1) as usize, v);
+    }
+
+    let exponent = v.abs().log10().floor() as i32;
+    let decimals = figures - 1 - exponent;
+    if decimals >= 0 {
+        format!("{:.*}", decimals as usize, v)
+    } else {
+        let unit = 10_f64.powi(-decimals);
+        format!("{:.0}", (v / unit).round() * unit)
+    }
+}
+
+/// How the values of an axis are displayed: in a magnitude of their units
+///
+/// Recognized units (seconds, bytes and operations per second, with any of their prefixes)
+/// are converted to the unit of the magnitude, e.g., 1500 ms are displayed as 1.5 s and
+/// 2048 B as 2 KiB. Other units are kept, prefixed with the SI magnitude of the values
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Scale {
+    units: Units,
+    magnitude: Magnitude,
+    format: AxisFormat,
+}
+
+impl Scale {
+    /// Choose the most common magnitude of a set of values, in given units,
+    /// unless the format sets one
+    pub(crate) fn choose(
+        units: &str,
+        values: impl Iterator<Item = Value>,
+        format: &AxisFormat,
+    ) -> Self {
+        let units = Units::parse(units);
+        if let Some(magnitude) = format.magnitude {
+            return Scale {
+                units,
+                magnitude,
+                format: *format,
+            };
+        }
+
+        let mut magnitude_counts = [0; Magnitude::ALL.len()];
+        values.for_each(|v| magnitude_counts[units.magnitude(&v).index()] += 1);
+
+        let idx = magnitude_counts
+            .iter()
+            .enumerate()
+            .max_by_key(|v| v.1)
+            .map(|(idx, c)| {
+                if *c > 0 {
+                    idx
+                } else {
+                    Magnitude::Normal.index()
+                }
+            })
+            .unwrap();
+
+        Scale {
+            units,
+            magnitude: Magnitude::from_index(idx),
+            format: *format,
+        }
+    }
+
+    /// Scale of values displayed as they are (e.g., percentages)
+    pub(crate) fn unit() -> Self {
+        Scale {
+            units: Units::Other(String::new()),
+            magnitude: Magnitude::Normal,
+            format: AxisFormat::new(),
+        }
+    }
+
+    /// Prefix of the magnitude of the values (e.g., "Ki" for KiB)
+    pub(crate) fn prefix(&self) -> &'static str {
+        match &self.units {
+            Units::Known(family, _) => family.prefix(self.magnitude),
+            Units::Other(_) => self.magnitude.prefix(),
+        }
+    }
+
+    /// Units the values are displayed in (e.g., "ms")
+    pub(crate) fn units(&self) -> String {
+        match &self.units {
+            Units::Known(family, _) => format!("{}{}", self.prefix(), family.base()),
+            Units::Other(units) => format!("{}{}", self.prefix(), units),
+        }
+    }
+
+    /// Factor from a value in the configured units to a displayed value
+    fn factor(&self) -> f64 {
+        match &self.units {
+            Units::Known(family, size) => size / family.step().powi(self.magnitude.exponent()),
+            Units::Other(_) => self.magnitude.scale(1.0),
+        }
+    }
+
+    /// Scale a value to the displayed units
+    pub(crate) fn scale(&self, v: f64) -> f64 {
+        match &self.units {
+            Units::Known(..) => v * self.factor(),
+            Units::Other(_) => self.magnitude.scale(v),
+        }
+    }
+
+    /// Display a value in the displayed units
+    ///
+    /// Unless the format sets the precision or the significant figures,
+    /// integers are kept as such if they are displayed in a smaller unit (scaled in 128 bits)
+    pub(crate) fn display(&self, v: &Value) -> String {
+        if let Some(figures) = self.format.significant_figures {
+            return format_significant(self.scale(v.as_float()), figures);
+        }
+        if let Some(precision) = self.format.precision {
+            return format!("{:.*}", precision, self.scale(v.as_float()));
+        }
+        if let Units::Other(_) = self.units {
+            return v.display_with_magnitude(self.magnitude);
+        }
+
+        let factor = self.factor();
+        if let Value::Int(i) = v {
+            let rounded = factor.round();
+            if rounded >= 1.0 && (factor - rounded).abs() <= 1e-9 * factor {
+                if let Some(scaled) = (*i as i128).checked_mul(rounded as i128) {
+                    return format!("{}", scaled);
+                }
+            }
+        }
+
+        let scaled = v.as_float() * factor;
+        match self.magnitude {
+            Magnitude::Normal => format!("{:.3}", scaled),
+            m if m < Magnitude::Normal => format!("{:.2}", scaled),
+            _ => format!("{:.1}", scaled),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn scale_known_units() {
+        let scale = Scale::choose(
+            "ms",
+            [Value::Int(1500), Value::Int(2500)].into_iter(),
+            &AxisFormat::new(),
+        );
+        assert_eq!(scale.units(), "s");
+        assert_eq!(scale.display(&Value::Int(1500)), "1.500");
+
+        let scale = Scale::choose("ns", [Value::Int(1500)].into_iter(), &AxisFormat::new());
+        assert_eq!(scale.units(), "μs");
+        assert_eq!(scale.display(&Value::Int(1500)), "1.50");
+
+        let scale = Scale::choose("s", [Value::Float(0.002)].into_iter(), &AxisFormat::new());
+        assert_eq!(scale.units(), "ms");
+        assert_eq!(scale.display(&Value::Float(0.002)), "2.00");
+
+        let scale = Scale::choose(
+            "B",
+            [Value::Int(2048), Value::Int(4096)].into_iter(),
+            &AxisFormat::new(),
+        );
+        assert_eq!(scale.units(), "KiB");
+        assert_eq!(scale.display(&Value::Int(2048)), "2.0");
+        assert_eq!(scale.scale(1024.0), 1.0);
+
+        let scale = Scale::choose("KiB", [Value::Int(3)].into_iter(), &AxisFormat::new());
+        assert_eq!(scale.units(), "KiB");
+        assert_eq!(scale.display(&Value::Int(3)), "3");
+
+        let scale = Scale::choose(
+            "ops/s",
+            [Value::Float(2.5e6)].into_iter(),
+            &AxisFormat::new(),
+        );
+        assert_eq!(scale.units(), "Mops/s");
+        assert_eq!(scale.display(&Value::Float(2.5e6)), "2.5");
+
+        let scale = Scale::choose(
+            "ops/s",
+            [Value::Int(3 << 40)].into_iter(),
+            &AxisFormat::new(),
+        );
+        assert_eq!(scale.units(), "Tops/s");
+
+        let scale = Scale::choose("GiB", [Value::Int(2 << 20)].into_iter(), &AxisFormat::new());
+        assert_eq!(scale.units(), "PiB");
+        assert_eq!(scale.display(&Value::Int(2 << 20)), "2.0");
+
+        let scale = Scale::choose("s", [Value::Float(4e-12)].into_iter(), &AxisFormat::new());
+        assert_eq!(scale.units(), "ps");
+        assert_eq!(scale.display(&Value::Float(4e-12)), "4.00");
+
+        let scale = Scale::choose("ns", [Value::Int(i64::MAX)].into_iter(), &AxisFormat::new());
+        assert_eq!(scale.units(), "s");
+
+        let scale = Scale::choose(
+            "s",
+            [Value::Int(1)].into_iter(),
+            &AxisFormat::new().magnitude(Some(Magnitude::Pico)),
+        );
+        assert_eq!(scale.units(), "ps");
+        assert_eq!(
+            scale.display(&Value::Int(i64::MAX / 1000)),
+            "9223372036854775000000000000"
+        );
+    }
+
+    #[test]
+    fn scale_with_format() {
+        let format = AxisFormat::new().magnitude(Some(Magnitude::Mili));
+        let scale = Scale::choose("s", [Value::Float(2.0)].into_iter(), &format);
+        assert_eq!(scale.units(), "ms");
+        assert_eq!(scale.display(&Value::Float(2.0)), "2000.00");
+
+        let format = format.precision(Some(0));
+        let scale = Scale::choose("s", [Value::Float(2.0)].into_iter(), &format);
+        assert_eq!(scale.display(&Value::Float(2.0)), "2000");
+
+        let format = AxisFormat::new().significant_figures(Some(3));
+        let scale = Scale::choose("req", [Value::Int(12345)].into_iter(), &format);
+        assert_eq!(scale.units(), "Kreq");
+        assert_eq!(scale.display(&Value::Int(12345)), "12.3");
+        assert_eq!(scale.display(&Value::Int(123456789)), "123000");
+        assert_eq!(scale.display(&Value::Int(5)), "0.00500");
+    }
+
+    #[test]
+    fn format_overrides() {
+        let config = AxisFormat::new()
+            .magnitude(Some(Magnitude::Kilo))
+            .significant_figures(Some(2));
+        let cli = AxisFormat::new().precision(Some(1));
+        assert_eq!(
+            config.overridden_by(cli),
+            AxisFormat::new()
+                .magnitude(Some(Magnitude::Kilo))
+                .precision(Some(1))
+        );
+        assert_eq!(config.overridden_by(AxisFormat::new()), config);
+    }
+
+    #[test]
+    fn scale_other_units() {
+        let scale = Scale::choose("req", [Value::Int(1500)].into_iter(), &AxisFormat::new());
+        assert_eq!(scale.units(), "Kreq");
+        assert_eq!(scale.display(&Value::Int(1500)), "1.5");
+
+        // a count of operations is not a rate
+        let scale = Scale::choose("ops", [Value::Int(1500)].into_iter(), &AxisFormat::new());
+        assert_eq!(scale.units(), "Kops");
+
+        let scale = Scale::choose("", [Value::Int(15)].into_iter(), &AxisFormat::new());
+        assert_eq!(scale.units(), "");
+        assert_eq!(scale.display(&Value::Int(15)), "15");
+
+        let scale = Scale::choose("J", [Value::Float(5e-12)].into_iter(), &AxisFormat::new());
+        assert_eq!(scale.units(), "pJ");
+        assert_eq!(scale.display(&Value::Float(5e-12)), "5.00");
+
+        let scale = Scale::choose(
+            "req",
+            [Value::Int(7_000_000_000_000)].into_iter(),
+            &AxisFormat::new(),
+        );
+        assert_eq!(scale.units(), "Treq");
+        assert_eq!(scale.display(&Value::Int(7_000_000_000_000)), "7.0");
+    }
+}
@@ -3,22 +3,88 @@ use evalexpr::ContextWithMutableVariables;
 use std::collections::BTreeMap;
 
 use crate::error::*;
+use crate::interval::map_interval;
+use crate::parsed::Monotonicity;
 use crate::stat::*;
+use crate::units::Scale;
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Deserialize, serde::Serialize,
+)]
+#[serde(rename_all = "lowercase")]
 pub enum Magnitude {
+    #[serde(alias = "p")]
+    Pico,
+    #[serde(alias = "n")]
     Nano,
+    #[serde(alias = "u", alias = "μ", alias = "µ")]
     Micro,
+    #[serde(rename = "milli", alias = "mili", alias = "m")]
     Mili,
+    #[serde(alias = "none", alias = "")]
     Normal,
+    #[serde(alias = "K", alias = "k")]
     Kilo,
+    #[serde(alias = "M")]
     Mega,
+    #[serde(alias = "G")]
     Giga,
+    #[serde(alias = "T")]
+    Tera,
+    #[serde(alias = "P")]
+    Peta,
+}
+
+impl std::str::FromStr for Magnitude {
+    type Err = BencherError;
+    fn from_str(s: &str) -> BencherResult<Magnitude> {
+        match s {
+            "p" => return Ok(Magnitude::Pico),
+            "n" => return Ok(Magnitude::Nano),
+            "u" | "μ" | "µ" => return Ok(Magnitude::Micro),
+            "m" => return Ok(Magnitude::Mili),
+            "" => return Ok(Magnitude::Normal),
+            "K" | "k" => return Ok(Magnitude::Kilo),
+            "M" => return Ok(Magnitude::Mega),
+            "G" => return Ok(Magnitude::Giga),
+            "T" => return Ok(Magnitude::Tera),
+            "P" => return Ok(Magnitude::Peta),
+            _ => {}
+        }
+        match s.to_lowercase().as_str() {
+            "pico" => Ok(Magnitude::Pico),
+            "nano" => Ok(Magnitude::Nano),
+            "micro" => Ok(Magnitude::Micro),
+            "milli" | "mili" => Ok(Magnitude::Mili),
+            "normal" | "none" => Ok(Magnitude::Normal),
+            "kilo" => Ok(Magnitude::Kilo),
+            "mega" => Ok(Magnitude::Mega),
+            "giga" => Ok(Magnitude::Giga),
+            "tera" => Ok(Magnitude::Tera),
+            "peta" => Ok(Magnitude::Peta),
+            _ => Err(BencherError::UnknownMagnitude(s.to_string())),
+        }
+    }
 }
 
 impl Magnitude {
+    /// Every magnitude, from the smallest to the largest
+    pub(crate) const ALL: [Magnitude; 10] = [
+        Magnitude::Pico,
+        Magnitude::Nano,
+        Magnitude::Micro,
+        Magnitude::Mili,
+        Magnitude::Normal,
+        Magnitude::Kilo,
+        Magnitude::Mega,
+        Magnitude::Giga,
+        Magnitude::Tera,
+        Magnitude::Peta,
+    ];
+
     pub fn prefix(&self) -> &'static str {
         match self {
+            Magnitude::Pico => "p",
             Magnitude::Nano => "n",
             Magnitude::Micro => "μ",
             Magnitude::Mili => "m",
@@ -26,46 +92,190 @@ impl Magnitude {
             Magnitude::Kilo => "K",
             Magnitude::Mega => "M",
             Magnitude::Giga => "G",
+            Magnitude::Tera => "T",
+            Magnitude::Peta => "P",
+        }
+    }
+
+    /// Position of the magnitude, from the smallest (Pico) to the largest (Peta)
+    pub(crate) fn index(&self) -> usize {
+        *self as usize
+    }
+
+    /// Magnitude at a position (see `index`), saturating at the largest one
+    pub(crate) fn from_index(idx: usize) -> Self {
+        Self::ALL[idx.min(Self::ALL.len() - 1)]
+    }
+
+    /// Power of a thousand of the magnitude (e.g., Mili is -1)
+    pub(crate) fn exponent(&self) -> i32 {
+        self.index() as i32 - Magnitude::Normal.index() as i32
+    }
+
+    /// Magnitude of a power of a thousand (e.g., -1 is Mili), saturating at the ends
+    pub(crate) fn from_exponent(exponent: i32) -> Self {
+        let idx = exponent + Magnitude::Normal.index() as i32;
+        Self::from_index(idx.clamp(0, Self::ALL.len() as i32 - 1) as usize)
+    }
+
+    /// Scale a value to this magnitude (e.g., 0.002 is 2 in Mili)
+    pub(crate) fn scale(&self, v: f64) -> f64 {
+        match self {
+            Magnitude::Pico => v * 1e+12_f64,
+            Magnitude::Nano => v * 1e+9_f64,
+            Magnitude::Micro => v * 1e+6_f64,
+            Magnitude::Mili => v * 1e+3_f64,
+            Magnitude::Normal => v,
+            Magnitude::Kilo => v * 1e-3_f64,
+            Magnitude::Mega => v * 1e-6_f64,
+            Magnitude::Giga => v * 1e-9_f64,
+            Magnitude::Tera => v * 1e-12_f64,
+            Magnitude::Peta => v * 1e-15_f64,
         }
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
-pub enum Confidence {
-    One,
-    Five,
-    Ten,
-    TwentyFive,
+/// A confidence interval, from the `p`th to the `(100 - p)`th percentile
+///
+/// The lower percentile is kept in thousandths, so tail percentiles such as p99.9 are exact.
+/// Confidences are ordered from the widest to the narrowest interval
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Confidence(u32);
+
+impl Confidence {
+    pub const ONE: Confidence = Confidence(1_000);
+    pub const FIVE: Confidence = Confidence(5_000);
+    pub const TEN: Confidence = Confidence(10_000);
+    pub const TWENTY_FIVE: Confidence = Confidence(25_000);
+
+    /// Build a confidence interval from either of its percentiles (e.g., 0.1 or 99.9)
+    pub fn new(percentile: f64) -> BencherResult<Self> {
+        let lower = (percentile.min(100.0 - percentile) * 1000.0).round();
+        if lower > 0.0 && lower < 50_000.0 {
+            Ok(Confidence(lower as u32))
+        } else {
+            Err(BencherError::InvalidConfidence(percentile))
+        }
+    }
+
+    /// Percentile of the lower bound of the interval
+    pub fn lower(&self) -> f64 {
+        self.0 as f64 / 1000.0
+    }
+
+    /// Percentile of the upper bound of the interval
+    pub fn upper(&self) -> f64 {
+        (100_000 - self.0) as f64 / 1000.0
+    }
 }
 
-pub const SUPPORTED_CONFIDENCES: [Confidence; 4] = [
-    Confidence::One,
-    Confidence::Five,
-    Confidence::Ten,
-    Confidence::TwentyFive,
+/// Confidences computed by default when aggregating a sample
+pub const DEFAULT_CONFIDENCES: [Confidence; 4] = [
+    Confidence::ONE,
+    Confidence::FIVE,
+    Confidence::TEN,
+    Confidence::TWENTY_FIVE,
 ];
 
 impl TryFrom<usize> for Confidence {
     type Error = BencherError;
     fn try_from(c: usize) -> BencherResult<Confidence> {
-        match c {
-            1 | 99 => Ok(Confidence::One),
-            5 | 95 => Ok(Confidence::Five),
-            10 | 90 => Ok(Confidence::Ten),
-            25 | 75 => Ok(Confidence::TwentyFive),
-            _ => Err(BencherError::InvalidConfidence(c))?,
+        Confidence::new(c as f64)
+    }
+}
+
+impl std::str::FromStr for Confidence {
+    type Err = BencherError;
+    fn from_str(s: &str) -> BencherResult<Confidence> {
+        let percentile = s
+            .trim_start_matches(['p', 'P'])
+            .parse::<f64>()
+            .map_err(|_| BencherError::InvalidValue(s.to_string()))?;
+        Confidence::new(percentile)
+    }
+}
+
+impl std::fmt::Display for Confidence {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "p{}-p{}", self.lower(), self.upper())
+    }
+}
+
+/// How to aggregate a sample into a single datapoint
+///
+/// Regardless of the aggregation, the confidences are computed from the sample percentiles
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Aggregation {
+    #[default]
+    Median,
+    Avg,
+}
+
+impl std::str::FromStr for Aggregation {
+    type Err = BencherError;
+    fn from_str(s: &str) -> BencherResult<Self> {
+        match s.to_lowercase().as_str() {
+            "median" => Ok(Aggregation::Median),
+            "avg" | "average" | "mean" => Ok(Aggregation::Avg),
+            _ => Err(BencherError::UnknownAggregation(s.to_string())),
         }
     }
 }
 
-impl From<Confidence> for usize {
-    fn from(c: Confidence) -> usize {
-        match c {
-            Confidence::One => 1,
-            Confidence::Five => 5,
-            Confidence::Ten => 10,
-            Confidence::TwentyFive => 25,
+/// A raw sample: either all integers or all floats
+pub type Sample = Either<Vec<i64>, Vec<f64>>;
+
+/// Build a sample from a list of values
+///
+/// The sample is integer if all values are integers, otherwise it is floating point
+pub fn sample_from_values(values: Vec<Value>) -> Sample {
+    if values.iter().all(|v| v.is_int()) {
+        Either::Left(values.into_iter().map(|v| v.to_int().unwrap()).collect())
+    } else {
+        Either::Right(
+            values
+                .into_iter()
+                .map(|v| v.to_float().or(v.to_int().map(|x| x as f64)).unwrap())
+                .collect(),
+        )
+    }
+}
+
+/// Lower and upper bounds of a confidence interval: either both integers or both floats
+type ConfidenceBounds = Either<(i64, i64), (f64, f64)>;
+
+/// Compute the confidence intervals of a sample at the given percentiles
+///
+/// Returns nothing for empty samples
+fn sample_confidences(
+    sample: &Sample,
+    confidences: &[Confidence],
+) -> Vec<(Confidence, ConfidenceBounds)> {
+    let mut sample = sample.clone();
+    match sample.as_mut() {
+        Either::Left(s) if !s.is_empty() => {
+            s.sort_unstable();
+            confidences
+                .iter()
+                .map(|&c| {
+                    let lower = integer_fractional_percentile(s, c.lower());
+                    let upper = integer_fractional_percentile(s, c.upper());
+                    (c, Either::Left((lower, upper)))
+                })
+                .collect()
+        }
+        Either::Right(s) if !s.is_empty() => {
+            s.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap());
+            confidences
+                .iter()
+                .map(|&c| {
+                    let lower = float_fractional_percentile(s, c.lower());
+                    let upper = float_fractional_percentile(s, c.upper());
+                    (c, Either::Right((lower, upper)))
+                })
+                .collect()
         }
+        _ => vec![],
     }
 }
 
@@ -103,6 +313,22 @@ impl std::cmp::Ord for Value {
     }
 }
 
+/// Values are parsed as integers if possible, otherwise as floats
+///
+/// Non-finite floats (NaN and infinities) are rejected, since values are sorted and aggregated
+impl std::str::FromStr for Value {
+    type Err = BencherError;
+    fn from_str(s: &str) -> BencherResult<Value> {
+        if let Ok(i) = s.parse::<i64>() {
+            Ok(Value::Int(i))
+        } else if let Some(f) = s.parse::<f64>().ok().filter(|f| f.is_finite()) {
+            Ok(Value::Float(f))
+        } else {
+            Err(BencherError::InvalidValue(s.to_string()))
+        }
+    }
+}
+
 impl From<Value> for evalexpr::Value {
     fn from(value: Value) -> evalexpr::Value {
         match value {
@@ -160,48 +386,123 @@ impl Value {
         }
     }
 
-    fn magnitude(&self) -> Magnitude {
+    /// The value as a float, converting integers
+    pub(crate) fn as_float(&self) -> f64 {
+        match self {
+            Value::Int(i) => *i as f64,
+            Value::Float(f) => *f,
+        }
+    }
+
+    pub(crate) fn magnitude(&self) -> Magnitude {
         match self {
-            Value::Int(i) => match i.abs() {
+            Value::Int(i) => match i.unsigned_abs() {
                 0..=999 => Magnitude::Normal,
                 1_000..=999_999 => Magnitude::Kilo,
                 1_000_000..=999_999_999 => Magnitude::Mega,
-                _ => Magnitude::Giga,
+                1_000_000_000..=999_999_999_999 => Magnitude::Giga,
+                1_000_000_000_000..=999_999_999_999_999 => Magnitude::Tera,
+                _ => Magnitude::Peta,
             },
             Value::Float(f) => match f.abs() {
                 x if x == 0.0_f64 => Magnitude::Normal,
+                x if x < 1e-9_f64 => Magnitude::Pico,
                 x if x < 1e-6_f64 => Magnitude::Nano,
-                x if x >= 1e-6_f64 && x < 1e-3_f64 => Magnitude::Micro,
-                x if x >= 1e-3_f64 && x < 1e+0_f64 => Magnitude::Mili,
-                x if x >= 1e+0_f64 && x < 1e+3_f64 => Magnitude::Normal,
-                x if x >= 1e+3_f64 && x < 1e+6_f64 => Magnitude::Kilo,
-                x if x >= 1e+6_f64 && x < 1e+9_f64 => Magnitude::Mega,
-                _ => Magnitude::Giga,
+                x if x < 1e-3_f64 => Magnitude::Micro,
+                x if x < 1e+0_f64 => Magnitude::Mili,
+                x if x < 1e+3_f64 => Magnitude::Normal,
+                x if x < 1e+6_f64 => Magnitude::Kilo,
+                x if x < 1e+9_f64 => Magnitude::Mega,
+                x if x < 1e+12_f64 => Magnitude::Giga,
+                x if x < 1e+15_f64 => Magnitude::Tera,
+                _ => Magnitude::Peta,
             },
         }
     }
 
+    /// Display the value in the units of a scale (see `Scale`)
+    pub(crate) fn display_with_scale(&self, scale: &Scale) -> String {
+        scale.display(self)
+    }
+
+    /// Display the value in a magnitude
+    ///
+    /// Integers are kept as such in smaller magnitudes (scaled in 128 bits, so as not to overflow)
     pub fn display_with_magnitude(&self, mag: Magnitude) -> String {
-        match self {
-            Value::Int(i) => match mag {
-                Magnitude::Nano => format!("{}", i * 1_000_000_000),
-                Magnitude::Micro => format!("{}", i * 1_000_000),
-                Magnitude::Mili => format!("{}", i * 1_000),
-                Magnitude::Normal => format!("{}", i),
-                Magnitude::Kilo => format!("{:.1}", *i as f64 * 1e-3_f64),
-                Magnitude::Mega => format!("{:.1}", *i as f64 * 1e-6_f64),
-                Magnitude::Giga => format!("{:.1}", *i as f64 * 1e-9_f64),
-            },
-            Value::Float(f) => match mag {
-                Magnitude::Nano => format!("{:.2}", f * 1e+9_f64),
-                Magnitude::Micro => format!("{:.2}", f * 1e+6_f64),
-                Magnitude::Mili => format!("{:.2}", f * 1e+3_f64),
-                Magnitude::Normal => format!("{:.3}", f),
-                Magnitude::Kilo => format!("{:.1}", f * 1e-3),
-                Magnitude::Mega => format!("{:.1}", f * 1e-6),
-                Magnitude::Giga => format!("{:.1}", f * 1e-9),
-            },
+        if let Value::Int(i) = self {
+            if mag <= Magnitude::Normal {
+                let factor = 1000_i128.pow((-mag.exponent()) as u32);
+                return format!("{}", *i as i128 * factor);
+            }
         }
+
+        let scaled = mag.scale(self.as_float());
+        match mag {
+            Magnitude::Normal => format!("{:.3}", scaled),
+            m if m < Magnitude::Normal => format!("{:.2}", scaled),
+            _ => format!("{:.1}", scaled),
+        }
+    }
+}
+
+/// Named values available to the expressions of virtual experiments,
+/// besides the ones of the datapoint being mapped
+///
+/// A variable may have confidence intervals, which are mapped along with the ones of the datapoint
+#[derive(Debug, Default, Clone)]
+pub(crate) struct ExpressionVariables {
+    variables: BTreeMap<String, (Value, ConfidenceIntervals)>,
+}
+
+type ConfidenceIntervals = BTreeMap<Confidence, (Value, Value)>;
+
+impl ExpressionVariables {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a variable without confidence intervals (e.g., an aggregate of a set)
+    pub(crate) fn add(&mut self, name: impl Into<String>, v: Value) {
+        self.variables.insert(name.into(), (v, BTreeMap::new()));
+    }
+
+    /// Add a variable along with its confidence intervals
+    pub(crate) fn add_with_confidence(
+        &mut self,
+        name: impl Into<String>,
+        v: Value,
+        confidence: &ConfidenceIntervals,
+    ) {
+        self.variables.insert(name.into(), (v, confidence.clone()));
+    }
+
+    pub(crate) fn extend(&mut self, other: &ExpressionVariables) {
+        self.variables.extend(
+            other
+                .variables
+                .iter()
+                .map(|(name, var)| (name.clone(), var.clone())),
+        );
+    }
+
+    /// Context with every variable at its value
+    fn context(&self) -> BencherResult<evalexpr::HashMapContext> {
+        let mut ctx = evalexpr::HashMapContext::new();
+        for (name, (v, _)) in &self.variables {
+            ctx.set_value(name.clone(), (*v).into())?;
+        }
+        Ok(ctx)
+    }
+
+    /// Bounds of the variables with an interval at the given confidence
+    fn intervals(&self, confidence: Confidence) -> BTreeMap<String, (Value, Value)> {
+        self.variables
+            .iter()
+            .filter_map(|(name, (_, intervals))| {
+                let &bounds = intervals.get(&confidence)?;
+                Some((name.clone(), bounds))
+            })
+            .collect()
     }
 }
 
@@ -249,13 +550,13 @@ impl LinearDatapoint {
         sample.sort_unstable();
         let mut datapoint = LinearDatapoint::new(group, Value::Int(integer_median(&sample)));
 
-        for confidence in SUPPORTED_CONFIDENCES {
+        for confidence in DEFAULT_CONFIDENCES {
             let (lower, upper) = (
-                integer_percentile(&sample, usize::from(confidence)),
-                integer_percentile(&sample, 100 - usize::from(confidence)),
+                integer_fractional_percentile(&sample, confidence.lower()),
+                integer_fractional_percentile(&sample, confidence.upper()),
             );
             datapoint
-                .add_confidence(confidence.into(), Either::Left((lower, upper)))
+                .add_confidence(confidence, Either::Left((lower, upper)))
                 .expect("Unexpected type mismatch");
         }
 
@@ -272,10 +573,10 @@ impl LinearDatapoint {
         sample.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap());
         let mut datapoint = LinearDatapoint::new(group, Value::Float(float_median(&sample)));
 
-        for confidence in SUPPORTED_CONFIDENCES {
+        for confidence in DEFAULT_CONFIDENCES {
             let (lower, upper) = (
-                float_percentile(&sample, usize::from(confidence)),
-                float_percentile(&sample, 100 - usize::from(confidence)),
+                float_fractional_percentile(&sample, confidence.lower()),
+                float_fractional_percentile(&sample, confidence.upper()),
             );
             datapoint
                 .add_confidence(confidence, Either::Right((lower, upper)))
@@ -295,10 +596,10 @@ impl LinearDatapoint {
         sample.sort_unstable();
         let mut datapoint = LinearDatapoint::new(group, Value::Int(integer_avg(&sample)));
 
-        for confidence in SUPPORTED_CONFIDENCES {
+        for confidence in DEFAULT_CONFIDENCES {
             let (lower, upper) = (
-                integer_percentile(&sample, usize::from(confidence)),
-                integer_percentile(&sample, 100 - usize::from(confidence)),
+                integer_fractional_percentile(&sample, confidence.lower()),
+                integer_fractional_percentile(&sample, confidence.upper()),
             );
             datapoint
                 .add_confidence(confidence, Either::Left((lower, upper)))
@@ -318,10 +619,10 @@ impl LinearDatapoint {
         sample.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap());
         let mut datapoint = LinearDatapoint::new(group, Value::Float(float_avg(&sample)));
 
-        for confidence in SUPPORTED_CONFIDENCES {
+        for confidence in DEFAULT_CONFIDENCES {
             let (lower, upper) = (
-                float_percentile(&sample, usize::from(confidence)),
-                float_percentile(&sample, 100 - usize::from(confidence)),
+                float_fractional_percentile(&sample, confidence.lower()),
+                float_fractional_percentile(&sample, confidence.upper()),
             );
             datapoint
                 .add_confidence(confidence, Either::Right((lower, upper)))
@@ -331,6 +632,20 @@ impl LinearDatapoint {
         Ok(Some(datapoint))
     }
 
+    /// Aggregate a sample into a datapoint
+    pub fn from_sample(
+        group: impl Into<String>,
+        sample: Either<&mut Vec<i64>, &mut Vec<f64>>,
+        aggregation: Aggregation,
+    ) -> Result<Option<Self>, BencherError> {
+        match (sample, aggregation) {
+            (Either::Left(s), Aggregation::Median) => Self::from_sample_i64_median(group, s),
+            (Either::Left(s), Aggregation::Avg) => Self::from_sample_i64_avg(group, s),
+            (Either::Right(s), Aggregation::Median) => Self::from_sample_f64_median(group, s),
+            (Either::Right(s), Aggregation::Avg) => Self::from_sample_f64_avg(group, s),
+        }
+    }
+
     pub fn tag(mut self, tag: isize) -> Self {
         self.tag = Some(tag);
         self
@@ -363,7 +678,20 @@ impl LinearDatapoint {
         Ok(())
     }
 
-    fn add_value_confidence(&mut self, confidence: Confidence, values: (Value, Value)) {
+    /// Add the confidence intervals of the sample this datapoint was aggregated from,
+    /// at the given percentiles
+    pub fn add_sample_confidences(
+        &mut self,
+        sample: &Sample,
+        confidences: &[Confidence],
+    ) -> BencherResult<()> {
+        for (confidence, values) in sample_confidences(sample, confidences) {
+            self.add_confidence(confidence, values)?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn add_value_confidence(&mut self, confidence: Confidence, values: (Value, Value)) {
         self.v_confidence.insert(confidence, values);
     }
 
@@ -371,44 +699,55 @@ impl LinearDatapoint {
         self.v_confidence.get(&confidence).cloned()
     }
 
+    /// Variables exposing this datapoint to the expressions of virtual experiments,
+    /// as a source named `name`
+    pub(crate) fn expression_variables(&self, name: &str) -> ExpressionVariables {
+        let mut variables = ExpressionVariables::new();
+        variables.add_with_confidence(name, self.v, &self.v_confidence);
+        variables
+    }
+
     fn get_evalexpr_context(
         value: Value,
         tag: isize,
-        min: Value,
-        max: Value,
-        avg: Value,
+        variables: &ExpressionVariables,
     ) -> BencherResult<evalexpr::HashMapContext> {
         let value: evalexpr::Value = value.into();
-        let mut ctx = evalexpr::HashMapContext::new();
+        let mut ctx = variables.context()?;
         ctx.set_value("v".to_string(), value.clone())?;
         ctx.set_value("V".to_string(), value)?;
         ctx.set_value("tag".to_string(), evalexpr::Value::Int(tag as i64))?;
-        ctx.set_value("min".to_string(), min.into())?;
-        ctx.set_value("max".to_string(), max.into())?;
-        ctx.set_value("avg".to_string(), avg.into())?;
 
         Ok(ctx)
     }
 
+    /// Intervals of the variables of an expression at the given confidence,
+    /// including the ones of this datapoint
+    fn get_intervals(
+        &self,
+        confidence: Confidence,
+        variables: &ExpressionVariables,
+    ) -> BTreeMap<String, (Value, Value)> {
+        let mut intervals = variables.intervals(confidence);
+        if let Some(&bounds) = self.v_confidence.get(&confidence) {
+            intervals.insert("v".to_string(), bounds);
+            intervals.insert("V".to_string(), bounds);
+        }
+        intervals
+    }
+
     pub(crate) fn map_expression(
         &self,
         v_expr: Option<&str>,
         tag_expr: Option<&str>,
-        global_min: Value,
-        global_max: Value,
-        global_avg: Value,
+        variables: &ExpressionVariables,
+        v_monotonicity: Option<Monotonicity>,
     ) -> BencherResult<LinearDatapoint> {
         let v_expr = v_expr.unwrap_or("v");
         let tag_expr = tag_expr.unwrap_or("tag");
 
         // build basic datapoint
-        let ctx = Self::get_evalexpr_context(
-            self.v,
-            self.tag.unwrap(),
-            global_min,
-            global_max,
-            global_avg,
-        )?;
+        let ctx = Self::get_evalexpr_context(self.v, self.tag.unwrap(), variables)?;
         let new_v: Value = evalexpr::eval_with_context(v_expr, &ctx)?.try_into()?;
         let new_tag = evalexpr::eval_with_context(tag_expr, &ctx)?;
         let new_tag = match new_tag {
@@ -417,38 +756,10 @@ impl LinearDatapoint {
         }?;
         let mut new_datapoint = LinearDatapoint::new(self.group.clone(), new_v).tag(new_tag);
 
-        for c in SUPPORTED_CONFIDENCES {
-            if let Some((min, max)) = self.v_confidence.get(&c) {
-                let new_min: BencherResult<BencherResult<Value>> = {
-                    let ctx = Self::get_evalexpr_context(
-                        min.clone(),
-                        self.tag.unwrap(),
-                        global_min,
-                        global_max,
-                        global_avg,
-                    )?;
-                    evalexpr::eval_with_context(v_expr, &ctx)
-                        .map_err(|e| e.into())
-                        .map(|v| v.try_into())
-                };
-                let new_min = new_min??;
-
-                let new_max: BencherResult<BencherResult<Value>> = {
-                    let ctx = Self::get_evalexpr_context(
-                        max.clone(),
-                        self.tag.unwrap(),
-                        global_min,
-                        global_max,
-                        global_avg,
-                    )?;
-                    evalexpr::eval_with_context(v_expr, &ctx)
-                        .map_err(|e| e.into())
-                        .map(|v| v.try_into())
-                };
-                let new_max = new_max??;
-
-                new_datapoint.add_value_confidence(c, (new_min, new_max));
-            }
+        for &c in self.v_confidence.keys() {
+            let intervals = self.get_intervals(c, variables);
+            let bounds = map_interval(v_expr, &ctx, &intervals, v_monotonicity)?;
+            new_datapoint.add_value_confidence(c, bounds);
         }
 
         Ok(new_datapoint)
@@ -459,22 +770,16 @@ impl LinearDatapoint {
         x_expr: Option<&str>,
         y_expr: Option<&str>,
         tag_expr: Option<&str>,
-        global_min: Value,
-        global_max: Value,
-        global_avg: Value,
+        variables: &ExpressionVariables,
+        x_monotonicity: Option<Monotonicity>,
+        y_monotonicity: Option<Monotonicity>,
     ) -> BencherResult<XYDatapoint> {
         let x_expr = x_expr.unwrap_or("tag");
         let y_expr = y_expr.unwrap_or("v");
         let tag_expr = tag_expr.unwrap_or("tag");
 
         // build basic datapoint
-        let ctx = Self::get_evalexpr_context(
-            self.v,
-            self.tag.unwrap(),
-            global_min,
-            global_max,
-            global_avg,
-        )?;
+        let ctx = Self::get_evalexpr_context(self.v, self.tag.unwrap(), variables)?;
 
         let new_x: Value = evalexpr::eval_with_context(x_expr, &ctx)?.try_into()?;
         let new_y: Value = evalexpr::eval_with_context(y_expr, &ctx)?.try_into()?;
@@ -485,80 +790,69 @@ impl LinearDatapoint {
         }?;
         let mut new_datapoint = XYDatapoint::new(new_x, new_y).tag(new_tag);
 
-        for c in SUPPORTED_CONFIDENCES {
-            if let Some((min, max)) = self.v_confidence.get(&c) {
-                let new_x_min: BencherResult<BencherResult<Value>> = {
-                    let ctx = Self::get_evalexpr_context(
-                        min.clone(),
-                        self.tag.unwrap(),
-                        global_min,
-                        global_max,
-                        global_avg,
-                    )?;
-                    evalexpr::eval_with_context(x_expr, &ctx)
-                        .map_err(|e| e.into())
-                        .map(|v| v.try_into())
-                };
-                let new_x_min = new_x_min??;
-
-                let new_x_max: BencherResult<BencherResult<Value>> = {
-                    let ctx = Self::get_evalexpr_context(
-                        max.clone(),
-                        self.tag.unwrap(),
-                        global_min,
-                        global_max,
-                        global_avg,
-                    )?;
-                    evalexpr::eval_with_context(x_expr, &ctx)
-                        .map_err(|e| e.into())
-                        .map(|v| v.try_into())
-                };
-                let new_x_max = new_x_max??;
-
-                new_datapoint.add_x_value_confidence(c, (new_x_min, new_x_max));
-
-                let new_y_min: BencherResult<BencherResult<Value>> = {
-                    let ctx = Self::get_evalexpr_context(
-                        min.clone(),
-                        self.tag.unwrap(),
-                        global_min,
-                        global_max,
-                        global_avg,
-                    )?;
-                    evalexpr::eval_with_context(y_expr, &ctx)
-                        .map_err(|e| e.into())
-                        .map(|v| v.try_into())
-                };
-                let new_y_min = new_y_min??;
-
-                let new_y_max: BencherResult<BencherResult<Value>> = {
-                    let ctx = Self::get_evalexpr_context(
-                        max.clone(),
-                        self.tag.unwrap(),
-                        global_min,
-                        global_max,
-                        global_avg,
-                    )?;
-                    evalexpr::eval_with_context(y_expr, &ctx)
-                        .map_err(|e| e.into())
-                        .map(|v| v.try_into())
-                };
-                let new_y_max = new_y_max??;
-
-                new_datapoint.add_y_value_confidence(c, (new_y_min, new_y_max));
-            }
+        for &c in self.v_confidence.keys() {
+            let intervals = self.get_intervals(c, variables);
+            let x_bounds = map_interval(x_expr, &ctx, &intervals, x_monotonicity)?;
+            new_datapoint.add_x_value_confidence(c, x_bounds);
+            let y_bounds = map_interval(y_expr, &ctx, &intervals, y_monotonicity)?;
+            new_datapoint.add_y_value_confidence(c, y_bounds);
         }
 
         Ok(new_datapoint)
     }
 }
 
+/// The raw sample behind a linear datapoint
+#[derive(Debug, PartialEq, Clone)]
+pub struct LinearRawDatapoint {
+    pub group: String,
+
+    pub v: Sample,
+
+    pub tag: Option<isize>,
+}
+
+impl LinearRawDatapoint {
+    pub fn new(group: impl Into<String>, v: Sample) -> Self {
+        LinearRawDatapoint {
+            group: group.into(),
+            v,
+            tag: None,
+        }
+    }
+
+    pub fn tag(mut self, tag: isize) -> Self {
+        self.tag = Some(tag);
+        self
+    }
+
+    /// Aggregate the sample into a datapoint (None if the sample is empty)
+    ///
+    /// The confidence intervals at the given percentiles are computed in addition to the default ones
+    pub fn aggregate(
+        &self,
+        aggregation: Aggregation,
+        confidences: &[Confidence],
+    ) -> BencherResult<Option<LinearDatapoint>> {
+        let mut v = self.v.clone();
+        let mut datapoint =
+            match LinearDatapoint::from_sample(self.group.clone(), v.as_mut(), aggregation)? {
+                Some(datapoint) => datapoint,
+                None => return Ok(None),
+            };
+        datapoint.add_sample_confidences(&self.v, confidences)?;
+
+        Ok(Some(match self.tag {
+            Some(tag) => datapoint.tag(tag),
+            None => datapoint,
+        }))
+    }
+}
+
 impl std::fmt::Display for LinearDatapoint {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        for c in SUPPORTED_CONFIDENCES {
-            if let Some((min, max)) = self.v_confidence.get(&c) {
-                return write!(f, "{}: {} ([{};{}])", self.group, self.v, min, max);
-            }
+        if let Some((min, max)) = self.v_confidence.values().next() {
+            return write!(f, "{}: {} ([{};{}])", self.group, self.v, min, max);
         }
 
         write!(f, "{}: {}", self.group, self.v)
@@ -621,14 +915,14 @@ impl XYDatapoint {
             Value::Int(integer_median(&y_sample)),
         );
 
-        for confidence in SUPPORTED_CONFIDENCES {
+        for confidence in DEFAULT_CONFIDENCES {
             let (x_lower, x_upper) = (
-                integer_percentile(&x_sample, usize::from(confidence)),
-                integer_percentile(&x_sample, 100 - usize::from(confidence)),
+                integer_fractional_percentile(&x_sample, confidence.lower()),
+                integer_fractional_percentile(&x_sample, confidence.upper()),
             );
             let (y_lower, y_upper) = (
-                integer_percentile(&y_sample, usize::from(confidence)),
-                integer_percentile(&y_sample, 100 - usize::from(confidence)),
+                integer_fractional_percentile(&y_sample, confidence.lower()),
+                integer_fractional_percentile(&y_sample, confidence.upper()),
             );
             datapoint
                 .add_x_confidence(confidence, Either::Left((x_lower, x_upper)))
@@ -654,14 +948,14 @@ impl XYDatapoint {
             Value::Float(float_median(&y_sample)),
         );
 
-        for confidence in SUPPORTED_CONFIDENCES {
+        for confidence in DEFAULT_CONFIDENCES {
             let (x_lower, x_upper) = (
-                integer_percentile(&x_sample, usize::from(confidence)),
-                integer_percentile(&x_sample, 100 - usize::from(confidence)),
+                integer_fractional_percentile(&x_sample, confidence.lower()),
+                integer_fractional_percentile(&x_sample, confidence.upper()),
             );
             let (y_lower, y_upper) = (
-                float_percentile(&y_sample, usize::from(confidence)),
-                float_percentile(&y_sample, 100 - usize::from(confidence)),
+                float_fractional_percentile(&y_sample, confidence.lower()),
+                float_fractional_percentile(&y_sample, confidence.upper()),
             );
             datapoint
                 .add_x_confidence(confidence, Either::Left((x_lower, x_upper)))
@@ -687,14 +981,14 @@ impl XYDatapoint {
             Value::Int(integer_median(&y_sample)),
         );
 
-        for confidence in SUPPORTED_CONFIDENCES {
+        for confidence in DEFAULT_CONFIDENCES {
             let (x_lower, x_upper) = (
-                float_percentile(&x_sample, usize::from(confidence)),
-                float_percentile(&x_sample, 100 - usize::from(confidence)),
+                float_fractional_percentile(&x_sample, confidence.lower()),
+                float_fractional_percentile(&x_sample, confidence.upper()),
             );
             let (y_lower, y_upper) = (
-                integer_percentile(&y_sample, usize::from(confidence)),
-                integer_percentile(&y_sample, 100 - usize::from(confidence)),
+                integer_fractional_percentile(&y_sample, confidence.lower()),
+                integer_fractional_percentile(&y_sample, confidence.upper()),
             );
             datapoint
                 .add_x_confidence(confidence, Either::Right((x_lower, x_upper)))
@@ -720,14 +1014,14 @@ impl XYDatapoint {
             Value::Float(float_median(&y_sample)),
         );
 
-        for confidence in SUPPORTED_CONFIDENCES {
+        for confidence in DEFAULT_CONFIDENCES {
             let (x_lower, x_upper) = (
-                float_percentile(&x_sample, usize::from(confidence)),
-                float_percentile(&x_sample, 100 - usize::from(confidence)),
+                float_fractional_percentile(&x_sample, confidence.lower()),
+                float_fractional_percentile(&x_sample, confidence.upper()),
             );
             let (y_lower, y_upper) = (
-                float_percentile(&y_sample, usize::from(confidence)),
-                float_percentile(&y_sample, 100 - usize::from(confidence)),
+                float_fractional_percentile(&y_sample, confidence.lower()),
+                float_fractional_percentile(&y_sample, confidence.upper()),
             );
             datapoint
                 .add_x_confidence(confidence, Either::Right((x_lower, x_upper)))
@@ -762,14 +1056,14 @@ impl XYDatapoint {
             Value::Int(integer_avg(&y_sample)),
         );
 
-        for confidence in SUPPORTED_CONFIDENCES {
+        for confidence in DEFAULT_CONFIDENCES {
             let (x_lower, x_upper) = (
-                integer_percentile(&x_sample, usize::from(confidence)),
-                integer_percentile(&x_sample, 100 - usize::from(confidence)),
+                integer_fractional_percentile(&x_sample, confidence.lower()),
+                integer_fractional_percentile(&x_sample, confidence.upper()),
             );
             let (y_lower, y_upper) = (
-                integer_percentile(&y_sample, usize::from(confidence)),
-                integer_percentile(&y_sample, 100 - usize::from(confidence)),
+                integer_fractional_percentile(&y_sample, confidence.lower()),
+                integer_fractional_percentile(&y_sample, confidence.upper()),
             );
             datapoint
                 .add_x_confidence(confidence, Either::Left((x_lower, x_upper)))
@@ -792,14 +1086,14 @@ impl XYDatapoint {
             Value::Float(float_avg(&y_sample)),
         );
 
-        for confidence in SUPPORTED_CONFIDENCES {
+        for confidence in DEFAULT_CONFIDENCES {
             let (x_lower, x_upper) = (
-                integer_percentile(&x_sample, usize::from(confidence)),
-                integer_percentile(&x_sample, 100 - usize::from(confidence)),
+                integer_fractional_percentile(&x_sample, confidence.lower()),
+                integer_fractional_percentile(&x_sample, confidence.upper()),
             );
             let (y_lower, y_upper) = (
-                float_percentile(&y_sample, usize::from(confidence)),
-                float_percentile(&y_sample, 100 - usize::from(confidence)),
+                float_fractional_percentile(&y_sample, confidence.lower()),
+                float_fractional_percentile(&y_sample, confidence.upper()),
             );
             datapoint
                 .add_x_confidence(confidence, Either::Left((x_lower, x_upper)))
@@ -822,14 +1116,14 @@ impl XYDatapoint {
             Value::Int(integer_avg(&y_sample)),
         );
 
-        for confidence in SUPPORTED_CONFIDENCES {
+        for confidence in DEFAULT_CONFIDENCES {
             let (x_lower, x_upper) = (
-                float_percentile(&x_sample, usize::from(confidence)),
-                float_percentile(&x_sample, 100 - usize::from(confidence)),
+                float_fractional_percentile(&x_sample, confidence.lower()),
+                float_fractional_percentile(&x_sample, confidence.upper()),
             );
             let (y_lower, y_upper) = (
-                integer_percentile(&y_sample, usize::from(confidence)),
-                integer_percentile(&y_sample, 100 - usize::from(confidence)),
+                integer_fractional_percentile(&y_sample, confidence.lower()),
+                integer_fractional_percentile(&y_sample, confidence.upper()),
             );
             datapoint
                 .add_x_confidence(confidence, Either::Right((x_lower, x_upper)))
@@ -852,14 +1146,14 @@ impl XYDatapoint {
             Value::Float(float_avg(&y_sample)),
         );
 
-        for confidence in SUPPORTED_CONFIDENCES {
+        for confidence in DEFAULT_CONFIDENCES {
             let (x_lower, x_upper) = (
-                float_percentile(&x_sample, usize::from(confidence)),
-                float_percentile(&x_sample, 100 - usize::from(confidence)),
+                float_fractional_percentile(&x_sample, confidence.lower()),
+                float_fractional_percentile(&x_sample, confidence.upper()),
             );
             let (y_lower, y_upper) = (
-                float_percentile(&y_sample, usize::from(confidence)),
-                float_percentile(&y_sample, 100 - usize::from(confidence)),
+                float_fractional_percentile(&y_sample, confidence.lower()),
+                float_fractional_percentile(&y_sample, confidence.upper()),
             );
             datapoint
                 .add_x_confidence(confidence, Either::Right((x_lower, x_upper)))
@@ -883,6 +1177,18 @@ impl XYDatapoint {
         }
     }
 
+    /// Aggregate a pair of samples into a datapoint
+    pub fn from_samples(
+        x_sample: Either<&mut Vec<i64>, &mut Vec<f64>>,
+        y_sample: Either<&mut Vec<i64>, &mut Vec<f64>>,
+        aggregation: Aggregation,
+    ) -> Option<Self> {
+        match aggregation {
+            Aggregation::Median => Self::from_samples_median(x_sample, y_sample),
+            Aggregation::Avg => Self::from_samples_avg(x_sample, y_sample),
+        }
+    }
+
     pub fn tag(mut self, tag: isize) -> Self {
         self.tag = Some(tag);
         self
@@ -915,6 +1221,23 @@ impl XYDatapoint {
         Ok(())
     }
 
+    /// Add the confidence intervals of the samples this datapoint was aggregated from,
+    /// at the given percentiles
+    pub fn add_sample_confidences(
+        &mut self,
+        x_sample: &Sample,
+        y_sample: &Sample,
+        confidences: &[Confidence],
+    ) -> BencherResult<()> {
+        for (confidence, values) in sample_confidences(x_sample, confidences) {
+            self.add_x_confidence(confidence, values)?;
+        }
+        for (confidence, values) in sample_confidences(y_sample, confidences) {
+            self.add_y_confidence(confidence, values)?;
+        }
+        Ok(())
+    }
+
     fn add_x_value_confidence(&mut self, confidence: Confidence, values: (Value, Value)) {
         self.x_confidence.insert(confidence, values);
     }
@@ -954,18 +1277,22 @@ impl XYDatapoint {
         self.y_confidence.get(&confidence).cloned()
     }
 
+    /// Variables exposing this datapoint to the expressions of virtual experiments,
+    /// as a source named `name` (`<name>_x` and `<name>_y`)
+    pub(crate) fn expression_variables(&self, name: &str) -> ExpressionVariables {
+        let mut variables = ExpressionVariables::new();
+        variables.add_with_confidence(format!("{}_x", name), self.x, &self.x_confidence);
+        variables.add_with_confidence(format!("{}_y", name), self.y, &self.y_confidence);
+        variables
+    }
+
     fn get_evalexpr_context(
         xvalue: Value,
         yvalue: Value,
         tag: isize,
-        xmin: Value,
-        xmax: Value,
-        xavg: Value,
-        ymin: Value,
-        ymax: Value,
-        yavg: Value,
+        variables: &ExpressionVariables,
     ) -> BencherResult<evalexpr::HashMapContext> {
-        let mut ctx = evalexpr::HashMapContext::new();
+        let mut ctx = variables.context()?;
         let xvalue: evalexpr::Value = xvalue.into();
         let yvalue: evalexpr::Value = yvalue.into();
         ctx.set_value("x".to_string(), xvalue.clone())?;
@@ -973,43 +1300,43 @@ impl XYDatapoint {
         ctx.set_value("y".to_string(), yvalue.clone())?;
         ctx.set_value("Y".to_string(), yvalue)?;
         ctx.set_value("tag".to_string(), evalexpr::Value::Int(tag as i64))?;
-        ctx.set_value("xmin".to_string(), xmin.into())?;
-        ctx.set_value("xmax".to_string(), xmax.into())?;
-        ctx.set_value("xavg".to_string(), xavg.into())?;
-        ctx.set_value("ymin".to_string(), ymin.into())?;
-        ctx.set_value("ymax".to_string(), ymax.into())?;
-        ctx.set_value("yavg".to_string(), yavg.into())?;
         Ok(ctx)
     }
 
+    /// Intervals of the variables of an expression at the given confidence,
+    /// including the ones of this datapoint
+    fn get_intervals(
+        &self,
+        confidence: Confidence,
+        variables: &ExpressionVariables,
+    ) -> BTreeMap<String, (Value, Value)> {
+        let mut intervals = variables.intervals(confidence);
+        if let Some(&bounds) = self.x_confidence.get(&confidence) {
+            intervals.insert("x".to_string(), bounds);
+            intervals.insert("X".to_string(), bounds);
+        }
+        if let Some(&bounds) = self.y_confidence.get(&confidence) {
+            intervals.insert("y".to_string(), bounds);
+            intervals.insert("Y".to_string(), bounds);
+        }
+        intervals
+    }
+
     pub(crate) fn map_expression(
         &self,
         x_expr: Option<&str>,
         y_expr: Option<&str>,
         tag_expr: Option<&str>,
-        global_x_min: Value,
-        global_x_max: Value,
-        global_x_avg: Value,
-        global_y_min: Value,
-        global_y_max: Value,
-        global_y_avg: Value,
+        variables: &ExpressionVariables,
+        x_monotonicity: Option<Monotonicity>,
+        y_monotonicity: Option<Monotonicity>,
     ) -> BencherResult<XYDatapoint> {
         // build basic datapoint
         let x_expr = x_expr.unwrap_or("x");
         let y_expr = y_expr.unwrap_or("y");
         let tag_expr = tag_expr.unwrap_or("tag");
 
-        let ctx = Self::get_evalexpr_context(
-            self.x,
-            self.y,
-            self.tag.unwrap(),
-            global_x_min,
-            global_x_max,
-            global_x_avg,
-            global_y_min,
-            global_y_max,
-            global_y_avg,
-        )?;
+        let ctx = Self::get_evalexpr_context(self.x, self.y, self.tag.unwrap(), variables)?;
         let new_x: Value = evalexpr::eval_with_context(x_expr, &ctx)?.try_into()?;
         let new_y: Value = evalexpr::eval_with_context(y_expr, &ctx)?.try_into()?;
         let new_tag = evalexpr::eval_with_context(tag_expr, &ctx)?;
@@ -1019,115 +1346,72 @@ impl XYDatapoint {
         }?;
         let mut new_datapoint = XYDatapoint::new(new_x, new_y).tag(new_tag);
 
-        for c in SUPPORTED_CONFIDENCES {
-            if let Some((x_min, x_max)) = self.x_confidence.get(&c.try_into().unwrap()) {
-                let new_x_min: BencherResult<BencherResult<Value>> = {
-                    let ctx = Self::get_evalexpr_context(
-                        x_min.clone(),
-                        self.y,
-                        self.tag.unwrap(),
-                        global_x_min,
-                        global_x_max,
-                        global_x_avg,
-                        global_y_min,
-                        global_y_max,
-                        global_y_avg,
-                    )?;
-                    evalexpr::eval_with_context(x_expr, &ctx)
-                        .map_err(|e| e.into())
-                        .map(|v| v.try_into())
-                };
-                let new_x_min = new_x_min??;
-
-                let new_x_max: BencherResult<BencherResult<Value>> = {
-                    let ctx = Self::get_evalexpr_context(
-                        x_max.clone(),
-                        self.y,
-                        self.tag.unwrap(),
-                        global_x_min,
-                        global_x_max,
-                        global_x_avg,
-                        global_y_min,
-                        global_y_max,
-                        global_y_avg,
-                    )?;
-                    evalexpr::eval_with_context(x_expr, &ctx)
-                        .map_err(|e| e.into())
-                        .map(|v| v.try_into())
-                };
-                let new_x_max = new_x_max??;
-
-                new_datapoint.add_x_value_confidence(c, (new_x_min, new_x_max));
-            }
+        for &c in self.x_confidence.keys() {
+            let intervals = self.get_intervals(c, variables);
+            let bounds = map_interval(x_expr, &ctx, &intervals, x_monotonicity)?;
+            new_datapoint.add_x_value_confidence(c, bounds);
+        }
 
-            if let Some((y_min, y_max)) = self.y_confidence.get(&c.try_into().unwrap()) {
-                let new_y_min: BencherResult<BencherResult<Value>> = {
-                    let ctx = Self::get_evalexpr_context(
-                        self.x,
-                        y_min.clone(),
-                        self.tag.unwrap(),
-                        global_x_min,
-                        global_x_max,
-                        global_x_avg,
-                        global_y_min,
-                        global_y_max,
-                        global_y_avg,
-                    )?;
-                    evalexpr::eval_with_context(y_expr, &ctx)
-                        .map_err(|e| e.into())
-                        .map(|v| v.try_into())
-                };
-                let new_y_min = new_y_min??;
-
-                let new_y_max: BencherResult<BencherResult<Value>> = {
-                    let ctx = Self::get_evalexpr_context(
-                        self.x,
-                        y_max.clone(),
-                        self.tag.unwrap(),
-                        global_x_min,
-                        global_x_max,
-                        global_x_avg,
-                        global_y_min,
-                        global_y_max,
-                        global_y_avg,
-                    )?;
-                    evalexpr::eval_with_context(y_expr, &ctx)
-                        .map_err(|e| e.into())
-                        .map(|v| v.try_into())
-                };
-                let new_y_max = new_y_max??;
-
-                new_datapoint.add_y_value_confidence(c, (new_y_min, new_y_max));
-            }
+        for &c in self.y_confidence.keys() {
+            let intervals = self.get_intervals(c, variables);
+            let bounds = map_interval(y_expr, &ctx, &intervals, y_monotonicity)?;
+            new_datapoint.add_y_value_confidence(c, bounds);
         }
 
         Ok(new_datapoint)
     }
 }
 
+/// The raw samples behind a bidimensional datapoint
+///
+/// The x and y samples are aggregated independently, so they need not have the same length
+#[derive(Debug, PartialEq, Clone)]
+pub struct XYRawDatapoint {
+    pub x: Sample,
+
+    pub y: Sample,
+
+    pub tag: Option<isize>,
+}
+
+impl XYRawDatapoint {
+    pub fn new(x: Sample, y: Sample) -> Self {
+        XYRawDatapoint { x, y, tag: None }
+    }
+
+    pub fn tag(mut self, tag: isize) -> Self {
+        self.tag = Some(tag);
+        self
+    }
+
+    /// Aggregate the samples into a datapoint (None if either sample is empty)
+    ///
+    /// The confidence intervals at the given percentiles are computed in addition to the default ones
+    pub fn aggregate(
+        &self,
+        aggregation: Aggregation,
+        confidences: &[Confidence],
+    ) -> BencherResult<Option<XYDatapoint>> {
+        let mut x = self.x.clone();
+        let mut y = self.y.clone();
+        let mut datapoint = match XYDatapoint::from_samples(x.as_mut(), y.as_mut(), aggregation) {
+            Some(datapoint) => datapoint,
+            None => return Ok(None),
+        };
+        datapoint.add_sample_confidences(&self.x, &self.y, confidences)?;
+
+        Ok(Some(match self.tag {
+            Some(tag) => datapoint.tag(tag),
+            None => datapoint,
+        }))
+    }
+}
+
 impl std::fmt::Display for XYDatapoint {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        let x_interval = {
-            let mut interval = None;
-            for c in SUPPORTED_CONFIDENCES {
-                if let Some((min, max)) = self.x_confidence.get(&c) {
-                    interval = Some((min, max));
-                    break;
-                }
-            }
-            interval
-        };
+        let x_interval = self.x_confidence.values().next();
 
-        let y_interval = {
-            let mut interval = None;
-            for c in SUPPORTED_CONFIDENCES {
-                if let Some((min, max)) = self.y_confidence.get(&c) {
-                    interval = Some((min, max));
-                    break;
-                }
-            }
-            interval
-        };
+        let y_interval = self.y_confidence.values().next();
 
         match (self.tag, x_interval, y_interval) {
             (None, None, None) => write!(f, "[]({}, {})", self.x, self.y),
@@ -1164,6 +1448,7 @@ mod test {
 
     #[test]
     fn magnitude_prefix() {
+        assert_eq!(Magnitude::Pico.prefix(), "p");
         assert_eq!(Magnitude::Nano.prefix(), "n");
         assert_eq!(Magnitude::Micro.prefix(), "μ");
         assert_eq!(Magnitude::Mili.prefix(), "m");
@@ -1171,6 +1456,8 @@ mod test {
         assert_eq!(Magnitude::Kilo.prefix(), "K");
         assert_eq!(Magnitude::Mega.prefix(), "M");
         assert_eq!(Magnitude::Giga.prefix(), "G");
+        assert_eq!(Magnitude::Tera.prefix(), "T");
+        assert_eq!(Magnitude::Peta.prefix(), "P");
     }
 
     #[test]
@@ -1180,45 +1467,59 @@ mod test {
             assert!(c.is_err())
         };
         {
-            let c: BencherResult<Confidence> = (14 as usize).try_into();
+            let c: BencherResult<Confidence> = 50_usize.try_into();
             assert!(c.is_err())
         };
         {
-            let c: BencherResult<Confidence> = (50 as usize).try_into();
+            let c: BencherResult<Confidence> = 100_usize.try_into();
+            assert!(c.is_err())
+        };
+        {
+            let c: BencherResult<Confidence> = "p99.9999".parse();
             assert!(c.is_err())
         };
 
         {
             let c: Confidence = (1 as usize).try_into().unwrap();
-            assert_eq!(c, Confidence::One)
+            assert_eq!(c, Confidence::ONE)
         };
         {
             let c: Confidence = (5 as usize).try_into().unwrap();
-            assert_eq!(c, Confidence::Five)
+            assert_eq!(c, Confidence::FIVE)
         };
         {
             let c: Confidence = (10 as usize).try_into().unwrap();
-            assert_eq!(c, Confidence::Ten)
+            assert_eq!(c, Confidence::TEN)
         };
         {
             let c: Confidence = (25 as usize).try_into().unwrap();
-            assert_eq!(c, Confidence::TwentyFive)
+            assert_eq!(c, Confidence::TWENTY_FIVE)
         };
         {
             let c: Confidence = (99 as usize).try_into().unwrap();
-            assert_eq!(c, Confidence::One)
+            assert_eq!(c, Confidence::ONE)
         };
         {
             let c: Confidence = (95 as usize).try_into().unwrap();
-            assert_eq!(c, Confidence::Five)
+            assert_eq!(c, Confidence::FIVE)
         };
         {
             let c: Confidence = (90 as usize).try_into().unwrap();
-            assert_eq!(c, Confidence::Ten)
+            assert_eq!(c, Confidence::TEN)
         };
         {
             let c: Confidence = (75 as usize).try_into().unwrap();
-            assert_eq!(c, Confidence::TwentyFive)
+            assert_eq!(c, Confidence::TWENTY_FIVE)
+        };
+        {
+            let c: Confidence = 14_usize.try_into().unwrap();
+            assert_eq!((c.lower(), c.upper()), (14.0, 86.0))
+        };
+        {
+            let c: Confidence = "p99.9".parse().unwrap();
+            assert_eq!(c, "0.1".parse().unwrap());
+            assert_eq!((c.lower(), c.upper()), (0.1, 99.9));
+            assert!(c < Confidence::ONE)
         };
     }
 
@@ -1229,6 +1530,27 @@ mod test {
         assert!(Value::new(None, None).is_err());
     }
 
+    #[test]
+    fn value_from_str() {
+        assert_eq!("1234".parse::<Value>().unwrap(), Value::Int(1234));
+        assert_eq!("-12".parse::<Value>().unwrap(), Value::Int(-12));
+        assert_eq!("5.5".parse::<Value>().unwrap(), Value::Float(5.5));
+        assert_eq!("1e3".parse::<Value>().unwrap(), Value::Float(1000.0));
+        assert!("abc".parse::<Value>().is_err());
+        assert!("".parse::<Value>().is_err());
+    }
+
+    #[test]
+    fn value_from_str_non_finite() {
+        for s in ["NaN", "nan", "inf", "-inf", "infinity", "1e400"] {
+            assert!(
+                matches!(s.parse::<Value>(), Err(BencherError::InvalidValue(v)) if v == s),
+                "{} should not parse",
+                s
+            );
+        }
+    }
+
     #[test]
     fn value_is() {
         assert_eq!(Value::Int(0).is_int(), true);
@@ -1254,7 +1576,13 @@ mod test {
         assert_eq!(Value::Int(1_000_000).magnitude(), Magnitude::Mega);
         assert_eq!(Value::Int(999_999_999).magnitude(), Magnitude::Mega);
         assert_eq!(Value::Int(1_000_000_000).magnitude(), Magnitude::Giga);
-        assert_eq!(Value::Int(1_000_000_000_000).magnitude(), Magnitude::Giga);
+        assert_eq!(Value::Int(999_999_999_999).magnitude(), Magnitude::Giga);
+        assert_eq!(Value::Int(1_000_000_000_000).magnitude(), Magnitude::Tera);
+        assert_eq!(
+            Value::Int(1_000_000_000_000_000).magnitude(),
+            Magnitude::Peta
+        );
+        assert_eq!(Value::Int(i64::MIN).magnitude(), Magnitude::Peta);
 
         assert_eq!(Value::Float(0.0_f64).magnitude(), Magnitude::Normal);
         assert_eq!(Value::Float(9e+2_f64).magnitude(), Magnitude::Normal);
@@ -1264,6 +1592,9 @@ mod test {
         assert_eq!(Value::Float(9e+7_f64).magnitude(), Magnitude::Mega);
         assert_eq!(Value::Float(1e+9_f64).magnitude(), Magnitude::Giga);
         assert_eq!(Value::Float(9e+10_f64).magnitude(), Magnitude::Giga);
+        assert_eq!(Value::Float(1e+12_f64).magnitude(), Magnitude::Tera);
+        assert_eq!(Value::Float(1e+15_f64).magnitude(), Magnitude::Peta);
+        assert_eq!(Value::Float(1e+20_f64).magnitude(), Magnitude::Peta);
 
         assert_eq!(Value::Float(0.1_f64).magnitude(), Magnitude::Mili);
         assert_eq!(Value::Float(0.001_f64).magnitude(), Magnitude::Mili);
@@ -1271,6 +1602,8 @@ mod test {
         assert_eq!(Value::Float(0.000001_f64).magnitude(), Magnitude::Micro);
         assert_eq!(Value::Float(0.0000001_f64).magnitude(), Magnitude::Nano);
         assert_eq!(Value::Float(0.000000001_f64).magnitude(), Magnitude::Nano);
+        assert_eq!(Value::Float(1e-10_f64).magnitude(), Magnitude::Pico);
+        assert_eq!(Value::Float(1e-15_f64).magnitude(), Magnitude::Pico);
 
         assert_eq!(Value::Float(-1e+6_f64).magnitude(), Magnitude::Mega);
         assert_eq!(Value::Float(-9e+10_f64).magnitude(), Magnitude::Giga);
@@ -1282,6 +1615,34 @@ mod test {
         assert_eq!(Value::Int(-1_000_000_000).magnitude(), Magnitude::Giga);
     }
 
+    #[test]
+    fn value_display_with_magnitude() {
+        assert_eq!(
+            Value::Int(3).display_with_magnitude(Magnitude::Nano),
+            "3000000000"
+        );
+        assert_eq!(
+            Value::Int(3).display_with_magnitude(Magnitude::Pico),
+            "3000000000000"
+        );
+        assert_eq!(
+            Value::Int(i64::MAX / 1000).display_with_magnitude(Magnitude::Nano),
+            "9223372036854775000000000"
+        );
+        assert_eq!(
+            Value::Int(2_500_000_000_000).display_with_magnitude(Magnitude::Tera),
+            "2.5"
+        );
+        assert_eq!(
+            Value::Float(2.5e15).display_with_magnitude(Magnitude::Peta),
+            "2.5"
+        );
+        assert_eq!(
+            Value::Float(3e-12).display_with_magnitude(Magnitude::Pico),
+            "3.00"
+        );
+    }
+
     #[test]
     fn linear_datapoint_magnitudes() {
         assert_eq!(
@@ -1322,6 +1683,46 @@ mod test {
         );
     }
 
+    #[test]
+    fn sample_from_mixed_values() {
+        assert_eq!(
+            sample_from_values(vec![Value::Int(1), Value::Int(2)]),
+            Either::Left(vec![1, 2])
+        );
+        assert_eq!(
+            sample_from_values(vec![Value::Int(1), Value::Float(2.5)]),
+            Either::Right(vec![1.0, 2.5])
+        );
+    }
+
+    #[test]
+    fn linear_raw_datapoint_aggregate() {
+        let raw = LinearRawDatapoint::new("", Either::Left((0..100).rev().collect())).tag(3);
+        let p999: Confidence = "99.9".parse().unwrap();
+        let median = raw
+            .aggregate(Aggregation::Median, &[p999])
+            .unwrap()
+            .unwrap();
+        assert_eq!(median.v, Value::Int(50));
+        assert_eq!(median.tag, Some(3));
+        assert_eq!(
+            median.get_confidence(Confidence::FIVE),
+            Some((Value::Int(5), Value::Int(95)))
+        );
+        assert_eq!(
+            median.get_confidence(p999),
+            Some((Value::Int(1), Value::Int(99)))
+        );
+
+        let avg = raw.aggregate(Aggregation::Avg, &[]).unwrap().unwrap();
+        assert_eq!(avg.v, Value::Int(49));
+
+        assert!(LinearRawDatapoint::new("", Either::Left(vec![]))
+            .aggregate(Aggregation::Median, &[])
+            .unwrap()
+            .is_none());
+    }
+
     #[test]
     fn xy_datapoint_tag() {
         assert_eq!(XYDatapoint::new(Value::Int(0), Value::Int(0)).tag, None);
@@ -1419,4 +1820,68 @@ mod test {
         assert_eq!(x_datapoint, datapoint.x_linear(""));
         assert_eq!(y_datapoint, datapoint.y_linear(""));
     }
+
+    #[test]
+    fn linear_datapoint_map_expression_intervals() {
+        let mut datapoint = LinearDatapoint::new("", Value::Float(2.0)).tag(0);
+        datapoint.add_value_confidence(Confidence::FIVE, (Value::Float(1.0), Value::Float(4.0)));
+        let variables = ExpressionVariables::new();
+
+        // decreasing: the bounds are swapped
+        let mapped = datapoint
+            .map_expression(Some("1.0 / v"), None, &variables, None)
+            .unwrap();
+        assert_eq!(mapped.v, Value::Float(0.5));
+        assert_eq!(
+            mapped.get_confidence(Confidence::FIVE),
+            Some((Value::Float(0.25), Value::Float(1.0)))
+        );
+
+        // non-monotonic: interval arithmetic
+        let mapped = datapoint
+            .map_expression(Some("(v - 2.0)^2"), None, &variables, None)
+            .unwrap();
+        assert_eq!(
+            mapped.get_confidence(Confidence::FIVE),
+            Some((Value::Float(0.0), Value::Float(4.0)))
+        );
+
+        // declared monotonicity
+        let mapped = datapoint
+            .map_expression(
+                Some("v * 3"),
+                None,
+                &variables,
+                Some(Monotonicity::Increasing),
+            )
+            .unwrap();
+        assert_eq!(
+            mapped.get_confidence(Confidence::FIVE),
+            Some((Value::Float(3.0), Value::Float(12.0)))
+        );
+    }
+
+    #[test]
+    fn linear_datapoint_map_expression_variables() {
+        let mut datapoint = LinearDatapoint::new("", Value::Float(2.0)).tag(0);
+        datapoint.add_value_confidence(Confidence::FIVE, (Value::Float(1.0), Value::Float(4.0)));
+        let mut baseline = LinearDatapoint::new("", Value::Float(4.0));
+        baseline.add_value_confidence(Confidence::FIVE, (Value::Float(2.0), Value::Float(8.0)));
+        let variables = baseline.expression_variables("baseline");
+
+        // increasing in v, decreasing in baseline
+        let mapped = datapoint
+            .map_expression(Some("v / baseline"), None, &variables, None)
+            .unwrap();
+        assert_eq!(mapped.v, Value::Float(0.5));
+        assert_eq!(
+            mapped.get_confidence(Confidence::FIVE),
+            Some((Value::Float(0.125), Value::Float(2.0)))
+        );
+
+        // division by an interval containing 0
+        assert!(datapoint
+            .map_expression(Some("1.0 / (v - 2.0) + v^2"), None, &variables, None)
+            .is_err());
+    }
 }